@@ -0,0 +1,333 @@
+//! Persist benchmark results of devices and compare new runs against them
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{DeviceFingerprint, DeviceInfo};
+
+/// A single measurement, eg. the runtime of a kernel
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct BenchmarkResult {
+    /// Name of the benchmark
+    pub name: String,
+    /// Measured value
+    pub value: f64,
+    /// Unit of the value, eg. `ms` or `GB/s`
+    pub unit: String,
+    /// Whether larger values are better, eg. for throughput as opposed to runtime
+    pub higher_is_better: bool,
+}
+
+/// All results of one run on one device
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct BenchmarkRecord {
+    /// The benchmarked device
+    pub device: DeviceFingerprint,
+    /// Version of the driver at the time of the run, empty if it was not queried
+    pub driver_version: String,
+    /// Time of the run in seconds since the unix epoch
+    pub timestamp: u64,
+    /// Results of the run
+    pub results: Vec<BenchmarkResult>,
+}
+
+/// Change of a benchmark between the stored baseline and a new run
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkDelta {
+    /// Name of the benchmark
+    pub name: String,
+    /// Unit of both values
+    pub unit: String,
+    /// Value of the baseline
+    pub baseline: f64,
+    /// Value of the new run
+    pub current: f64,
+    /// Whether larger values are better
+    pub higher_is_better: bool,
+    /// Driver version of the baseline
+    pub baseline_driver: String,
+    /// Driver version of the new run
+    pub current_driver: String,
+}
+
+impl BenchmarkDelta {
+    /// Relative change from the baseline, eg. `0.1` if the value grew by 10%
+    pub fn relative(&self) -> f64 {
+        (self.current - self.baseline) / self.baseline.abs()
+    }
+
+    /// Checks if the value got worse by more than the relative tolerance
+    pub fn is_regression(&self, tolerance: f64) -> bool {
+        match self.higher_is_better {
+            true => self.relative() < -tolerance,
+            false => self.relative() > tolerance,
+        }
+    }
+
+    /// Checks if the driver was updated since the baseline
+    pub fn driver_changed(&self) -> bool {
+        self.baseline_driver != self.current_driver
+    }
+}
+
+impl std::fmt::Display for BenchmarkDelta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} {unit} -> {} {unit} ({:+.1}%)",
+            self.name,
+            self.baseline,
+            self.current,
+            100.0 * self.relative(),
+            unit = self.unit
+        )?;
+        if self.driver_changed() {
+            write!(
+                f,
+                " after driver update {} -> {}",
+                self.baseline_driver, self.current_driver
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Benchmark runs of all devices, newest last
+///
+/// Runs are keyed by the [DeviceFingerprint] of the device and the version of its driver such
+/// that regressions after driver updates can be spotted.
+#[cfg_attr(feature = "serde", doc = "```")]
+#[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+/// use opencl3_select::{BenchmarkHistory, BenchmarkResult, DeviceInfo};
+/// let device: DeviceInfo = serde_json::from_str(r#"{
+///     "platform": "PoCL", "vendor": "AuthenticAMD", "vendor_id": 4098, "vendor_id_text": "AMD",
+///     "name": "cpu-haswell", "version": "OpenCL 3.0 PoCL", "type": 2,
+///     "type_text": "CL_DEVICE_TYPE_CPU", "driver_version": "5.0"
+/// }"#)?;
+/// let runtime = |value| BenchmarkResult {
+///     name: "saxpy".into(),
+///     value,
+///     unit: "ms".into(),
+///     higher_is_better: false,
+/// };
+/// let mut history = BenchmarkHistory::default();
+/// assert!(history.record(&device, vec![runtime(2.0)]).is_empty());
+/// let deltas = history.record(&device, vec![runtime(2.5)]);
+/// assert!(deltas[0].is_regression(0.05));
+/// assert_eq!(deltas[0].to_string(), "saxpy: 2 ms -> 2.5 ms (+25.0%)");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(default))]
+pub struct BenchmarkHistory {
+    /// Stored runs ordered from oldest to newest
+    pub records: Vec<BenchmarkRecord>,
+}
+
+impl BenchmarkHistory {
+    /// Loads the runs from the given path or returns no runs if it does not exist
+    #[cfg(feature = "storage")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage")))]
+    pub fn load_or_default(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        match path.exists() {
+            true => crate::load(path),
+            false => Ok(Self::default()),
+        }
+    }
+
+    /// Most recent run of the device, regardless of its driver version
+    pub fn baseline(&self, device: &DeviceInfo) -> Option<&BenchmarkRecord> {
+        self.records
+            .iter()
+            .rev()
+            .find(|record| record.device.matches(device))
+    }
+
+    /// Most recent run of the device with the given driver version
+    pub fn baseline_with_driver(
+        &self,
+        device: &DeviceInfo,
+        driver_version: &str,
+    ) -> Option<&BenchmarkRecord> {
+        self.records
+            .iter()
+            .rev()
+            .find(|record| record.device.matches(device) && record.driver_version == driver_version)
+    }
+
+    /// Changes of the results compared to the [baseline](BenchmarkHistory::baseline)
+    ///
+    /// Benchmarks which are not part of the baseline are skipped.
+    pub fn compare(&self, device: &DeviceInfo, results: &[BenchmarkResult]) -> Vec<BenchmarkDelta> {
+        let Some(baseline) = self.baseline(device) else {
+            return Vec::new();
+        };
+        let current_driver = device.driver_version().unwrap_or_default();
+        results
+            .iter()
+            .filter_map(|result| {
+                let previous = baseline
+                    .results
+                    .iter()
+                    .find(|previous| previous.name == result.name)?;
+                Some(BenchmarkDelta {
+                    name: result.name.clone(),
+                    unit: result.unit.clone(),
+                    baseline: previous.value,
+                    current: result.value,
+                    higher_is_better: result.higher_is_better,
+                    baseline_driver: baseline.driver_version.clone(),
+                    current_driver: current_driver.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Stores a new run of the device and returns its changes compared to the previous one
+    pub fn record(
+        &mut self,
+        device: &DeviceInfo,
+        results: Vec<BenchmarkResult>,
+    ) -> Vec<BenchmarkDelta> {
+        let deltas = self.compare(device, &results);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        self.records.push(BenchmarkRecord {
+            device: device.fingerprint(),
+            driver_version: device.driver_version().unwrap_or_default(),
+            timestamp,
+            results,
+        });
+        deltas
+    }
+}
+
+/// Progress reported while benchmarks run in the background, see [BenchmarkProgress]
+#[derive(Clone, Debug, PartialEq)]
+pub enum BenchmarkEvent {
+    /// A benchmark started on the device with the given index
+    Started {
+        /// Index of the device in the list of benchmarked devices
+        device: usize,
+        /// Name of the benchmark
+        benchmark: String,
+    },
+    /// A benchmark finished on the device with the given index
+    Finished {
+        /// Index of the device in the list of benchmarked devices
+        device: usize,
+        /// Measurement of the benchmark
+        result: BenchmarkResult,
+    },
+    /// A benchmark could not be run on the device with the given index
+    Failed {
+        /// Index of the device in the list of benchmarked devices
+        device: usize,
+        /// Name of the benchmark
+        benchmark: String,
+        /// Description of the error
+        message: String,
+    },
+}
+
+impl BenchmarkEvent {
+    /// Index of the device the event refers to
+    pub fn device(&self) -> usize {
+        match self {
+            Self::Started { device, .. }
+            | Self::Finished { device, .. }
+            | Self::Failed { device, .. } => *device,
+        }
+    }
+}
+
+/// Benchmarks of one device which are done or still running
+/// ```
+/// use opencl3_select::{BenchmarkEvent, BenchmarkProgress, BenchmarkResult};
+/// let mut progress = BenchmarkProgress::new(2);
+/// progress.apply(BenchmarkEvent::Started { device: 0, benchmark: "saxpy".into() });
+/// assert_eq!(progress.current.as_deref(), Some("saxpy"));
+/// progress.apply(BenchmarkEvent::Finished {
+///     device: 0,
+///     result: BenchmarkResult {
+///         name: "saxpy".into(),
+///         value: 1.5,
+///         unit: "ms".into(),
+///         higher_is_better: false,
+///     },
+/// });
+/// assert_eq!(progress.gauge(4), "▰▰▱▱ 1/2");
+/// assert!(!progress.is_done());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BenchmarkProgress {
+    /// Number of benchmarks which are run on the device
+    pub total: usize,
+    /// Name of the running benchmark
+    pub current: Option<String>,
+    /// Results of the finished benchmarks
+    pub results: Vec<BenchmarkResult>,
+    /// Names of the failed benchmarks together with their errors
+    pub failures: Vec<(String, String)>,
+}
+
+impl BenchmarkProgress {
+    /// No benchmark started yet out of `total`
+    pub fn new(total: usize) -> Self {
+        Self {
+            total,
+            ..Default::default()
+        }
+    }
+
+    /// Number of benchmarks which finished or failed
+    pub fn completed(&self) -> usize {
+        self.results.len() + self.failures.len()
+    }
+
+    /// Fraction of the benchmarks which are completed
+    pub fn fraction(&self) -> f64 {
+        match self.total {
+            0 => 1.0,
+            total => self.completed() as f64 / total as f64,
+        }
+    }
+
+    /// Checks if all benchmarks are completed
+    pub fn is_done(&self) -> bool {
+        self.completed() >= self.total
+    }
+
+    /// Updates the progress with an event of this device
+    pub fn apply(&mut self, event: BenchmarkEvent) {
+        match event {
+            BenchmarkEvent::Started { benchmark, .. } => self.current = Some(benchmark),
+            BenchmarkEvent::Finished { result, .. } => {
+                self.current = None;
+                self.results.push(result);
+            }
+            BenchmarkEvent::Failed {
+                benchmark, message, ..
+            } => {
+                self.current = None;
+                self.failures.push((benchmark, message));
+            }
+        }
+    }
+
+    /// Bar of `width` cells filled according to the completed benchmarks, eg. `▰▰▱▱ 1/2`
+    pub fn gauge(&self, width: usize) -> String {
+        let filled = (self.fraction().clamp(0.0, 1.0) * width as f64).round() as usize;
+        format!(
+            "{}{} {}/{}",
+            "▰".repeat(filled),
+            "▱".repeat(width - filled),
+            self.completed(),
+            self.total
+        )
+    }
+}
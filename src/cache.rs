@@ -0,0 +1,285 @@
+//! Cache of the enumerated setup which is invalidated when the installed drivers change
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{ClState, HostInfo, Result, StorageError};
+
+/// Directories searched for ICD files unless `OCL_ICD_VENDORS` or `OPENCL_VENDOR_PATH` is set
+const VENDOR_DIRS: [&str; 2] = ["/etc/OpenCL/vendors", "/usr/local/etc/OpenCL/vendors"];
+
+/// Directories searched for libraries which ICD files name without a path, after
+/// `LD_LIBRARY_PATH` and the directories configured for the dynamic linker
+const LIBRARY_DIRS: [&str; 8] = [
+    "/lib",
+    "/lib64",
+    "/usr/lib",
+    "/usr/lib64",
+    "/usr/local/lib",
+    "/usr/lib/x86_64-linux-gnu",
+    "/usr/lib/aarch64-linux-gnu",
+    "/usr/lib/i386-linux-gnu",
+];
+
+/// Files whose contents reveal the version of a loaded kernel driver
+const DRIVER_VERSION_FILES: [&str; 2] =
+    ["/proc/driver/nvidia/version", "/sys/module/amdgpu/version"];
+
+/// Hash of the installed OpenCL drivers
+///
+/// Covers the ICD files which register the drivers with the ICD loader, the size and
+/// modification time of the libraries they name, the versions of loaded kernel drivers and the
+/// version of the ICD loader. Libraries named without a path are looked up like the dynamic
+/// linker does. Installing, removing or upgrading a driver thus changes the
+/// fingerprint without enumerating any device. Windows registers drivers in the registry, which
+/// is not covered.
+/// ```
+/// use opencl3_select::DriverFingerprint;
+/// let vendors = std::env::temp_dir().join("opencl3_select_doctest_vendors");
+/// std::fs::create_dir_all(&vendors).unwrap();
+/// std::fs::write(vendors.join("pocl.icd"), "libpocl.so.2\n").unwrap();
+/// let before = DriverFingerprint::of_vendor_dirs(&[&vendors]);
+/// assert_eq!(before, DriverFingerprint::of_vendor_dirs(&[&vendors]));
+///
+/// std::fs::write(vendors.join("nvidia.icd"), "libnvidia-opencl.so.1\n").unwrap();
+/// assert_ne!(before, DriverFingerprint::of_vendor_dirs(&[&vendors]));
+///
+/// // Upgrades which replace the library under the same name are noticed as well
+/// std::env::set_var("LD_LIBRARY_PATH", &vendors);
+/// std::fs::write(vendors.join("libnvidia-opencl.so.1"), "550").unwrap();
+/// let old = DriverFingerprint::of_vendor_dirs(&[&vendors]);
+/// std::fs::write(vendors.join("libnvidia-opencl.so.1"), "550.1").unwrap();
+/// assert_ne!(old, DriverFingerprint::of_vendor_dirs(&[&vendors]));
+/// # std::fs::remove_dir_all(&vendors).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct DriverFingerprint(pub u64);
+
+impl DriverFingerprint {
+    /// Fingerprint of the drivers installed on this machine
+    ///
+    /// ICD files are searched in the same directories as the ICD loader does.
+    pub fn current() -> Self {
+        let dirs: Vec<PathBuf> = match std::env::var_os("OCL_ICD_VENDORS")
+            .or_else(|| std::env::var_os("OPENCL_VENDOR_PATH"))
+        {
+            Some(dir) => vec![dir.into()],
+            None => VENDOR_DIRS.iter().map(PathBuf::from).collect(),
+        };
+        Self::of_vendor_dirs(&dirs)
+    }
+
+    /// Fingerprint of the drivers registered by the ICD files in the given directories
+    pub fn of_vendor_dirs(dirs: &[impl AsRef<Path>]) -> Self {
+        let mut contents = Vec::new();
+        for dir in dirs {
+            let mut icds: Vec<_> = std::fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "icd"))
+                .collect();
+            icds.sort();
+            for icd in icds {
+                let library = std::fs::read_to_string(&icd).unwrap_or_default();
+                contents.extend(icd.to_string_lossy().bytes());
+                contents.extend(library.bytes());
+                // Upgrades often replace the library but keep the ICD file
+                if let Some(metadata) =
+                    resolve_library(library.trim()).and_then(|path| std::fs::metadata(path).ok())
+                {
+                    contents.extend(metadata.len().to_le_bytes());
+                    let modified = metadata
+                        .modified()
+                        .ok()
+                        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                        .map_or(0, |duration| duration.as_nanos());
+                    contents.extend(modified.to_le_bytes());
+                }
+            }
+        }
+        for file in DRIVER_VERSION_FILES {
+            contents.extend(std::fs::read(file).unwrap_or_default());
+        }
+        if let Some(filenames) = std::env::var_os("OCL_ICD_FILENAMES") {
+            contents.extend(filenames.to_string_lossy().bytes());
+        }
+        contents.extend(
+            HostInfo::current()
+                .icd_loader_version
+                .unwrap_or_default()
+                .bytes(),
+        );
+        Self(crate::storage::checksum(&contents))
+    }
+}
+
+/// Location of the library as the dynamic linker would find it
+///
+/// Names with a path are taken as they are, bare names such as `libnvidia-opencl.so.1` are
+/// searched in `LD_LIBRARY_PATH`, the directories of `/etc/ld.so.conf.d` and [LIBRARY_DIRS].
+fn resolve_library(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    if name.is_empty() || path.components().count() > 1 {
+        return path.exists().then(|| path.to_path_buf());
+    }
+    let mut dirs: Vec<PathBuf> = std::env::var_os("LD_LIBRARY_PATH")
+        .map(|dirs| std::env::split_paths(&dirs).collect())
+        .unwrap_or_default();
+    let mut configs: Vec<_> = std::fs::read_dir("/etc/ld.so.conf.d")
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "conf")
+        })
+        .collect();
+    configs.sort();
+    for config in configs {
+        let contents = std::fs::read_to_string(config).unwrap_or_default();
+        dirs.extend(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| line.starts_with('/'))
+                .map(PathBuf::from),
+        );
+    }
+    dirs.extend(LIBRARY_DIRS.iter().map(PathBuf::from));
+    dirs.into_iter()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+impl std::fmt::Display for DriverFingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Contents of the cache file
+#[derive(Deserialize, Serialize)]
+struct CachedState {
+    /// Drivers which were installed when the state was enumerated
+    fingerprint: DriverFingerprint,
+    /// Seconds since the Unix epoch at which the state was stored
+    created: u64,
+    /// The enumerated platforms and devices
+    state: ClState,
+}
+
+/// Stores the enumerated [ClState] in a file to skip the enumeration on later runs
+///
+/// The cached state is discarded as soon as the [DriverFingerprint] changes, ie. after drivers
+/// were installed, removed or upgraded, and optionally after a maximum age. The format of the
+/// file is taken from its extension.
+/// ```
+/// use opencl3_select::{ClSelectError, ClState, EnumerationCache, PlatformInfo, StorageError};
+/// use std::time::Duration;
+/// let path = std::env::temp_dir().join("opencl3_select_doctest_cache.json");
+/// let cache = EnumerationCache::new(&path);
+/// let state = ClState::from_platforms(vec![PlatformInfo::builder().name("PoCL").build()]);
+/// cache.store(&state)?;
+/// assert_eq!(cache.load()?.platforms_ref()[0].name(), "PoCL");
+///
+/// let expired = EnumerationCache::new(&path).ttl(Duration::ZERO);
+/// std::thread::sleep(Duration::from_secs(1));
+/// assert!(matches!(
+///     expired.load(),
+///     Err(ClSelectError::Storage(StorageError::CacheStale(_)))
+/// ));
+/// # Ok::<(), ClSelectError>(())
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EnumerationCache {
+    /// File in which the state is stored
+    path: PathBuf,
+    /// Maximum age of the cached state, unlimited if `None`
+    ttl: Option<Duration>,
+}
+
+impl EnumerationCache {
+    /// Cache stored in the given file which is only invalidated by driver changes
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            ttl: None,
+        }
+    }
+
+    /// Additionally discards the cached state once it is older than `ttl`
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// The cached state if it is still valid
+    ///
+    /// Fails with [StorageError::CacheStale] if the drivers changed or the state expired.
+    pub fn load(&self) -> Result<ClState> {
+        let cached: CachedState = crate::load(&self.path)?;
+        let fingerprint = DriverFingerprint::current();
+        let age = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs())
+            .saturating_sub(cached.created);
+        let expired = self.ttl.is_some_and(|ttl| Duration::from_secs(age) > ttl);
+        if cached.fingerprint != fingerprint || expired {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                path = ?self.path,
+                cached = %cached.fingerprint,
+                current = %fingerprint,
+                age,
+                "cached state is stale"
+            );
+            return Err(StorageError::CacheStale(self.path.clone()).into());
+        }
+        Ok(cached.state)
+    }
+
+    /// Stores the state together with the fingerprint of the current drivers
+    pub fn store(&self, state: &ClState) -> Result<()> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(StorageError::Io)?;
+        }
+        let cached = CachedState {
+            fingerprint: DriverFingerprint::current(),
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            state: state.clone(),
+        };
+        crate::save(&cached, &self.path)
+    }
+
+    /// The cached state if it is still valid and otherwise the freshly enumerated one, which
+    /// is then cached
+    ///
+    /// A cache file which can not be read or decoded, eg. after an update of this crate changed
+    /// the stored types, is replaced like a stale one.
+    /// ```no_run
+    /// use opencl3_select::EnumerationCache;
+    /// let cache = EnumerationCache::new(std::env::temp_dir().join("opencl3_select.json"));
+    /// let cl_state = cache.get_setup()?;
+    /// # Ok::<(), opencl3_select::ClSelectError>(())
+    /// ```
+    #[cfg(feature = "opencl")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "opencl")))]
+    pub fn get_setup(&self) -> Result<ClState> {
+        if self.path.exists() {
+            match self.load() {
+                Ok(cl_state) => return Ok(cl_state),
+                #[cfg(feature = "tracing")]
+                Err(error) => tracing::debug!(path = ?self.path, %error, "cache miss"),
+                #[cfg(not(feature = "tracing"))]
+                Err(_) => {}
+            }
+        }
+        let cl_state = crate::get_setup()?;
+        self.store(&cl_state)?;
+        Ok(cl_state)
+    }
+}
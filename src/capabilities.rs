@@ -0,0 +1,251 @@
+//! Group raw extension names into user-facing capabilities
+
+use crate::DeviceInfo;
+
+/// A user-facing capability which is provided by any of several extensions
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Capability {
+    /// Human-readable label, eg. "Double precision"
+    pub label: &'static str,
+    /// Extensions of which at least one has to be supported
+    pub extensions: &'static [&'static str],
+}
+
+/// Sharing of buffers and textures with OpenGL
+pub const GL_SHARING: Capability = Capability {
+    label: "OpenGL sharing",
+    extensions: &["cl_khr_gl_sharing", "cl_apple_gl_sharing"],
+};
+
+/// Creating images from EGL images, eg. on Android and embedded Linux
+pub const EGL_SHARING: Capability = Capability {
+    label: "EGL image sharing",
+    extensions: &["cl_khr_egl_image"],
+};
+
+/// Sharing of buffers and textures with Direct3D 11
+pub const D3D11_SHARING: Capability = Capability {
+    label: "Direct3D 11 sharing",
+    extensions: &["cl_khr_d3d11_sharing", "cl_nv_d3d11_sharing"],
+};
+
+/// All known capabilities in the order in which they are displayed
+pub const CAPABILITIES: &[Capability] = &[
+    Capability {
+        label: "Double precision",
+        extensions: &["cl_khr_fp64", "cl_amd_fp64"],
+    },
+    Capability {
+        label: "Half precision",
+        extensions: &["cl_khr_fp16"],
+    },
+    Capability {
+        label: "64-bit atomics",
+        extensions: &["cl_khr_int64_base_atomics", "cl_khr_int64_extended_atomics"],
+    },
+    Capability {
+        label: "Subgroups",
+        extensions: &["cl_khr_subgroups", "cl_intel_subgroups"],
+    },
+    Capability {
+        label: "Unified shared memory",
+        extensions: &["cl_intel_unified_shared_memory"],
+    },
+    Capability {
+        label: "Device-side enqueue",
+        extensions: &["cl_khr_device_enqueue_local_arg_types"],
+    },
+    Capability {
+        label: "SPIR-V ingestion",
+        extensions: &[
+            "cl_khr_il_program",
+            "cl_khr_spirv_no_integer_wrap_decoration",
+        ],
+    },
+    Capability {
+        label: "Integer dot product",
+        extensions: &["cl_khr_integer_dot_product"],
+    },
+    Capability {
+        label: "3D image writes",
+        extensions: &["cl_khr_3d_image_writes"],
+    },
+    GL_SHARING,
+    EGL_SHARING,
+    D3D11_SHARING,
+    Capability {
+        label: "External memory",
+        extensions: &["cl_khr_external_memory"],
+    },
+];
+
+impl Capability {
+    /// Checks if the space-separated list of extensions provides this capability
+    /// ```
+    /// use opencl3_select::CAPABILITIES;
+    /// let double_precision = &CAPABILITIES[0];
+    /// assert!(double_precision.is_provided_by("cl_khr_icd cl_khr_fp64"));
+    /// assert!(!double_precision.is_provided_by("cl_khr_icd cl_khr_fp16"));
+    /// ```
+    pub fn is_provided_by(&self, extensions: &str) -> bool {
+        extensions
+            .split_whitespace()
+            .any(|extension| self.extensions.contains(&extension))
+    }
+}
+
+impl DeviceInfo {
+    /// All [CAPABILITIES] together with whether the device supports them
+    pub fn capabilities(&self) -> Vec<(&'static Capability, bool)> {
+        let extensions = self.extensions_ref().unwrap_or_default();
+        CAPABILITIES
+            .iter()
+            .map(|capability| (capability, capability.is_provided_by(extensions)))
+            .collect()
+    }
+
+    /// Checks if the device supports an extension, capability or OpenCL C feature
+    ///
+    /// The requirement is either the full name of an extension, the name without its
+    /// `cl_<vendor>_` prefix such as `fp64`, the label of one of the [CAPABILITIES] or an
+    /// OpenCL C feature macro such as `__opencl_c_generic_address_space`.
+    /// ```
+    /// use opencl3_select::{DeviceInfo, NameVersion, NumericVersion};
+    /// let feature = NameVersion {
+    ///     name: "__opencl_c_generic_address_space".into(),
+    ///     version: NumericVersion { major: 3, minor: 0, patch: 0 },
+    /// };
+    /// let device = DeviceInfo::builder()
+    ///     .extensions("cl_khr_fp64")
+    ///     .opencl_c_features(vec![feature])
+    ///     .build();
+    /// assert!(device.supports("fp64"));
+    /// assert!(device.supports("__opencl_c_generic_address_space"));
+    /// assert!(!device.supports("__opencl_c_pipes"));
+    /// ```
+    pub fn supports(&self, requirement: &str) -> bool {
+        if requirement.starts_with("__opencl_c_") {
+            return self.has_opencl_c_feature(requirement) == Some(true);
+        }
+        let extensions = self.extensions_ref().unwrap_or_default();
+        let suffix = format!("_{requirement}");
+        extensions
+            .split_whitespace()
+            .any(|extension| extension == requirement || extension.ends_with(&suffix))
+            || CAPABILITIES.iter().any(|capability| {
+                capability.label.eq_ignore_ascii_case(requirement)
+                    && capability.is_provided_by(extensions)
+            })
+    }
+
+    /// Checks if the compiler of the device defines the OpenCL C feature macro, eg.
+    /// `__opencl_c_fp64`, `None` if the features were not queried
+    ///
+    /// Devices before OpenCL 3.0 report no features, even if they support the functionality.
+    pub fn has_opencl_c_feature(&self, feature: &str) -> Option<bool> {
+        self.opencl_c_features_ref()
+            .map(|features| features.iter().any(|known| known.name == feature))
+    }
+
+    /// Values of the `-cl-std` build option the compiler of the device accepts, from the
+    /// oldest to the newest version, eg. `["-cl-std=CL1.2", "-cl-std=CL3.0"]`
+    ///
+    /// Taken from `CL_DEVICE_OPENCL_C_ALL_VERSIONS` and otherwise from the newest version in
+    /// `CL_DEVICE_OPENCL_C_VERSION`. OpenCL C 1.0 has no option of its own.
+    /// ```
+    /// use opencl3_select::{DeviceInfo, NameVersion, NumericVersion};
+    /// let version = |major, minor| NameVersion {
+    ///     name: "OpenCL C".into(),
+    ///     version: NumericVersion { major, minor, patch: 0 },
+    /// };
+    /// let device = DeviceInfo::builder()
+    ///     .opencl_c_all_versions(vec![version(3, 0), version(1, 2), version(1, 1), version(1, 0)])
+    ///     .build();
+    /// assert_eq!(
+    ///     device.cl_std_options(),
+    ///     ["-cl-std=CL1.1", "-cl-std=CL1.2", "-cl-std=CL3.0"]
+    /// );
+    /// let legacy = DeviceInfo::builder().opencl_c_version("OpenCL C 1.2 CUDA").build();
+    /// assert_eq!(legacy.cl_std_options(), ["-cl-std=CL1.2"]);
+    /// ```
+    pub fn cl_std_options(&self) -> Vec<String> {
+        let mut versions: Vec<(u32, u32)> = match self.opencl_c_all_versions_ref() {
+            Some(versions) => versions
+                .iter()
+                .map(|version| (version.version.major, version.version.minor))
+                .collect(),
+            None => self
+                .opencl_c_version_ref()
+                .and_then(|version| version.strip_prefix("OpenCL C "))
+                .and_then(|version| version.split_whitespace().next())
+                .and_then(|version| version.split_once('.'))
+                .and_then(|(major, minor)| Some((major.parse().ok()?, minor.parse().ok()?)))
+                .into_iter()
+                .collect(),
+        };
+        versions.sort();
+        versions.dedup();
+        versions
+            .into_iter()
+            .filter(|&version| version >= (1, 1))
+            .map(|(major, minor)| format!("-cl-std=CL{major}.{minor}"))
+            .collect()
+    }
+
+    /// Checks if the device provides the capability, `None` if the extensions were not queried
+    pub fn provides(&self, capability: &Capability) -> Option<bool> {
+        self.extensions_ref()
+            .map(|extensions| capability.is_provided_by(extensions))
+    }
+
+    /// Checks if the device can share objects with OpenGL, see [GL_SHARING]
+    pub fn gl_sharing(&self) -> Option<bool> {
+        self.provides(&GL_SHARING)
+    }
+
+    /// Checks if the device can create images from EGL images, see [EGL_SHARING]
+    pub fn egl_sharing(&self) -> Option<bool> {
+        self.provides(&EGL_SHARING)
+    }
+
+    /// Checks if the device can share objects with Direct3D 11, see [D3D11_SHARING]
+    pub fn d3d11_sharing(&self) -> Option<bool> {
+        self.provides(&D3D11_SHARING)
+    }
+
+    /// Short names of the graphics APIs the device can share objects with, eg. `["GL", "EGL"]`
+    /// ```
+    /// use opencl3_select::DeviceInfo;
+    /// let device = DeviceInfo::builder()
+    ///     .extensions("cl_khr_fp64 cl_khr_gl_sharing cl_khr_egl_image")
+    ///     .build();
+    /// assert_eq!(device.gl_sharing(), Some(true));
+    /// assert_eq!(device.d3d11_sharing(), Some(false));
+    /// assert_eq!(device.interop_apis(), ["GL", "EGL"]);
+    /// ```
+    pub fn interop_apis(&self) -> Vec<&'static str> {
+        [
+            ("GL", self.gl_sharing()),
+            ("EGL", self.egl_sharing()),
+            ("D3D11", self.d3d11_sharing()),
+        ]
+        .into_iter()
+        .filter(|(_, supported)| *supported == Some(true))
+        .map(|(api, _)| api)
+        .collect()
+    }
+
+    /// Extensions of the device which are not part of any of the [CAPABILITIES]
+    pub fn uncategorized_extensions(&self) -> Vec<String> {
+        self.extensions_ref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .filter(|extension| {
+                !CAPABILITIES
+                    .iter()
+                    .any(|capability| capability.extensions.contains(extension))
+            })
+            .map(String::from)
+            .collect()
+    }
+}
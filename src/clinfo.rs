@@ -1,8 +1,96 @@
-use opencl3::device::{device_type_text, CL_DEVICE_TYPE_ALL};
-use opencl3::error_codes::ClError;
+//! Query and store information about available platforms and devices
+
+#[cfg(feature = "opencl")]
+use crate::{error::query, ClSelectError, ClinfoError};
+use crate::{
+    AffinityDomains, AtomicCapabilities, CpuInfo, HostInfo, ImageFormat, NameVersion,
+    NumericVersion, PartitionScheme, SvmCapabilities,
+};
+#[cfg(not(feature = "opencl"))]
+pub(crate) use cl_types::*;
+#[cfg(feature = "opencl")]
+use opencl3::device::{device_type_text, vendor_id_text, CL_DEVICE_TYPE_ALL};
+#[cfg(feature = "opencl")]
+pub(crate) use opencl3::{
+    device::{
+        cl_device_svm_capabilities, cl_device_type, cl_uint, cl_ulong, CL_DEVICE_TYPE_GPU, CL_NONE,
+        CL_READ_ONLY_CACHE, CL_READ_WRITE_CACHE,
+    },
+    types::cl_device_mem_cache_type,
+};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+#[cfg(feature = "opencl")]
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// Stand-ins for the OpenCL types and constants when building without the `opencl` feature
+///
+/// Values are taken from the OpenCL headers such that snapshots are interpreted identically.
+#[cfg(not(feature = "opencl"))]
+#[allow(non_camel_case_types)]
+mod cl_types {
+    /// See [cl_uint](https://registry.khronos.org/OpenCL/specs/3.0-unified/html/OpenCL_API.html#scalar-data-types)
+    pub type cl_uint = u32;
+    /// See [cl_ulong](https://registry.khronos.org/OpenCL/specs/3.0-unified/html/OpenCL_API.html#scalar-data-types)
+    pub type cl_ulong = u64;
+    /// Bitfield of device types
+    pub type cl_device_type = cl_ulong;
+    /// Bitfield of shared virtual memory capabilities
+    pub type cl_device_svm_capabilities = cl_ulong;
+    /// Type of the global memory cache
+    pub type cl_device_mem_cache_type = cl_uint;
+
+    /// Bit of [cl_device_type] which marks GPUs
+    pub const CL_DEVICE_TYPE_GPU: cl_device_type = 1 << 2;
+    /// The device has no global memory cache
+    pub const CL_NONE: cl_device_mem_cache_type = 0x0;
+    /// The global memory cache is read-only
+    pub const CL_READ_ONLY_CACHE: cl_device_mem_cache_type = 0x1;
+    /// The global memory cache can be read and written
+    pub const CL_READ_WRITE_CACHE: cl_device_mem_cache_type = 0x2;
+
+    /// Name of the device type as in the OpenCL headers, eg. `CL_DEVICE_TYPE_GPU`
+    pub fn device_type_text(r#type: cl_device_type) -> &'static str {
+        match r#type {
+            0x1 => "CL_DEVICE_TYPE_DEFAULT",
+            0x2 => "CL_DEVICE_TYPE_CPU",
+            CL_DEVICE_TYPE_GPU => "CL_DEVICE_TYPE_GPU",
+            0x8 => "CL_DEVICE_TYPE_ACCELERATOR",
+            0x10 => "CL_DEVICE_TYPE_CUSTOM",
+            0xFFFF_FFFF => "CL_DEVICE_TYPE_ALL",
+            _ => "COMBINED_DEVICE_TYPE",
+        }
+    }
+
+    /// Name of the vendor with the given PCI vendor id, eg. `NVIDIA`
+    pub fn vendor_id_text(vendor_id: cl_uint) -> &'static str {
+        match vendor_id {
+            0x1002 => "AMD",
+            0x1014 => "IBM",
+            0x10de => "NVIDIA",
+            0x10ee => "XILINX",
+            0x1166 => "BROADCOM",
+            0x1172 => "ALTERA",
+            0x13b5 => "ARM",
+            0x1412 => "VIA_TECHNOLOGIES",
+            0x104c => "TEXAS_INSTRUMENTS",
+            0x168c => "QUALCOMM",
+            0x8086 => "INTEL",
+            0x1021d00 => "AMD_ON_APPLE",
+            _ => "UNKNOWN_VENDOR",
+        }
+    }
+}
 
+/// Implements a cloning getter for a single field of a struct
 macro_rules! impl_getter_single(
     ($struct_name:ident, $field:ident: $field_type:ty) => {
         impl $struct_name {
@@ -17,6 +105,7 @@ macro_rules! impl_getter_single(
     }
 );
 
+/// Implements cloning getters for all listed fields of a struct
 macro_rules! impl_getters(
     ($struct_name:ident, $($field:ident: $field_type:ty,)+) => {
         $(
@@ -25,16 +114,59 @@ macro_rules! impl_getters(
     }
 );
 
+/// Implements getters which borrow from the struct instead of cloning
+///
+/// Each entry names the getter, the field and the method which converts a reference to the
+/// field into the returned type, eg. `name_ref -> name.as_str(): &str`.
+macro_rules! impl_borrow_getters(
+    ($struct_name:ident, $($getter:ident -> $field:ident.$method:ident(): $field_type:ty,)+) => {
+        impl $struct_name {
+            $(
+                /// Borrowing getter of the
+                #[doc = stringify!($field)]
+                /// field
+                #[allow(unused)]
+                pub fn $getter(&self) -> $field_type {
+                    self.$field.$method()
+                }
+            )*
+        }
+    }
+);
+
 /// Information about a [Platform](opencl3::platform::Platform)
-#[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+///
+/// The serialized field names match the names of the getters and are kept stable
+/// across releases.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(rename_all = "snake_case")
+)]
 pub struct PlatformInfo {
+    /// Name of the platform
     name: String,
+    /// OpenCL version supported by the platform
     version: String,
+    /// Vendor of the platform
     vendor: String,
+    /// Profile (FULL_PROFILE or EMBEDDED_PROFILE)
     profile: String,
+    /// Space-separated list of supported extensions
     extensions: String,
+    /// All devices belonging to this platform
     devices: Vec<DeviceInfo>,
+    /// Properties which are not modelled by a field
+    ///
+    /// Unknown fields of deserialized snapshots, eg. written by a newer version of this crate,
+    /// are kept here and serialized again next to the known fields. Values which are no strings
+    /// are kept in their JSON notation.
+    #[cfg_attr(
+        feature = "serde",
+        serde(flatten, deserialize_with = "deserialize_extra")
+    )]
+    extra: BTreeMap<String, String>,
 }
 
 impl_getters!(
@@ -45,104 +177,1349 @@ impl_getters!(
     profile: String,
     extensions: String,
     devices: Vec<DeviceInfo>,
+    extra: BTreeMap<String, String>,
+);
+
+impl_borrow_getters!(
+    PlatformInfo,
+    name_ref -> name.as_str(): &str,
+    version_ref -> version.as_str(): &str,
+    vendor_ref -> vendor.as_str(): &str,
+    profile_ref -> profile.as_str(): &str,
+    extensions_ref -> extensions.as_str(): &str,
+    devices_ref -> devices.as_slice(): &[DeviceInfo],
 );
 
+#[cfg(feature = "opencl")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "opencl")))]
 impl PlatformInfo {
     /// Create a new instance from the given opencl platform and devices
     ///
     /// See also [DeviceInfo::construct]
     pub fn construct(
         platform: &opencl3::platform::Platform,
-        devices: &Vec<DeviceInfo>,
-    ) -> Result<Self, ClError> {
-        Ok(PlatformInfo {
-            name: platform.name()?,
-            version: platform.version()?,
-            vendor: platform.vendor()?,
-            profile: platform.profile()?,
-            extensions: platform.extensions()?,
-            devices: devices.clone(),
-        })
+        devices: &[DeviceInfo],
+    ) -> crate::Result<Self> {
+        let name = query("name", platform.name())?;
+        let construct = || {
+            Ok(PlatformInfo {
+                version: query("version", platform.version())?,
+                vendor: query("vendor", platform.vendor())?,
+                profile: query("profile", platform.profile())?,
+                extensions: query("extensions", platform.extensions())?,
+                devices: devices.to_vec(),
+                name: name.clone(),
+                extra: BTreeMap::new(),
+            })
+        };
+        construct().map_err(|error: ClSelectError| error.with_platform(&name))
+    }
+}
+
+/// Properties of a device which are queried during enumeration
+///
+/// Every property is a separate round-trip to the driver, so enumerating with fewer
+/// properties can be considerably faster. Properties which are not part of the level are
+/// [None] in the resulting [DeviceInfo].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum QueryLevel {
+    /// Only the properties which identify a device, ie. platform, vendor, name, version and type
+    Minimal,
+    /// Additionally the profile, extensions, OpenCL C and driver version and SVM capabilities
+    Standard,
+    /// All properties
+    #[default]
+    Full,
+    /// All properties and those which need a throwaway context, ie. the supported image formats
+    Deep,
+}
+
+/// Runs the query only if `enabled` is set and attaches the name of the property to its error
+#[cfg(feature = "opencl")]
+fn query_if<T>(
+    enabled: bool,
+    property: &'static str,
+    f: impl FnOnce() -> std::result::Result<T, opencl3::error_codes::ClError>,
+) -> crate::Result<Option<T>> {
+    match enabled {
+        true => query(property, f()).map(Some),
+        false => Ok(None),
     }
 }
 
 /// Contains information about a [Device](opencl3::device::Device)
-#[derive(Clone, Debug, Eq, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+///
+/// The serialized field names match the names of the getters and are kept stable
+/// across releases.
+/// Fields which were added after the first release fall back to their default value
+/// such that snapshots written by older versions can still be read. Unknown fields of snapshots
+/// written by newer versions are kept in [extra](DeviceInfo::extra).
+#[cfg_attr(feature = "serde", doc = "```")]
+#[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+/// # use opencl3_select::DeviceInfo;
+/// // Written by version 0.1.0
+/// let device: DeviceInfo = serde_json::from_str(r#"{
+///     "vendor": "NVIDIA Corporation",
+///     "vendor_id": 4318,
+///     "vendor_id_text": "NVIDIA",
+///     "name": "NVIDIA GeForce RTX 3060",
+///     "version": "OpenCL 3.0 CUDA",
+///     "type": 4,
+///     "type_text": "CL_DEVICE_TYPE_GPU",
+///     "profile": "FULL_PROFILE",
+///     "extensions": "cl_khr_fp64",
+///     "opencl_c_version": "OpenCL C 1.2",
+///     "svm_mem_capability": 3
+/// }"#)?;
+/// assert_eq!(device.name(), "NVIDIA GeForce RTX 3060");
+/// assert_eq!(device.r#type(), 4);
+/// assert_eq!(device.global_mem_size(), None);
+/// assert_eq!(device.host_unified_memory(), None);
+/// assert_eq!(device.max_compute_units(), None);
+///
+/// // Field names do not change when serializing again
+/// let value = serde_json::to_value(&device)?;
+/// assert_eq!(value["type"], 4);
+/// assert_eq!(value["vendor_id_text"], "NVIDIA");
+///
+/// // Fields of newer versions survive a round-trip
+/// let mut value = value;
+/// value["max_sub_group_count"] = 28.into();
+/// let device: DeviceInfo = serde_json::from_value(value)?;
+/// assert_eq!(device.extra_ref()["max_sub_group_count"], "28");
+/// assert_eq!(serde_json::to_value(&device)?["max_sub_group_count"], "28");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(rename_all = "snake_case")
+)]
 pub struct DeviceInfo {
+    // PLATFORM
+    /// Name of the platform the device belongs to
+    #[cfg_attr(feature = "serde", serde(default))]
+    platform: String,
     // VENDOR
+    /// Vendor of the device
     vendor: String,
-    vendor_id: opencl3::device::cl_uint,
+    /// Unique identifier of the vendor
+    vendor_id: cl_uint,
+    /// Human-readable name of the vendor id
     vendor_id_text: String,
     // Device
+    /// Name of the device
     name: String,
+    /// OpenCL version supported by the device
     version: String,
     // TYPE
-    r#type: opencl3::device::cl_device_type,
+    /// Bitfield of the device type
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    r#type: cl_device_type,
+    /// Human-readable device type
     type_text: String,
     // OTHER
-    profile: String,
-    extensions: String,
-    opencl_c_version: String,
-    svm_mem_capability: opencl3::device::cl_device_svm_capabilities,
+    /// Profile (FULL_PROFILE or EMBEDDED_PROFILE)
+    #[cfg_attr(feature = "serde", serde(default))]
+    profile: Option<String>,
+    /// Space-separated list of supported extensions
+    #[cfg_attr(feature = "serde", serde(default))]
+    extensions: Option<String>,
+    /// Highest OpenCL C version supported by the compiler
+    #[cfg_attr(feature = "serde", serde(default))]
+    opencl_c_version: Option<String>,
+    /// Version of the driver, eg. `535.104.05`
+    #[cfg_attr(feature = "serde", serde(default))]
+    driver_version: Option<String>,
+    /// Bitfield of shared virtual memory capabilities
+    #[cfg_attr(feature = "serde", serde(default))]
+    svm_mem_capability: Option<cl_device_svm_capabilities>,
+    // MEMORY
+    /// Size of the global memory in bytes
+    #[cfg_attr(feature = "serde", serde(default))]
+    global_mem_size: Option<cl_ulong>,
+    /// Size of the global memory cache in bytes
+    #[cfg_attr(feature = "serde", serde(default))]
+    global_mem_cache_size: Option<cl_ulong>,
+    /// Size of a global memory cache line in bytes
+    #[cfg_attr(feature = "serde", serde(default))]
+    global_mem_cacheline_size: Option<cl_uint>,
+    /// Type of the global memory cache
+    #[cfg_attr(feature = "serde", serde(default))]
+    global_mem_cache_type: Option<cl_device_mem_cache_type>,
+    /// Human-readable type of the global memory cache
+    #[cfg_attr(feature = "serde", serde(default))]
+    global_mem_cache_type_text: Option<String>,
+    /// Maximum size of a constant buffer allocation in bytes
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_constant_buffer_size: Option<cl_ulong>,
+    /// Alignment of the base address of memory objects in bits
+    #[cfg_attr(feature = "serde", serde(default))]
+    mem_base_addr_align: Option<cl_uint>,
+    /// Whether the device and the host share a unified memory subsystem
+    #[cfg_attr(feature = "serde", serde(default))]
+    host_unified_memory: Option<bool>,
+    /// Whether the device corrects errors of its memories and caches
+    #[cfg_attr(feature = "serde", serde(default))]
+    error_correction_support: Option<bool>,
+    /// Size of the largest memory object the device can allocate in bytes
+    ///
+    /// Usually a fraction of the [global memory](DeviceInfo::global_mem_size).
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_mem_alloc_size: Option<cl_ulong>,
+    /// Size of the local memory of a work-group in bytes
+    #[cfg_attr(feature = "serde", serde(default))]
+    local_mem_size: Option<cl_ulong>,
+    // EXECUTION
+    /// Number of parallel compute units
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_compute_units: Option<cl_uint>,
+    /// Maximum clock frequency in MHz
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_clock_frequency: Option<cl_uint>,
+    /// Maximum number of work-items in a work-group
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_work_group_size: Option<cl_ulong>,
+    /// Whether the device supports images as reported by the device itself
+    ///
+    /// See [image_support](DeviceInfo::image_support) which also considers the image formats.
+    #[cfg_attr(feature = "serde", serde(default))]
+    image_support: Option<bool>,
+    /// Size of the buffer for the output of `printf` in kernels in bytes
+    #[cfg_attr(feature = "serde", serde(default))]
+    printf_buffer_size: Option<cl_ulong>,
+    /// Resolution of the profiling timer in nanoseconds
+    #[cfg_attr(feature = "serde", serde(default))]
+    profiling_timer_resolution: Option<cl_ulong>,
+    /// Whether the device is little endian
+    #[cfg_attr(feature = "serde", serde(default))]
+    endian_little: Option<bool>,
+    // IDENTITY
+    /// Universally unique identifier of the device as reported via `cl_khr_device_uuid`
+    #[cfg_attr(feature = "serde", serde(default))]
+    uuid: Option<[u8; 16]>,
+    /// Locally unique identifier of the device as reported via `cl_khr_device_uuid`
+    ///
+    /// Only present on platforms which support LUIDs, ie. Windows.
+    #[cfg_attr(feature = "serde", serde(default))]
+    luid: Option<[u8; 8]>,
+    // PARTITIONING
+    /// Maximum number of sub-devices the device can be split into
+    #[cfg_attr(feature = "serde", serde(default))]
+    partition_max_sub_devices: Option<cl_uint>,
+    /// Ways in which the device can be split, empty if it can not be split
+    #[cfg_attr(feature = "serde", serde(default))]
+    partition_properties: Option<Vec<PartitionScheme>>,
+    /// Affinity domains along which the device can be split
+    #[cfg_attr(feature = "serde", serde(default))]
+    partition_affinity_domains: Option<AffinityDomains>,
+    // OPENCL 3.0
+    /// Version of the device as `CL_DEVICE_NUMERIC_VERSION`
+    #[cfg_attr(feature = "serde", serde(default))]
+    numeric_version: Option<NumericVersion>,
+    /// Supported intermediate languages, eg. SPIR-V
+    #[cfg_attr(feature = "serde", serde(default))]
+    ils: Option<Vec<NameVersion>>,
+    /// Memory orderings and scopes supported by atomic memory operations
+    #[cfg_attr(feature = "serde", serde(default))]
+    atomic_memory_capabilities: Option<AtomicCapabilities>,
+    /// Memory orderings and scopes supported by atomic fences
+    #[cfg_attr(feature = "serde", serde(default))]
+    atomic_fence_capabilities: Option<AtomicCapabilities>,
+    /// Versions of OpenCL C the compiler accepts, eg. `OpenCL C 1.2.0`
+    #[cfg_attr(feature = "serde", serde(default))]
+    opencl_c_all_versions: Option<Vec<NameVersion>>,
+    /// Optional OpenCL C features, eg. `__opencl_c_fp64`
+    #[cfg_attr(feature = "serde", serde(default))]
+    opencl_c_features: Option<Vec<NameVersion>>,
+    // HOST
+    /// Processor of the machine if this is a CPU device, queried with the `sysinfo` feature
+    #[cfg_attr(feature = "serde", serde(default))]
+    host_cpu: Option<CpuInfo>,
+    // IMAGES
+    /// Formats of read-only 2D images, only queried with [QueryLevel::Deep]
+    ///
+    /// Empty if the device does not support images.
+    #[cfg_attr(feature = "serde", serde(default))]
+    image_formats: Option<Vec<ImageFormat>>,
+    // OTHER
+    /// Properties which are not modelled by a field
+    ///
+    /// Unknown fields of deserialized snapshots, eg. written by a newer version of this crate,
+    /// are kept here and serialized again next to the known fields. Values which are no strings
+    /// are kept in their JSON notation.
+    #[cfg_attr(
+        feature = "serde",
+        serde(flatten, deserialize_with = "deserialize_extra")
+    )]
+    extra: BTreeMap<String, String>,
 }
 
 impl_getters!(
     DeviceInfo,
+    // PLATFORM
+    platform: String,
     // VENDOR
     vendor: String,
-    vendor_id: opencl3::device::cl_uint,
+    vendor_id: cl_uint,
     vendor_id_text: String,
     // Device
     name: String,
     version: String,
     // TYPE
-    r#type: opencl3::device::cl_device_type,
+    r#type: cl_device_type,
     type_text: String,
     // OTHER
-    profile: String,
-    extensions: String,
-    opencl_c_version: String,
-    svm_mem_capability: opencl3::device::cl_device_svm_capabilities,
+    profile: Option<String>,
+    extensions: Option<String>,
+    opencl_c_version: Option<String>,
+    driver_version: Option<String>,
+    svm_mem_capability: Option<cl_device_svm_capabilities>,
+    // MEMORY
+    global_mem_size: Option<cl_ulong>,
+    global_mem_cache_size: Option<cl_ulong>,
+    global_mem_cacheline_size: Option<cl_uint>,
+    global_mem_cache_type: Option<cl_device_mem_cache_type>,
+    global_mem_cache_type_text: Option<String>,
+    max_constant_buffer_size: Option<cl_ulong>,
+    mem_base_addr_align: Option<cl_uint>,
+    host_unified_memory: Option<bool>,
+    error_correction_support: Option<bool>,
+    max_mem_alloc_size: Option<cl_ulong>,
+    local_mem_size: Option<cl_ulong>,
+    // EXECUTION
+    max_compute_units: Option<cl_uint>,
+    max_clock_frequency: Option<cl_uint>,
+    max_work_group_size: Option<cl_ulong>,
+    printf_buffer_size: Option<cl_ulong>,
+    profiling_timer_resolution: Option<cl_ulong>,
+    endian_little: Option<bool>,
+    // IDENTITY
+    uuid: Option<[u8; 16]>,
+    luid: Option<[u8; 8]>,
+    // PARTITIONING
+    partition_max_sub_devices: Option<cl_uint>,
+    partition_properties: Option<Vec<PartitionScheme>>,
+    partition_affinity_domains: Option<AffinityDomains>,
+    // OPENCL 3.0
+    numeric_version: Option<NumericVersion>,
+    ils: Option<Vec<NameVersion>>,
+    atomic_memory_capabilities: Option<AtomicCapabilities>,
+    atomic_fence_capabilities: Option<AtomicCapabilities>,
+    opencl_c_all_versions: Option<Vec<NameVersion>>,
+    opencl_c_features: Option<Vec<NameVersion>>,
+    // HOST
+    host_cpu: Option<CpuInfo>,
+    // IMAGES
+    image_formats: Option<Vec<ImageFormat>>,
+    // OTHER
+    extra: BTreeMap<String, String>,
+);
+
+impl_borrow_getters!(
+    DeviceInfo,
+    // PLATFORM
+    platform_ref -> platform.as_str(): &str,
+    // VENDOR
+    vendor_ref -> vendor.as_str(): &str,
+    vendor_id_text_ref -> vendor_id_text.as_str(): &str,
+    // Device
+    name_ref -> name.as_str(): &str,
+    version_ref -> version.as_str(): &str,
+    // TYPE
+    type_text_ref -> type_text.as_str(): &str,
+    // OTHER
+    profile_ref -> profile.as_deref(): Option<&str>,
+    extensions_ref -> extensions.as_deref(): Option<&str>,
+    opencl_c_version_ref -> opencl_c_version.as_deref(): Option<&str>,
+    driver_version_ref -> driver_version.as_deref(): Option<&str>,
+    // MEMORY
+    global_mem_cache_type_text_ref -> global_mem_cache_type_text.as_deref(): Option<&str>,
+    // PARTITIONING
+    partition_properties_ref -> partition_properties.as_deref(): Option<&[PartitionScheme]>,
+    // OPENCL 3.0
+    ils_ref -> ils.as_deref(): Option<&[NameVersion]>,
+    opencl_c_all_versions_ref -> opencl_c_all_versions.as_deref(): Option<&[NameVersion]>,
+    opencl_c_features_ref -> opencl_c_features.as_deref(): Option<&[NameVersion]>,
+    // HOST
+    host_cpu_ref -> host_cpu.as_ref(): Option<&CpuInfo>,
+    // IMAGES
+    image_formats_ref -> image_formats.as_deref(): Option<&[ImageFormat]>,
 );
 
+#[cfg(feature = "opencl")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "opencl")))]
 impl DeviceInfo {
     /// Create new instance from given opencl device
-    pub fn construct(device: &opencl3::device::Device) -> Result<Self, ClError> {
-        Ok(Self {
-            // VENDOR
-            vendor: device.vendor()?,
-            vendor_id: device.vendor_id()?,
-            vendor_id_text: opencl3::device::vendor_id_text(device.vendor_id()?).into(),
-            // DEVICE
-            name: device.name()?,
-            version: device.version()?,
-            // TYPE
-            r#type: device.dev_type()?,
-            type_text: device_type_text(device.dev_type()?).into(),
-            // OTHER
-            profile: device.profile()?,
-            extensions: device.extensions()?,
-            opencl_c_version: device.opencl_c_version()?,
-            svm_mem_capability: device.svm_mem_capability(),
+    ///
+    /// All properties are queried, see [construct_with](DeviceInfo::construct_with).
+    pub fn construct(device: &opencl3::device::Device) -> crate::Result<Self> {
+        Self::construct_with(device, QueryLevel::Full)
+    }
+
+    /// Create new instance from given opencl device querying only the properties of the level
+    ///
+    /// Properties which are not part of the level are [None].
+    pub fn construct_with(
+        device: &opencl3::device::Device,
+        level: QueryLevel,
+    ) -> crate::Result<Self> {
+        let name = query("name", device.name())?;
+        let standard = level >= QueryLevel::Standard;
+        let full = level >= QueryLevel::Full;
+        let deep = level >= QueryLevel::Deep;
+        let construct = || {
+            let platform = opencl3::platform::Platform::new(query("platform", device.platform())?);
+            let vendor_id = query("vendor_id", device.vendor_id())?;
+            let r#type = query("type", device.dev_type())?;
+            let cache_type = query_if(full, "global_mem_cache_type", || {
+                device.global_mem_cache_type()
+            })?;
+            let extensions = query_if(standard, "extensions", || device.extensions())?;
+            let has_uuid = full
+                && extensions.as_deref().is_some_and(|extensions| {
+                    extensions
+                        .split_whitespace()
+                        .any(|extension| extension == "cl_khr_device_uuid")
+                });
+            // The feature queries fail on devices which do not support OpenCL 3.0
+            let version = query("version", device.version())?;
+            let at_least = |major, minor| {
+                full && OpenClVersion::parse(&version)
+                    .is_some_and(|version| version >= OpenClVersion { major, minor })
+            };
+            // Partitioning was introduced with OpenCL 1.2
+            let cl12 = at_least(1, 2);
+            let cl3 = at_least(3, 0);
+            let name_versions = |values: Vec<opencl3::device::cl_name_version>| {
+                values.iter().map(NameVersion::from).collect::<Vec<_>>()
+            };
+            Ok(Self {
+                // PLATFORM
+                platform: query("platform name", platform.name())?,
+                // VENDOR
+                vendor: query("vendor", device.vendor())?,
+                vendor_id,
+                vendor_id_text: vendor_id_text(vendor_id).into(),
+                // DEVICE
+                name: name.clone(),
+                version,
+                // TYPE
+                r#type,
+                type_text: device_type_text(r#type).into(),
+                // OTHER
+                profile: query_if(standard, "profile", || device.profile())?,
+                extensions,
+                opencl_c_version: query_if(standard, "opencl_c_version", || {
+                    device.opencl_c_version()
+                })?,
+                driver_version: query_if(standard, "driver_version", || device.driver_version())?,
+                svm_mem_capability: standard.then(|| device.svm_mem_capability()),
+                // MEMORY
+                global_mem_size: query_if(full, "global_mem_size", || device.global_mem_size())?,
+                global_mem_cache_size: query_if(full, "global_mem_cache_size", || {
+                    device.global_mem_cache_size()
+                })?,
+                global_mem_cacheline_size: query_if(full, "global_mem_cacheline_size", || {
+                    device.global_mem_cacheline_size()
+                })?,
+                global_mem_cache_type: cache_type,
+                global_mem_cache_type_text: cache_type
+                    .map(|cache_type| mem_cache_type_text(cache_type).into()),
+                max_constant_buffer_size: query_if(full, "max_constant_buffer_size", || {
+                    device.max_constant_buffer_size()
+                })?,
+                mem_base_addr_align: query_if(full, "mem_base_addr_align", || {
+                    device.mem_base_addr_align()
+                })?,
+                // Deprecated since OpenCL 2.0 and thus not reported by every device
+                host_unified_memory: full.then(|| device.host_unified_memory().ok()).flatten(),
+                error_correction_support: query_if(full, "error_correction_support", || {
+                    device.error_correction_support()
+                })?,
+                max_mem_alloc_size: query_if(full, "max_mem_alloc_size", || {
+                    device.max_mem_alloc_size()
+                })?,
+                local_mem_size: query_if(full, "local_mem_size", || device.local_mem_size())?,
+                // EXECUTION
+                max_compute_units: query_if(full, "max_compute_units", || {
+                    device.max_compute_units()
+                })?,
+                max_clock_frequency: query_if(full, "max_clock_frequency", || {
+                    device.max_clock_frequency()
+                })?,
+                max_work_group_size: query_if(full, "max_work_group_size", || {
+                    device.max_work_group_size()
+                })?
+                .map(|size| size as cl_ulong),
+                image_support: query_if(full, "image_support", || device.image_support())?,
+                // The printf buffer was introduced with OpenCL 1.2
+                printf_buffer_size: query_if(cl12, "printf_buffer_size", || {
+                    device.printf_buffer_size()
+                })?
+                .map(|size| size as cl_ulong),
+                profiling_timer_resolution: query_if(full, "profiling_timer_resolution", || {
+                    device.profiling_timer_resolution()
+                })?
+                .map(|resolution| resolution as cl_ulong),
+                endian_little: query_if(full, "endian_little", || device.endian_little())?,
+                // IDENTITY
+                uuid: query_if(has_uuid, "uuid", || device.uuid_khr())?,
+                luid: match has_uuid && query("luid_valid", device.luid_valid_khr())? {
+                    true => Some(query("luid", device.luid_khr())?),
+                    false => None,
+                },
+                // PARTITIONING
+                partition_max_sub_devices: query_if(cl12, "partition_max_sub_devices", || {
+                    device.partition_max_sub_devices()
+                })?,
+                partition_properties: query_if(cl12, "partition_properties", || {
+                    device.partition_properties()
+                })?
+                .map(|properties| {
+                    properties
+                        .into_iter()
+                        .filter_map(PartitionScheme::from_raw)
+                        .collect()
+                }),
+                partition_affinity_domains: query_if(cl12, "partition_affinity_domain", || {
+                    device.partition_affinity_domain()
+                })?
+                .map(|domains| {
+                    AffinityDomains(domains.into_iter().fold(0, |all, bits| all | bits))
+                }),
+                // OPENCL 3.0
+                numeric_version: query_if(cl3, "numeric_version", || device.numeric_version())?
+                    .map(NumericVersion::from_raw),
+                ils: query_if(cl3, "ils_with_version", || device.ils_with_version())?
+                    .map(name_versions),
+                atomic_memory_capabilities: query_if(cl3, "atomic_memory_capabilities", || {
+                    device.atomic_memory_capabilities()
+                })?
+                .map(AtomicCapabilities),
+                atomic_fence_capabilities: query_if(cl3, "atomic_fence_capabilities", || {
+                    device.atomic_fence_capabilities()
+                })?
+                .map(AtomicCapabilities),
+                opencl_c_all_versions: query_if(cl3, "opencl_c_all_versions", || {
+                    device.opencl_c_all_versions()
+                })?
+                .map(name_versions),
+                opencl_c_features: query_if(cl3, "opencl_c_features", || {
+                    device.opencl_c_features()
+                })?
+                .map(name_versions),
+                // HOST
+                #[cfg(feature = "sysinfo")]
+                host_cpu: (full && r#type & opencl3::device::CL_DEVICE_TYPE_CPU != 0)
+                    .then(CpuInfo::current),
+                #[cfg(not(feature = "sysinfo"))]
+                host_cpu: None,
+                // IMAGES
+                image_formats: match deep {
+                    true => Some(query_image_formats(device)?),
+                    false => None,
+                },
+                // OTHER
+                extra: BTreeMap::new(),
+            })
+        };
+        construct().map_err(|error: ClSelectError| error.with_device(&name))
+    }
+}
+
+/// Formats of read-only 2D images supported by the device, empty if it has no image support
+///
+/// The formats can only be queried from a context, so a throwaway one is created for the device.
+#[cfg(feature = "opencl")]
+fn query_image_formats(device: &opencl3::device::Device) -> crate::Result<Vec<ImageFormat>> {
+    if !query("image_support", device.image_support())? {
+        return Ok(vec![]);
+    }
+    let context = query(
+        "image_formats",
+        opencl3::context::Context::from_device(device),
+    )?;
+    let formats = query(
+        "image_formats",
+        context.get_supported_image_formats(
+            opencl3::memory::CL_MEM_READ_ONLY,
+            opencl3::memory::CL_MEM_OBJECT_IMAGE2D,
+        ),
+    )?;
+    Ok(formats
+        .iter()
+        .filter_map(|format| {
+            ImageFormat::from_raw(format.image_channel_order, format.image_channel_data_type)
+        })
+        .collect())
+}
+
+#[cfg(feature = "opencl")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "opencl")))]
+impl DeviceInfo {
+    /// Find the [Device](opencl3::device::Device) described by this info on the current machine
+    ///
+    /// Devices are matched by their vendor, name and version.
+    /// See [resolve_with](DeviceInfo::resolve_with) if the device is present on several platforms.
+    pub fn resolve(&self) -> crate::Result<opencl3::device::Device> {
+        self.resolve_with(&crate::UniquePriorityList::new())
+    }
+
+    /// Variant of [resolve](DeviceInfo::resolve) which consults the platform priorities
+    ///
+    /// The same device can be present on several platforms, eg. on the ICD of its vendor and
+    /// on rusticl. Matching devices are then taken from the platform with the highest priority.
+    /// Without priority, the platform on which the device was enumerated is preferred over
+    /// any other platform.
+    /// ```no_run
+    /// use opencl3_select::UniquePriorityList;
+    /// let cl_state = opencl3_select::get_setup()?;
+    /// let platforms = UniquePriorityList::from(cl_state.get_platforms());
+    /// let device = cl_state.get_all_devices()[0].resolve_with(&platforms)?;
+    /// # Ok::<(), opencl3_select::ClSelectError>(())
+    /// ```
+    pub fn resolve_with(
+        &self,
+        platforms: &crate::UniquePriorityList<PlatformInfo>,
+    ) -> crate::Result<opencl3::device::Device> {
+        let mut candidates = vec![];
+        for platform in opencl3::platform::get_platforms()? {
+            let platform_name = platform.name()?;
+            for device_id in platform.get_devices(CL_DEVICE_TYPE_ALL)? {
+                let device = opencl3::device::Device::new(device_id);
+                if device.vendor_id()? == self.vendor_id
+                    && device.vendor()? == self.vendor
+                    && device.name()? == self.name
+                    && device.version()? == self.version
+                {
+                    let priority = platforms
+                        .view_priority_list()
+                        .into_iter()
+                        .position(|platform| platform.name == platform_name)
+                        .unwrap_or(usize::MAX);
+                    candidates.push(((priority, platform_name != self.platform), device));
+                }
+            }
+        }
+        candidates
+            .into_iter()
+            .min_by_key(|(rank, _)| *rank)
+            .map(|(_, device)| device)
+            .ok_or_else(|| {
+                crate::SelectionError::DeviceNotFound(crate::DeviceSpec::Name(self.name.clone()))
+                    .into()
+            })
+    }
+
+    /// Create a [Context](opencl3::context::Context) containing only this device
+    /// ```no_run
+    /// let cl_state = opencl3_select::get_setup()?;
+    /// let device_info = &cl_state.get_all_devices()[0];
+    /// let context = device_info.create_context()?;
+    /// # Ok::<(), opencl3_select::ClSelectError>(())
+    /// ```
+    pub fn create_context(&self) -> crate::Result<opencl3::context::Context> {
+        Ok(opencl3::context::Context::from_device(&self.resolve()?)?)
+    }
+
+    /// Create a [Context](opencl3::context::Context) and a
+    /// [CommandQueue](opencl3::command_queue::CommandQueue) for this device
+    ///
+    /// See [CommandQueue::create_default_with_properties](opencl3::command_queue::CommandQueue::create_default_with_properties)
+    /// for the meaning of `properties` and `queue_size`.
+    /// ```no_run
+    /// use opencl3::command_queue::CL_QUEUE_PROFILING_ENABLE;
+    ///
+    /// let cl_state = opencl3_select::get_setup()?;
+    /// let device_info = &cl_state.get_all_devices()[0];
+    /// let (context, queue) =
+    ///     device_info.create_queue_with_properties(CL_QUEUE_PROFILING_ENABLE, 0)?;
+    /// # Ok::<(), opencl3_select::ClSelectError>(())
+    /// ```
+    pub fn create_queue_with_properties(
+        &self,
+        properties: opencl3::command_queue::cl_command_queue_properties,
+        queue_size: cl_uint,
+    ) -> crate::Result<(
+        opencl3::context::Context,
+        opencl3::command_queue::CommandQueue,
+    )> {
+        let context = self.create_context()?;
+        let queue = opencl3::command_queue::CommandQueue::create_default_with_properties(
+            &context, properties, queue_size,
+        )?;
+        Ok((context, queue))
+    }
+}
+
+/// Human-readable name of a [cl_device_mem_cache_type](opencl3::types::cl_device_mem_cache_type)
+/// ```
+/// use opencl3_select::mem_cache_type_text;
+/// assert_eq!(mem_cache_type_text(2), "CL_READ_WRITE_CACHE");
+/// ```
+pub fn mem_cache_type_text(cache_type: cl_device_mem_cache_type) -> &'static str {
+    match cache_type {
+        CL_NONE => "CL_NONE",
+        CL_READ_ONLY_CACHE => "CL_READ_ONLY_CACHE",
+        CL_READ_WRITE_CACHE => "CL_READ_WRITE_CACHE",
+        _ => "UNKNOWN_CACHE_TYPE",
+    }
+}
+
+/// Version of the OpenCL standard as reported by platforms and devices
+/// ```
+/// use opencl3_select::OpenClVersion;
+/// let version = OpenClVersion::parse("OpenCL 3.0 CUDA 12.2.148").unwrap();
+/// assert_eq!(version, OpenClVersion { major: 3, minor: 0 });
+/// assert!(version > OpenClVersion { major: 1, minor: 2 });
+/// assert_eq!(version.to_string(), "3.0");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct OpenClVersion {
+    /// Major version
+    pub major: u32,
+    /// Minor version
+    pub minor: u32,
+}
+
+impl OpenClVersion {
+    /// Parses strings of the form `OpenCL <major>.<minor> <vendor-specific information>`
+    pub fn parse(version: &str) -> Option<Self> {
+        let numbers = version.strip_prefix("OpenCL ")?.split_whitespace().next()?;
+        let (major, minor) = numbers.split_once('.')?;
+        Some(Self {
+            major: major.parse().ok()?,
+            minor: minor.parse().ok()?,
         })
     }
 }
 
+impl std::fmt::Display for OpenClVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl PlatformInfo {
+    /// Borrowing getter of the extra field
+    pub fn extra_ref(&self) -> &BTreeMap<String, String> {
+        &self.extra
+    }
+}
+
+impl DeviceInfo {
+    /// Borrowing getter of the extra field
+    pub fn extra_ref(&self) -> &BTreeMap<String, String> {
+        &self.extra
+    }
+
+    /// Checks if the device can be split into sub-devices
+    ///
+    /// `None` if the partitioning properties were not queried.
+    pub fn can_partition(&self) -> Option<bool> {
+        let max_sub_devices = self.partition_max_sub_devices?;
+        let properties = self.partition_properties.as_ref()?;
+        Some(max_sub_devices > 1 && !properties.is_empty())
+    }
+
+    /// Whether the device supports images, `None` if it was not queried
+    ///
+    /// Taken from the [image formats](DeviceInfo::image_formats) if they were queried.
+    /// ```
+    /// use opencl3_select::DeviceInfo;
+    /// let device = DeviceInfo::builder().image_support(true).build();
+    /// assert_eq!(device.image_support(), Some(true));
+    /// assert_eq!(DeviceInfo::builder().image_formats(vec![]).build().image_support(), Some(false));
+    /// assert_eq!(DeviceInfo::builder().build().image_support(), None);
+    /// ```
+    pub fn image_support(&self) -> Option<bool> {
+        match &self.image_formats {
+            Some(formats) => Some(!formats.is_empty()),
+            None => self.image_support,
+        }
+    }
+
+    /// The OpenCL version supported by the device, parsed from [version](DeviceInfo::version)
+    pub fn opencl_version(&self) -> Option<OpenClVersion> {
+        OpenClVersion::parse(&self.version)
+    }
+
+    /// Decoded [svm_mem_capability](DeviceInfo::svm_mem_capability) if it was queried
+    pub fn svm_capabilities(&self) -> Option<SvmCapabilities> {
+        self.svm_mem_capability.map(SvmCapabilities::from_raw)
+    }
+
+    /// Compact label without spaces for shell prompts, eg. `gpu:RTX3060`
+    ///
+    /// Consists of the lowercase type and the name without brand and trademark words, cut to
+    /// [SHORT_LABEL_LENGTH] characters.
+    /// ```
+    /// use opencl3_select::DeviceInfo;
+    /// let gpu = DeviceInfo::builder().name("NVIDIA GeForce RTX 3060").r#type(4).build();
+    /// assert_eq!(gpu.short_label(), "gpu:RTX3060");
+    /// let cpu = DeviceInfo::builder().name("Intel(R) Core(TM) i7-8700 CPU").r#type(2).build();
+    /// assert_eq!(cpu.short_label(), "cpu:i7-8700");
+    /// ```
+    pub fn short_label(&self) -> String {
+        let kind = self
+            .type_text
+            .trim_start_matches("CL_DEVICE_TYPE_")
+            .to_lowercase();
+        let name: String = self
+            .name
+            .replace("(R)", "")
+            .replace("(TM)", "")
+            .split_whitespace()
+            .filter(|word| {
+                !SHORT_LABEL_NOISE
+                    .iter()
+                    .any(|noise| noise.eq_ignore_ascii_case(word))
+            })
+            .collect();
+        let name: String = match name.is_empty() {
+            true => self.name.split_whitespace().collect(),
+            false => name,
+        };
+        format!(
+            "{kind}:{}",
+            name.chars().take(SHORT_LABEL_LENGTH).collect::<String>()
+        )
+    }
+}
+
+/// Maximum number of characters of the name in a [short label](DeviceInfo::short_label)
+pub const SHORT_LABEL_LENGTH: usize = 16;
+
+/// Brand and generic words which are left out of [short labels](DeviceInfo::short_label)
+const SHORT_LABEL_NOISE: [&str; 14] = [
+    "NVIDIA",
+    "GeForce",
+    "AMD",
+    "Radeon",
+    "Intel",
+    "Core",
+    "Graphics",
+    "Apple",
+    "Corporation",
+    "CPU",
+    "GPU",
+    "Processor",
+    "with",
+    "Series",
+];
+
+/// Concise one-line description of the device
+#[cfg_attr(feature = "serde", doc = "```")]
+#[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+/// # use opencl3_select::DeviceInfo;
+/// # let device: DeviceInfo = serde_json::from_str(r#"{
+/// #     "platform": "NVIDIA CUDA", "vendor": "NVIDIA Corporation", "vendor_id": 4318,
+/// #     "vendor_id_text": "NVIDIA", "name": "NVIDIA GeForce RTX 3060",
+/// #     "version": "OpenCL 3.0 CUDA", "type": 4, "type_text": "CL_DEVICE_TYPE_GPU",
+/// #     "profile": "FULL_PROFILE", "extensions": "", "opencl_c_version": "OpenCL C 1.2",
+/// #     "svm_mem_capability": 3
+/// # }"#)?;
+/// assert_eq!(
+///     device.to_string(),
+///     "NVIDIA GeForce RTX 3060 (NVIDIA Corporation, CL_DEVICE_TYPE_GPU, OpenCL 3.0 CUDA)"
+/// );
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+impl std::fmt::Display for DeviceInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}, {}, {})",
+            self.name, self.vendor, self.type_text, self.version
+        )
+    }
+}
+
+impl DeviceInfo {
+    /// Key by which devices are ordered
+    ///
+    /// Devices are sorted by platform, vendor and name. The remaining fields only break ties
+    /// such that the ordering agrees with [PartialEq].
+    #[allow(clippy::type_complexity)]
+    fn ordering_key(
+        &self,
+    ) -> (
+        (&str, &str, &str),
+        (
+            cl_uint,
+            &str,
+            &str,
+            cl_device_type,
+            &str,
+            &Option<String>,
+            &Option<String>,
+            &Option<String>,
+            &Option<String>,
+        ),
+        (
+            Option<cl_device_svm_capabilities>,
+            Option<cl_ulong>,
+            Option<cl_ulong>,
+            Option<cl_uint>,
+        ),
+        (
+            Option<cl_device_mem_cache_type>,
+            &Option<String>,
+            Option<cl_ulong>,
+            Option<cl_uint>,
+            Option<bool>,
+            Option<bool>,
+            Option<cl_ulong>,
+            Option<cl_ulong>,
+        ),
+        (
+            Option<cl_uint>,
+            Option<cl_uint>,
+            Option<cl_ulong>,
+            Option<bool>,
+            Option<cl_ulong>,
+            Option<cl_ulong>,
+            Option<bool>,
+        ),
+        (Option<[u8; 16]>, Option<[u8; 8]>),
+        (
+            Option<cl_uint>,
+            &Option<Vec<PartitionScheme>>,
+            Option<AffinityDomains>,
+        ),
+        (
+            Option<NumericVersion>,
+            &Option<Vec<NameVersion>>,
+            Option<AtomicCapabilities>,
+            Option<AtomicCapabilities>,
+            &Option<Vec<NameVersion>>,
+            &Option<Vec<NameVersion>>,
+        ),
+        &Option<CpuInfo>,
+        &Option<Vec<ImageFormat>>,
+        &BTreeMap<String, String>,
+    ) {
+        (
+            (&self.platform, &self.vendor, &self.name),
+            (
+                self.vendor_id,
+                &self.vendor_id_text,
+                &self.version,
+                self.r#type,
+                &self.type_text,
+                &self.profile,
+                &self.extensions,
+                &self.opencl_c_version,
+                &self.driver_version,
+            ),
+            (
+                self.svm_mem_capability,
+                self.global_mem_size,
+                self.global_mem_cache_size,
+                self.global_mem_cacheline_size,
+            ),
+            (
+                self.global_mem_cache_type,
+                &self.global_mem_cache_type_text,
+                self.max_constant_buffer_size,
+                self.mem_base_addr_align,
+                self.host_unified_memory,
+                self.error_correction_support,
+                self.max_mem_alloc_size,
+                self.local_mem_size,
+            ),
+            (
+                self.max_compute_units,
+                self.max_clock_frequency,
+                self.max_work_group_size,
+                self.image_support,
+                self.printf_buffer_size,
+                self.profiling_timer_resolution,
+                self.endian_little,
+            ),
+            (self.uuid, self.luid),
+            (
+                self.partition_max_sub_devices,
+                &self.partition_properties,
+                self.partition_affinity_domains,
+            ),
+            (
+                self.numeric_version,
+                &self.ils,
+                self.atomic_memory_capabilities,
+                self.atomic_fence_capabilities,
+                &self.opencl_c_all_versions,
+                &self.opencl_c_features,
+            ),
+            &self.host_cpu,
+            &self.image_formats,
+            &self.extra,
+        )
+    }
+}
+
+impl PartialOrd for DeviceInfo {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DeviceInfo {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.ordering_key().cmp(&other.ordering_key())
+    }
+}
+
+/// Collects the unknown fields of a [PlatformInfo] or [DeviceInfo]
+///
+/// Values of any type are accepted such that snapshots of newer versions can be read, those which
+/// are no strings are kept in their JSON notation.
+#[cfg(feature = "serde")]
+fn deserialize_extra<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, String>, D::Error> {
+    let values = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .map(|(key, value)| match value {
+            serde_json::Value::String(text) => (key, text),
+            value => (key, value.to_string()),
+        })
+        .collect())
+}
+
+/// Implements setters of a builder which store the value in the field of the built struct
+///
+/// Values of fields marked with `=> Some` are wrapped in [Some], eg. `profile: String => Some`.
+macro_rules! impl_builder_setters(
+    ($builder:ident, $($field:ident: $field_type:ty $(=> $wrap:ident)?,)+) => {
+        impl $builder {
+            $(
+                /// Sets the
+                #[doc = stringify!($field)]
+                /// field
+                pub fn $field(mut self, $field: impl Into<$field_type>) -> Self {
+                    self.0.$field = $($wrap)?($field.into());
+                    self
+                }
+            )*
+        }
+    }
+);
+
+/// Constructs a [DeviceInfo] without an OpenCL runtime, eg. for tests and examples
+///
+/// The device starts out as a GPU of a placeholder vendor whose optional properties were not
+/// queried. Only the properties set explicitly differ from these defaults.
+/// ```
+/// use opencl3_select::DeviceInfo;
+/// let device = DeviceInfo::builder()
+///     .name("NVIDIA GeForce RTX 3060")
+///     .vendor("NVIDIA Corporation")
+///     .vendor_id(0x10de)
+///     .version("OpenCL 3.0 CUDA")
+///     .global_mem_size(12u64 << 30)
+///     .build();
+/// assert_eq!(device.vendor_id_text(), "NVIDIA");
+/// assert_eq!(device.type_text(), "CL_DEVICE_TYPE_GPU");
+/// assert_eq!(device.global_mem_size(), Some(12 << 30));
+/// assert_eq!(device.driver_version(), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DeviceInfoBuilder(DeviceInfo);
+
+impl Default for DeviceInfoBuilder {
+    fn default() -> Self {
+        Self(DeviceInfo {
+            platform: "Synthetic Platform".into(),
+            vendor: "Synthetic Vendor".into(),
+            vendor_id: 0,
+            vendor_id_text: vendor_id_text(0).into(),
+            name: "Synthetic Device".into(),
+            version: "OpenCL 3.0".into(),
+            r#type: CL_DEVICE_TYPE_GPU,
+            type_text: device_type_text(CL_DEVICE_TYPE_GPU).into(),
+            profile: Some("FULL_PROFILE".into()),
+            extensions: Some(String::new()),
+            opencl_c_version: None,
+            driver_version: None,
+            svm_mem_capability: None,
+            global_mem_size: None,
+            global_mem_cache_size: None,
+            global_mem_cacheline_size: None,
+            global_mem_cache_type: None,
+            global_mem_cache_type_text: None,
+            max_constant_buffer_size: None,
+            mem_base_addr_align: None,
+            host_unified_memory: None,
+            error_correction_support: None,
+            max_mem_alloc_size: None,
+            local_mem_size: None,
+            max_compute_units: None,
+            max_clock_frequency: None,
+            max_work_group_size: None,
+            image_support: None,
+            printf_buffer_size: None,
+            profiling_timer_resolution: None,
+            endian_little: None,
+            uuid: None,
+            luid: None,
+            partition_max_sub_devices: None,
+            partition_properties: None,
+            partition_affinity_domains: None,
+            numeric_version: None,
+            ils: None,
+            atomic_memory_capabilities: None,
+            atomic_fence_capabilities: None,
+            opencl_c_all_versions: None,
+            opencl_c_features: None,
+            host_cpu: None,
+            image_formats: None,
+            extra: BTreeMap::new(),
+        })
+    }
+}
+
+impl_builder_setters!(
+    DeviceInfoBuilder,
+    // PLATFORM
+    platform: String,
+    // VENDOR
+    vendor: String,
+    // Device
+    name: String,
+    version: String,
+    // OTHER
+    profile: String => Some,
+    extensions: String => Some,
+    opencl_c_version: String => Some,
+    driver_version: String => Some,
+    svm_mem_capability: cl_device_svm_capabilities => Some,
+    // MEMORY
+    global_mem_size: cl_ulong => Some,
+    global_mem_cache_size: cl_ulong => Some,
+    global_mem_cacheline_size: cl_uint => Some,
+    max_constant_buffer_size: cl_ulong => Some,
+    mem_base_addr_align: cl_uint => Some,
+    host_unified_memory: bool => Some,
+    error_correction_support: bool => Some,
+    max_mem_alloc_size: cl_ulong => Some,
+    local_mem_size: cl_ulong => Some,
+    // EXECUTION
+    max_compute_units: cl_uint => Some,
+    max_clock_frequency: cl_uint => Some,
+    max_work_group_size: cl_ulong => Some,
+    image_support: bool => Some,
+    printf_buffer_size: cl_ulong => Some,
+    profiling_timer_resolution: cl_ulong => Some,
+    endian_little: bool => Some,
+    // IDENTITY
+    uuid: [u8; 16] => Some,
+    luid: [u8; 8] => Some,
+    // PARTITIONING
+    partition_max_sub_devices: cl_uint => Some,
+    partition_properties: Vec<PartitionScheme> => Some,
+    partition_affinity_domains: AffinityDomains => Some,
+    // OPENCL 3.0
+    numeric_version: NumericVersion => Some,
+    ils: Vec<NameVersion> => Some,
+    atomic_memory_capabilities: AtomicCapabilities => Some,
+    atomic_fence_capabilities: AtomicCapabilities => Some,
+    opencl_c_all_versions: Vec<NameVersion> => Some,
+    opencl_c_features: Vec<NameVersion> => Some,
+    // HOST
+    host_cpu: CpuInfo => Some,
+    // IMAGES
+    image_formats: Vec<ImageFormat> => Some,
+);
+
+impl DeviceInfoBuilder {
+    /// Sets the vendor_id field together with its textual representation
+    pub fn vendor_id(mut self, vendor_id: cl_uint) -> Self {
+        self.0.vendor_id = vendor_id;
+        self.0.vendor_id_text = vendor_id_text(vendor_id).into();
+        self
+    }
+
+    /// Sets the type field together with its textual representation
+    pub fn r#type(mut self, r#type: cl_device_type) -> Self {
+        self.0.r#type = r#type;
+        self.0.type_text = device_type_text(r#type).into();
+        self
+    }
+
+    /// Sets the global_mem_cache_type field together with its textual representation
+    pub fn global_mem_cache_type(mut self, cache_type: cl_device_mem_cache_type) -> Self {
+        self.0.global_mem_cache_type = Some(cache_type);
+        self.0.global_mem_cache_type_text = Some(mem_cache_type_text(cache_type).into());
+        self
+    }
+
+    /// Adds a property which is not modelled by a field, see [extra](DeviceInfo::extra)
+    pub fn extra(mut self, property: impl Into<String>, value: impl ToString) -> Self {
+        self.0.extra.insert(property.into(), value.to_string());
+        self
+    }
+
+    /// The device with all properties set so far
+    pub fn build(self) -> DeviceInfo {
+        self.0
+    }
+}
+
+impl DeviceInfo {
+    /// Starts constructing a device without an OpenCL runtime, see [DeviceInfoBuilder]
+    pub fn builder() -> DeviceInfoBuilder {
+        DeviceInfoBuilder::default()
+    }
+}
+
+/// Constructs a [PlatformInfo] without an OpenCL runtime, eg. for tests and examples
+///
+/// The [platform](DeviceInfo::platform) of every added device is set to the name of the
+/// platform when it is built.
+/// ```
+/// use opencl3_select::{ClState, DeviceInfo, PlatformInfo};
+/// let platform = PlatformInfo::builder()
+///     .name("Portable Computing Language")
+///     .vendor("The pocl project")
+///     .device(DeviceInfo::builder().name("cpu-haswell").r#type(1 << 1).build())
+///     .build();
+/// let state = ClState::from_platforms(vec![platform]);
+/// let devices = state.get_all_devices();
+/// assert_eq!(devices[0].platform(), "Portable Computing Language");
+/// assert_eq!(devices[0].type_text(), "CL_DEVICE_TYPE_CPU");
+/// ```
+#[derive(Clone, Debug)]
+pub struct PlatformInfoBuilder(PlatformInfo);
+
+impl Default for PlatformInfoBuilder {
+    fn default() -> Self {
+        Self(PlatformInfo {
+            name: "Synthetic Platform".into(),
+            version: "OpenCL 3.0".into(),
+            vendor: "Synthetic Vendor".into(),
+            profile: "FULL_PROFILE".into(),
+            extensions: String::new(),
+            devices: Vec::new(),
+            extra: BTreeMap::new(),
+        })
+    }
+}
+
+impl_builder_setters!(
+    PlatformInfoBuilder,
+    name: String,
+    version: String,
+    vendor: String,
+    profile: String,
+    extensions: String,
+    devices: Vec<DeviceInfo>,
+);
+
+impl PlatformInfoBuilder {
+    /// Adds a device to the platform
+    pub fn device(mut self, device: DeviceInfo) -> Self {
+        self.0.devices.push(device);
+        self
+    }
+
+    /// Adds a property which is not modelled by a field, see [extra](PlatformInfo::extra)
+    pub fn extra(mut self, property: impl Into<String>, value: impl ToString) -> Self {
+        self.0.extra.insert(property.into(), value.to_string());
+        self
+    }
+
+    /// The platform with all properties set so far
+    pub fn build(mut self) -> PlatformInfo {
+        for device in self.0.devices.iter_mut() {
+            device.platform.clone_from(&self.0.name);
+        }
+        self.0
+    }
+}
+
+impl PlatformInfo {
+    /// Starts constructing a platform without an OpenCL runtime, see [PlatformInfoBuilder]
+    pub fn builder() -> PlatformInfoBuilder {
+        PlatformInfoBuilder::default()
+    }
+}
+
+/// Suggestions shown when no platforms or devices were found
+pub const SETUP_HINTS: [&str; 3] = [
+    "Install an OpenCL driver (ICD) for your hardware, eg. the GPU vendor driver or PoCL for CPUs",
+    "Check that the ICD loader finds it: /etc/OpenCL/vendors (or OCL_ICD_VENDORS) has to list it",
+    "Run `clinfo` to see which platforms and devices the loader reports",
+];
+
 /// The complete opencl state of the current machine
 #[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(rename_all = "snake_case")
+)]
 pub struct ClState {
+    /// All platforms found on the machine
     platforms: Vec<PlatformInfo>,
+    /// Time spent querying every platform and device
+    ///
+    /// Not serialized since it describes a single run of [get_setup].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    timings: Vec<PlatformTiming>,
+    /// Machine on which the platforms were found
+    ///
+    /// Missing in snapshots saved before it was recorded.
+    #[cfg_attr(feature = "serde", serde(default))]
+    host: Option<HostInfo>,
+    /// Platforms which could not be enumerated and are missing from the state
+    #[cfg_attr(feature = "serde", serde(default))]
+    errors: Vec<EnumerationError>,
 }
 
 impl ClState {
+    /// State consisting of the given platforms, eg. built with [PlatformInfo::builder]
+    ///
+    /// The platforms are kept in the given order, see [canonicalize](ClState::canonicalize).
+    pub fn from_platforms(platforms: Vec<PlatformInfo>) -> Self {
+        Self {
+            platforms,
+            timings: Vec::new(),
+            host: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Checks if no platform provides any device
+    ///
+    #[cfg_attr(feature = "serde", doc = "```")]
+    #[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+    /// use opencl3_select::ClState;
+    /// let state: ClState = serde_json::from_str(r#"{"platforms": []}"#)?;
+    /// assert!(state.is_empty());
+    /// assert!(state.get_all_devices().is_empty());
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.platforms
+            .iter()
+            .all(|platform| platform.devices.is_empty())
+    }
+
     /// Obtain all devices for any platform
     pub fn get_all_devices(&self) -> Vec<DeviceInfo> {
         self.platforms
             .iter()
-            .map(|pltfm| pltfm.devices.clone())
-            .flatten()
+            .flat_map(|pltfm| pltfm.devices.clone())
             .collect::<Vec<_>>()
     }
 
@@ -150,22 +1527,342 @@ impl ClState {
     pub fn get_platforms(&self) -> Vec<PlatformInfo> {
         self.platforms.clone()
     }
+
+    /// Borrowing variant of [get_platforms](ClState::get_platforms)
+    pub fn platforms_ref(&self) -> &[PlatformInfo] {
+        &self.platforms
+    }
+
+    /// Time spent querying every platform and device during [get_setup]
+    ///
+    /// Empty if the state was not obtained by enumerating the current machine,
+    /// for example when it was loaded from a file.
+    pub fn enumeration_timings(&self) -> &[PlatformTiming] {
+        &self.timings
+    }
+
+    /// Machine on which the platforms were found, if recorded
+    pub fn host(&self) -> Option<&HostInfo> {
+        self.host.as_ref()
+    }
+
+    /// Platforms which could not be enumerated, see [get_setup_with_timeout]
+    pub fn enumeration_errors(&self) -> &[EnumerationError] {
+        &self.errors
+    }
+
+    /// Sorts platforms and devices into their canonical order
+    ///
+    /// Platforms are ordered by vendor, name and version, devices within a platform by
+    /// vendor and name (see [Ord for DeviceInfo](DeviceInfo#impl-Ord-for-DeviceInfo)).
+    /// The order does not depend on the order in which the ICD loader reports platforms,
+    /// such that indices into [get_platforms](ClState::get_platforms) and
+    /// [get_all_devices](ClState::get_all_devices) are stable across runs on the same machine.
+    /// [get_setup] already returns the state in canonical order.
+    pub fn canonicalize(&mut self) {
+        self.platforms.sort_by(|a, b| {
+            (&a.vendor, &a.name, &a.version).cmp(&(&b.vendor, &b.name, &b.version))
+        });
+        for platform in self.platforms.iter_mut() {
+            platform.devices.sort();
+        }
+    }
+
+    /// Moves the platforms of the preferred vendors to the front, in the order of the preference
+    ///
+    /// A vendor matches if it is part of the vendor or name of the platform, ignoring case, such
+    /// that `AMD` matches the platform `AMD Accelerated Parallel Processing`. Platforms of other
+    /// vendors follow in their previous order.
+    /// ```
+    /// use opencl3_select::{ClState, PlatformInfo};
+    /// let platform = |name: &str, vendor: &str| PlatformInfo::builder().name(name).vendor(vendor).build();
+    /// let mut state = ClState::from_platforms(vec![
+    ///     platform("Portable Computing Language", "The pocl project"),
+    ///     platform("Intel(R) OpenCL", "Intel(R) Corporation"),
+    ///     platform("NVIDIA CUDA", "NVIDIA Corporation"),
+    /// ]);
+    /// state.sort_platforms_by_vendor_preference(&["NVIDIA", "AMD", "Intel"]);
+    /// let names: Vec<_> = state.platforms_ref().iter().map(|p| p.name()).collect();
+    /// assert_eq!(names, ["NVIDIA CUDA", "Intel(R) OpenCL", "Portable Computing Language"]);
+    /// ```
+    pub fn sort_platforms_by_vendor_preference(&mut self, vendors: &[impl AsRef<str>]) {
+        self.platforms.sort_by_key(|platform| {
+            vendor_preference(vendors, &[platform.vendor.as_str(), platform.name.as_str()])
+        });
+    }
+
+    /// Removes all devices for which the predicate returns `false`
+    ///
+    /// Platforms are kept even if none of their devices remain.
+    pub fn retain_devices(&mut self, mut predicate: impl FnMut(&DeviceInfo) -> bool) {
+        for platform in self.platforms.iter_mut() {
+            platform.devices.retain(&mut predicate);
+        }
+    }
+}
+
+/// Position of the first preferred vendor which is part of one of the names, ignoring case
+///
+/// Names without a preferred vendor come after all others.
+pub(crate) fn vendor_preference(vendors: &[impl AsRef<str>], names: &[&str]) -> usize {
+    let names: Vec<_> = names.iter().map(|name| name.to_lowercase()).collect();
+    vendors
+        .iter()
+        .position(|vendor| {
+            let vendor = vendor.as_ref().to_lowercase();
+            names.iter().any(|name| name.contains(&vendor))
+        })
+        .unwrap_or(vendors.len())
+}
+
+/// A platform which is missing from a [ClState] since its enumeration failed
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct EnumerationError {
+    /// Name of the platform
+    pub platform: String,
+    /// Description of the failure
+    pub message: String,
+}
+
+/// Time spent querying a single device during [get_setup]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeviceTiming {
+    /// Name of the device
+    pub name: String,
+    /// Time spent constructing its [DeviceInfo]
+    pub duration: Duration,
+}
+
+/// Time spent querying a platform and all of its devices during [get_setup]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlatformTiming {
+    /// Name of the platform
+    pub name: String,
+    /// Total time spent on the platform including all of its devices
+    pub duration: Duration,
+    /// Timings of the individual devices
+    pub devices: Vec<DeviceTiming>,
 }
 
 /// Constructs the complete state of the opencl setup of the current machine
-pub fn get_setup() -> Result<ClState, ClError> {
+///
+/// Platforms and devices are returned in their [canonical order](ClState::canonicalize).
+///
+/// The time spent on every platform and device is recorded and can be obtained with
+/// [ClState::enumeration_timings].
+#[cfg(feature = "opencl")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "opencl")))]
+pub fn get_setup() -> crate::Result<ClState> {
+    get_setup_with_level(QueryLevel::Full)
+}
+
+/// Variant of [get_setup] which only queries the device properties of the given level
+/// ```no_run
+/// use opencl3_select::QueryLevel;
+/// let cl_state = opencl3_select::get_setup_with_level(QueryLevel::Minimal)?;
+/// assert!(cl_state.get_all_devices()[0].extensions().is_none());
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+#[cfg(feature = "opencl")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "opencl")))]
+pub fn get_setup_with_level(level: QueryLevel) -> crate::Result<ClState> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("get_setup", ?level).entered();
     let mut platforms = vec![];
+    let mut timings = vec![];
 
-    for platform in opencl3::platform::get_platforms()? {
-        let mut devices = vec![];
-        for device_id in platform.get_devices(CL_DEVICE_TYPE_ALL)? {
-            let device = opencl3::device::Device::new(device_id);
-            let device_info = DeviceInfo::construct(&device)?;
-            devices.push(device_info);
-        }
-        let platform_info = PlatformInfo::construct(&platform, &devices)?;
+    for platform in query("platforms", opencl3::platform::get_platforms())? {
+        let (platform_info, timing) =
+            enumerate_platform(&platform, level, &AtomicBool::new(false))?;
+        timings.push(timing);
         platforms.push(platform_info);
     }
 
-    Ok(ClState { platforms })
+    let mut cl_state = ClState {
+        platforms,
+        timings,
+        host: Some(HostInfo::current()),
+        errors: vec![],
+    };
+    cl_state.canonicalize();
+    Ok(cl_state)
+}
+
+/// Variant of [get_setup_with_level] which gives up on platforms which take longer than the
+/// timeout
+///
+/// The platforms are listed and every platform is enumerated on its own thread such that a
+/// deadlocking ICD loader or ICD does not block the caller. Platforms which failed or did not
+/// finish within the timeout are missing from the state and recorded in
+/// [ClState::enumeration_errors] instead. Their threads are told to stop at the next device but
+/// can not be interrupted while an ICD is stuck inside a query.
+/// ```no_run
+/// use opencl3_select::QueryLevel;
+/// use std::time::Duration;
+/// let timeout = Duration::from_secs(5);
+/// let cl_state = opencl3_select::get_setup_with_timeout(QueryLevel::Standard, timeout)?;
+/// for error in cl_state.enumeration_errors() {
+///     eprintln!("skipped {}: {}", error.platform, error.message);
+/// }
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+#[cfg(feature = "opencl")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "opencl")))]
+pub fn get_setup_with_timeout(level: QueryLevel, timeout: Duration) -> crate::Result<ClState> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("get_setup", ?level, ?timeout).entered();
+    let deadline = Instant::now() + timeout;
+    let mut cl_state = ClState {
+        platforms: vec![],
+        timings: vec![],
+        host: Some(HostInfo::current()),
+        errors: vec![],
+    };
+
+    // The ICD loader opens every driver while listing the platforms, which may hang as well
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(query("platforms", opencl3::platform::get_platforms()));
+    });
+    let cl_platforms = match receiver.recv_timeout(timeout) {
+        Ok(cl_platforms) => cl_platforms?,
+        Err(_) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?timeout, "listing the platforms timed out");
+            cl_state.errors.push(EnumerationError {
+                platform: "ICD loader".to_string(),
+                message: format!("platforms were not listed within {timeout:?}"),
+            });
+            return Ok(cl_state);
+        }
+    };
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = std::sync::mpsc::channel();
+    for (index, platform) in cl_platforms.iter().copied().enumerate() {
+        let sender = sender.clone();
+        let cancel = Arc::clone(&cancel);
+        std::thread::spawn(move || {
+            // The receiver is gone if the timeout expired, the result is of no use then
+            if let Ok(name) = platform.name() {
+                let _ = sender.send(PlatformMessage::Name(index, name));
+            }
+            let result = enumerate_platform(&platform, level, &cancel);
+            let _ = sender.send(PlatformMessage::Done(index, Box::new(result)));
+        });
+    }
+    drop(sender);
+
+    let mut names: Vec<_> = (0..cl_platforms.len())
+        .map(|index| format!("platform {index}"))
+        .collect();
+    let mut results: Vec<_> = cl_platforms.iter().map(|_| None).collect();
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        match receiver.recv_timeout(remaining) {
+            Ok(PlatformMessage::Name(index, name)) => names[index] = name,
+            Ok(PlatformMessage::Done(index, result)) => results[index] = Some(*result),
+            // Either every platform is done or the timeout expired
+            Err(_) => break,
+        }
+    }
+    cancel.store(true, Ordering::Relaxed);
+
+    for (name, result) in names.into_iter().zip(results) {
+        let message = match result {
+            Some(Ok((platform_info, timing))) => {
+                cl_state.timings.push(timing);
+                cl_state.platforms.push(platform_info);
+                continue;
+            }
+            Some(Err(error)) => error.to_string(),
+            None => format!("enumeration did not finish within {timeout:?}"),
+        };
+        #[cfg(feature = "tracing")]
+        tracing::warn!(platform = name, message, "skipped platform");
+        cl_state.errors.push(EnumerationError {
+            platform: name,
+            message,
+        });
+    }
+    cl_state.canonicalize();
+    Ok(cl_state)
+}
+
+/// Progress of a platform enumerated by [get_setup_with_timeout] on its own thread
+#[cfg(feature = "opencl")]
+enum PlatformMessage {
+    /// Name of the platform with the given index, sent ahead such that a platform which hangs
+    /// later on can still be named
+    Name(usize, String),
+    /// Outcome of the enumeration of the platform with the given index
+    Done(usize, Box<crate::Result<(PlatformInfo, PlatformTiming)>>),
+}
+
+/// Queries the platform and all of its devices, stopping early once `cancel` is set
+#[cfg(feature = "opencl")]
+fn enumerate_platform(
+    platform: &opencl3::platform::Platform,
+    level: QueryLevel,
+    cancel: &AtomicBool,
+) -> crate::Result<(PlatformInfo, PlatformTiming)> {
+    let platform_start = Instant::now();
+    let platform_name = query("name", platform.name())?;
+    let mut devices = vec![];
+    let mut device_timings = vec![];
+    let device_ids = query("devices", platform.get_devices(CL_DEVICE_TYPE_ALL))
+        .map_err(|error| error.with_platform(&platform_name))?;
+    for device_id in device_ids {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(ClinfoError::EnumerationCancelled(platform_name).into());
+        }
+        let device_start = Instant::now();
+        let device = opencl3::device::Device::new(device_id);
+        let device_info = DeviceInfo::construct_with(&device, level)
+            .map_err(|error| error.with_platform(&platform_name))?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            platform = platform_name,
+            device = device_info.name_ref(),
+            duration = ?device_start.elapsed(),
+            "found device"
+        );
+        device_timings.push(DeviceTiming {
+            name: device_info.name(),
+            duration: device_start.elapsed(),
+        });
+        devices.push(device_info);
+    }
+    let platform_info = PlatformInfo::construct(platform, &devices)?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        platform = platform_name,
+        devices = devices.len(),
+        duration = ?platform_start.elapsed(),
+        "found platform"
+    );
+    let timing = PlatformTiming {
+        name: platform_info.name(),
+        duration: platform_start.elapsed(),
+        devices: device_timings,
+    };
+    Ok((platform_info, timing))
+}
+
+/// Asynchronous variant of [get_setup]
+///
+/// The OpenCL queries are run on a blocking thread of the current [tokio] runtime
+/// such that the calling task is not blocked during enumeration.
+/// ```no_run
+/// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// # runtime.block_on(async {
+/// let cl_state = opencl3_select::get_setup_async().await?;
+/// println!("{:?}", cl_state.get_platforms());
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// # });
+/// ```
+#[cfg(all(feature = "opencl", feature = "tokio"))]
+#[cfg_attr(doc_cfg, doc(cfg(all(feature = "opencl", feature = "tokio"))))]
+pub async fn get_setup_async() -> crate::Result<ClState> {
+    tokio::task::spawn_blocking(get_setup).await?
 }
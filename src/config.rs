@@ -0,0 +1,477 @@
+//! Configuration of the binary loaded from `opencl3_select.toml`
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::keymap::{Action, Key};
+use crate::locale::Locale;
+use opencl3_select::{
+    DeviceFingerprint, Result, SelectConfig, StorageError, StorageFormat, DEFAULT_CSV_COLUMNS,
+    PROJECT_SELECTION_FILE, SELECTION_HISTORY_FILE,
+};
+use ratatui::style::{palette::tailwind, Color};
+use serde::{Deserialize, Serialize};
+
+/// Name of the config file which is searched for in the config directory
+const CONFIG_FILE: &str = "opencl3_select.toml";
+
+/// Name of the file in the data directory which stores the device groups
+const GROUPS_FILE: &str = "groups.json";
+
+/// Name of the file in the data directory which stores the nicknames of devices
+const NICKNAMES_FILE: &str = "nicknames.json";
+
+/// Name of the file in the data directory which stores the notes about devices
+const NOTES_FILE: &str = "notes.json";
+
+/// Name of the file in the data directory which stores the hidden devices
+const HIDDEN_FILE: &str = "hidden.json";
+
+/// Name of the file in the data directory which stores the results of benchmark runs
+const BENCHMARKS_FILE: &str = "benchmarks.json";
+
+/// Name of the file in the cache directory which stores the state of the TUI
+const UI_STATE_FILE: &str = "ui_state.json";
+
+/// Name of the file in the cache directory which stores the last enumerated platforms and devices
+const ENUMERATION_CACHE_FILE: &str = "enumeration.json";
+
+/// Settings of the binary which can be changed by the user
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Colors used by the TUI and plain-text output
+    pub theme: Theme,
+    /// When to use colors, overridden by `--color`
+    pub color: ColorChoice,
+    /// Number of colors the terminal can show, detected from `COLORTERM` and `TERM` by default
+    pub color_depth: ColorDepth,
+    /// Language of the TUI, eg. `de`
+    pub locale: Locale,
+    /// Initial width of the left panes in percent, kept between 10 and 90
+    pub divider_percentage: u16,
+    /// Initial ordering of devices
+    pub sort: SortOrder,
+    /// Format used when printing without an explicit `--format`
+    pub format: StorageFormat,
+    /// Maps a pressed key to the built-in key it should act as, eg. `n = "j"`
+    ///
+    /// Kept for older configs, a config may set either `keys` or `bindings` but not both.
+    pub keys: BTreeMap<char, char>,
+    /// Binds keys to actions, eg. `"C-n" = "down"` or `p = "priority_0"`
+    pub bindings: HashMap<Key, Action>,
+    /// Devices which are hidden everywhere
+    pub select: SelectConfig,
+    /// Number of accepted selections which are kept in the history
+    pub history_length: usize,
+    /// Columns printed by `list --format csv`, named like the fields of a device
+    pub csv_columns: Vec<String>,
+    /// Harness files of kernels which are benchmarked next to the built-in benchmarks
+    pub benchmark_kernels: Vec<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            color: ColorChoice::default(),
+            color_depth: ColorDepth::default(),
+            locale: Locale::default(),
+            divider_percentage: 40,
+            sort: SortOrder::default(),
+            format: StorageFormat::Json,
+            keys: BTreeMap::new(),
+            bindings: HashMap::new(),
+            select: SelectConfig::default(),
+            history_length: 10,
+            csv_columns: DEFAULT_CSV_COLUMNS.map(String::from).to_vec(),
+            benchmark_kernels: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Default location of the config file inside the users config directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("opencl3_select").join(CONFIG_FILE))
+    }
+
+    /// Location of the history of accepted selections inside the users data directory
+    pub fn history_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("opencl3_select").join(SELECTION_HISTORY_FILE))
+    }
+
+    /// Location of the user-defined device groups inside the users data directory
+    pub fn groups_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("opencl3_select").join(GROUPS_FILE))
+    }
+
+    /// Location of the nicknames of devices inside the users data directory
+    pub fn nicknames_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("opencl3_select").join(NICKNAMES_FILE))
+    }
+
+    /// Location of the notes about devices inside the users data directory
+    pub fn notes_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("opencl3_select").join(NOTES_FILE))
+    }
+
+    /// Location of the devices hidden from lists and selections inside the users data directory
+    pub fn hidden_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("opencl3_select").join(HIDDEN_FILE))
+    }
+
+    /// Location of the benchmark results of all devices inside the users data directory
+    pub fn benchmarks_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("opencl3_select").join(BENCHMARKS_FILE))
+    }
+
+    /// Location of the state of the TUI inside the users cache directory, see [UiState]
+    pub fn ui_state_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("opencl3_select").join(UI_STATE_FILE))
+    }
+
+    /// Location of the last enumerated setup inside the users cache directory
+    ///
+    /// Read by shell completions which can not enumerate the devices themselves, see
+    /// [EnumerationCache](opencl3_select::EnumerationCache).
+    pub fn enumeration_cache_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("opencl3_select").join(ENUMERATION_CACHE_FILE))
+    }
+
+    /// Project-local selection in the working directory or the closest of its ancestors
+    ///
+    /// Like `.gitignore`, the file applies to all subdirectories and overrides the most recent
+    /// selection of the global history.
+    pub fn project_path() -> Option<PathBuf> {
+        std::env::current_dir()
+            .ok()?
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_SELECTION_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// Default location of the socket of `opencl3-select serve` inside the users runtime directory
+    ///
+    /// Falls back to the cache directory on platforms without a runtime directory.
+    pub fn socket_path() -> Option<PathBuf> {
+        dirs::runtime_dir()
+            .or_else(dirs::cache_dir)
+            .map(|dir| dir.join("opencl3_select.sock"))
+    }
+
+    /// Loads the config from the given path or from the [default path](Config::default_path)
+    ///
+    /// A missing file at the default path is not an error and yields the default config.
+    /// Configs which remap keys with both `keys` and `bindings` are rejected, as it would be
+    /// unclear which of the two wins.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let config: Self = match path {
+            Some(path) => opencl3_select::load(path)?,
+            None => match Self::default_path() {
+                Some(path) if path.exists() => opencl3_select::load(path)?,
+                _ => Self::default(),
+            },
+        };
+        if !config.keys.is_empty() && !config.bindings.is_empty() {
+            let message = "`keys` and `bindings` both remap keys, use only `bindings`";
+            return Err(StorageError::Deserialize(serde::de::Error::custom(message)).into());
+        }
+        Ok(config)
+    }
+}
+
+/// Colors used by the TUI and plain-text output
+///
+/// All styling goes through the theme such that colors can be disabled in one place, see
+/// [Theme::monochrome].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Background of pane titles, the status bar and section rows
+    pub header_bg: Color,
+    /// Background of even rows
+    pub normal_row: Color,
+    /// Background of odd rows
+    pub alt_row: Color,
+    /// Highlight of the selected row in the focused pane
+    pub selected_fg: Color,
+    /// Highlight of the selected row in unfocused panes
+    pub selected_fg_light: Color,
+    /// Foreground of regular text
+    pub text: Color,
+    /// Foreground of warnings, eg. slow platforms
+    pub warning: Color,
+    /// Foreground of errors, eg. the border of failure notifications
+    pub error: Color,
+    /// Foreground of NVIDIA platforms
+    pub nvidia: Color,
+    /// Foreground of Intel platforms
+    pub intel: Color,
+    /// Foreground of AMD platforms
+    pub amd: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            header_bg: tailwind::ZINC.c950,
+            normal_row: tailwind::ZINC.c950,
+            alt_row: tailwind::ZINC.c900,
+            selected_fg: tailwind::ZINC.c300,
+            selected_fg_light: tailwind::ZINC.c500,
+            text: tailwind::ZINC.c200,
+            warning: Color::Yellow,
+            error: Color::Red,
+            nvidia: Color::Green,
+            intel: Color::Blue,
+            amd: Color::Red,
+        }
+    }
+}
+
+impl Theme {
+    /// Theme which leaves every color to the terminal
+    ///
+    /// Highlights remain visible since they are also reversed and bold.
+    pub fn monochrome() -> Self {
+        Self {
+            header_bg: Color::Reset,
+            normal_row: Color::Reset,
+            alt_row: Color::Reset,
+            selected_fg: Color::Reset,
+            selected_fg_light: Color::Reset,
+            text: Color::Reset,
+            warning: Color::Reset,
+            error: Color::Reset,
+            nvidia: Color::Reset,
+            intel: Color::Reset,
+            amd: Color::Reset,
+        }
+    }
+
+    /// Theme with every color replaced by the closest one the terminal can show
+    ///
+    /// Terminals with 16 colors render bright black like black on many serial consoles, so it is
+    /// avoided and the greys of the palette become black, white or light grey instead.
+    pub fn with_color_depth(self, depth: ColorDepth) -> Self {
+        let reduce = |color| reduce_color(color, depth);
+        Self {
+            header_bg: reduce(self.header_bg),
+            normal_row: reduce(self.normal_row),
+            alt_row: reduce(self.alt_row),
+            selected_fg: reduce(self.selected_fg),
+            selected_fg_light: reduce(self.selected_fg_light),
+            text: reduce(self.text),
+            warning: reduce(self.warning),
+            error: reduce(self.error),
+            nvidia: reduce(self.nvidia),
+            intel: reduce(self.intel),
+            amd: reduce(self.amd),
+        }
+    }
+
+    /// Colors the text for printing to a terminal, unless the color is [Color::Reset]
+    pub fn paint(text: &str, color: Color) -> String {
+        use crossterm::style::Stylize;
+        match color {
+            Color::Reset => text.to_string(),
+            color => text.with(color.into()).to_string(),
+        }
+    }
+}
+
+/// When to use colors in the TUI and in plain-text output
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Use colors if writing to a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    /// Always use colors
+    Always,
+    /// Never use colors
+    Never,
+}
+
+impl ColorChoice {
+    /// Checks if output written to a terminal (or not) should be colored
+    ///
+    /// See [no-color.org](https://no-color.org) for the meaning of `NO_COLOR`.
+    pub fn enabled(self, terminal: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+        }
+    }
+}
+
+/// Number of colors a terminal can show
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorDepth {
+    /// Detect the depth from the environment, see [ColorDepth::detect]
+    #[default]
+    Auto,
+    /// The 16 colors of ANSI terminals, eg. the Linux console or serial consoles
+    Basic,
+    /// The 256 colors of the xterm palette
+    Ansi256,
+    /// Arbitrary RGB colors
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// The configured depth, or the one detected from the environment for [ColorDepth::Auto]
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto => Self::detect(),
+            depth => depth,
+        }
+    }
+
+    /// Depth announced by `COLORTERM` and `TERM`, assuming 16 colors if neither tells
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" || term.ends_with("-direct") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else if cfg!(windows) && std::env::var_os("WT_SESSION").is_some() {
+            Self::TrueColor
+        } else {
+            Self::Basic
+        }
+    }
+}
+
+/// The 16 ANSI colors with their usual RGB values, except for bright black
+///
+/// Bright black is rendered like black by many consoles and so left out.
+const BASIC_COLORS: [(Color, (u8, u8, u8)); 15] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// The closest color of the given depth
+fn reduce_color(color: Color, depth: ColorDepth) -> Color {
+    let distance = |(r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)| {
+        [(r1, r2), (g1, g2), (b1, b2)]
+            .into_iter()
+            .map(|(a, b)| (i32::from(a) - i32::from(b)).pow(2))
+            .sum::<i32>()
+    };
+    match (color, depth) {
+        (Color::DarkGray, ColorDepth::Basic) => Color::Gray,
+        (Color::Rgb(r, g, b), ColorDepth::Basic) => BASIC_COLORS
+            .iter()
+            .min_by_key(|(_, rgb)| distance(*rgb, (r, g, b)))
+            .map_or(color, |(basic, _)| *basic),
+        (Color::Rgb(r, g, b), ColorDepth::Ansi256) => {
+            // Closest entry of the 6x6x6 color cube and of the grey ramp of the xterm palette
+            let level = |c: u8| (u16::from(c) * 5 + 127) / 255;
+            let value = |level: u16| if level == 0 { 0 } else { 55 + 40 * level as u8 };
+            let (lr, lg, lb) = (level(r), level(g), level(b));
+            let cube = (value(lr), value(lg), value(lb));
+            let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+            let grey_index = (average.saturating_sub(3) / 10).min(23) as u8;
+            let grey = 8 + 10 * grey_index;
+            match distance((grey, grey, grey), (r, g, b)) < distance(cube, (r, g, b)) {
+                true => Color::Indexed(232 + grey_index),
+                false => Color::Indexed(16 + (36 * lr + 6 * lg + lb) as u8),
+            }
+        }
+        (color, _) => color,
+    }
+}
+
+/// Layout and position of the TUI which are restored when it is opened again
+///
+/// Fields which are missing, eg. since the highlighted device disappeared, keep the defaults of
+/// the [Config].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct UiState {
+    /// Width of the left pane in percent
+    pub divider_percentage: Option<u16>,
+    /// Name of the highlighted platform
+    pub platform: Option<String>,
+    /// Highlighted device of the highlighted platform
+    pub device: Option<DeviceFingerprint>,
+    /// Ordering of the devices
+    pub sort: Option<SortOrder>,
+    /// Filter of the devices
+    pub filter: String,
+}
+
+impl UiState {
+    /// Loads the state from the given path, a missing or unreadable file yields the default
+    ///
+    /// The state is a mere convenience, so a broken file must not keep the TUI from starting.
+    pub fn load_or_default(path: &Path) -> Self {
+        opencl3_select::load(path).unwrap_or_default()
+    }
+
+    /// Saves the state to the given path, creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(StorageError::Io)?;
+        }
+        opencl3_select::save(self, path)
+    }
+}
+
+/// Ordering of devices within a platform
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Canonical order of the devices, see [ClState::canonicalize](opencl3_select::ClState::canonicalize)
+    #[default]
+    Enumeration,
+    /// Alphabetically by name
+    Name,
+    /// Alphabetically by vendor
+    Vendor,
+    /// By device type, then by name
+    Type,
+}
+
+impl SortOrder {
+    /// The order which follows when cycling through all variants
+    pub fn next(self) -> Self {
+        match self {
+            Self::Enumeration => Self::Name,
+            Self::Name => Self::Vendor,
+            Self::Vendor => Self::Type,
+            Self::Type => Self::Enumeration,
+        }
+    }
+}
+
+impl std::fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Enumeration => write!(f, "enumeration"),
+            Self::Name => write!(f, "name"),
+            Self::Vendor => write!(f, "vendor"),
+            Self::Type => write!(f, "type"),
+        }
+    }
+}
@@ -0,0 +1,458 @@
+//! Check whether enumerated devices are actually usable
+
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use std::time::Instant;
+
+use opencl3::kernel::{ExecuteKernel, Kernel};
+use opencl3::memory::{Buffer, ClMem, CL_MEM_READ_WRITE};
+use opencl3::program::Program;
+use opencl3::types::{cl_float, cl_uint, CL_BLOCKING};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{BenchmarkError, BenchmarkEvent, BenchmarkResult, DeviceInfo, StorageError};
+
+/// Trivial kernel which is built by [compile_test] if no other source is at hand
+pub const SMOKE_TEST_KERNEL: &str = r#"
+__kernel void smoke_test(__global float *values) {
+    values[get_global_id(0)] *= 2.0f;
+}
+"#;
+
+/// Outcome of building a kernel on a device
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct CompileReport {
+    /// Whether the program was built successfully
+    pub success: bool,
+    /// Build log reported by the compiler of the device
+    pub build_log: String,
+}
+
+/// Builds the given OpenCL C source on the device and reports the result
+///
+/// Some devices enumerate fine but fail as soon as a program is compiled for them.
+/// A failing build is not an error but reported via [CompileReport::success].
+/// Errors are returned if the device can not be found or no context can be created.
+/// ```no_run
+/// use opencl3_select::{compile_test, SMOKE_TEST_KERNEL};
+/// let cl_state = opencl3_select::get_setup()?;
+/// for device in cl_state.get_all_devices() {
+///     let report = compile_test(&device, SMOKE_TEST_KERNEL)?;
+///     println!("{}: {}", device.name(), report.success);
+/// }
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+pub fn compile_test(device: &DeviceInfo, source: &str) -> crate::Result<CompileReport> {
+    let cl_device = device.resolve()?;
+    let context = opencl3::context::Context::from_device(&cl_device)?;
+    let mut program = Program::create_from_source(&context, source)?;
+    let success = program.build(&[cl_device.id()], "").is_ok();
+    let build_log = program.get_build_log(cl_device.id()).unwrap_or_default();
+    Ok(CompileReport { success, build_log })
+}
+
+/// Kernel timed by [Benchmark::Saxpy]
+const SAXPY_KERNEL: &str = r#"
+__kernel void saxpy(__global float *y, __global const float *x, float a) {
+    size_t i = get_global_id(0);
+    y[i] += a * x[i];
+}
+"#;
+
+/// Number of timed repetitions of every benchmark, after one untimed warm-up
+const ROUNDS: u32 = 10;
+
+/// Measurements which can be run on any device to compare it with others or earlier runs
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Benchmark {
+    /// Mean runtime of `y = a * x + y` on 4 Mi floats
+    Saxpy,
+    /// Throughput of blocking writes of 64 MiB from the host to the device
+    WriteBandwidth,
+}
+
+impl Benchmark {
+    /// All benchmarks in the order in which they are run
+    pub const ALL: [Self; 2] = [Self::Saxpy, Self::WriteBandwidth];
+
+    /// Name under which the results are stored, eg. `write_bandwidth`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Saxpy => "saxpy",
+            Self::WriteBandwidth => "write_bandwidth",
+        }
+    }
+
+    /// Runs the benchmark on the device, blocking until it is done
+    pub fn run(&self, device: &DeviceInfo) -> crate::Result<BenchmarkResult> {
+        let (context, queue) = device.create_queue_with_properties(0, 0)?;
+        let result = |value, unit: &str, higher_is_better| BenchmarkResult {
+            name: self.name().to_string(),
+            value,
+            unit: unit.to_string(),
+            higher_is_better,
+        };
+        match self {
+            Self::Saxpy => {
+                let n = 1 << 22;
+                let mut program = Program::create_from_source(&context, SAXPY_KERNEL)?;
+                program.build(context.devices(), "")?;
+                let kernel = Kernel::create(&program, "saxpy")?;
+                let zeros = vec![0.0 as cl_float; n];
+                // SAFETY: both buffers hold `n` floats and are written before the kernel runs
+                // on `n` work items, all commands are finished before the buffers are dropped
+                unsafe {
+                    let mut y = Buffer::<cl_float>::create(
+                        &context,
+                        CL_MEM_READ_WRITE,
+                        n,
+                        std::ptr::null_mut(),
+                    )?;
+                    let mut x = Buffer::<cl_float>::create(
+                        &context,
+                        CL_MEM_READ_WRITE,
+                        n,
+                        std::ptr::null_mut(),
+                    )?;
+                    queue.enqueue_write_buffer(&mut y, CL_BLOCKING, 0, &zeros, &[])?;
+                    queue.enqueue_write_buffer(&mut x, CL_BLOCKING, 0, &zeros, &[])?;
+                    let run = || -> crate::Result<()> {
+                        ExecuteKernel::new(&kernel)
+                            .set_arg(&y)
+                            .set_arg(&x)
+                            .set_arg(&(2.0 as cl_float))
+                            .set_global_work_size(n)
+                            .enqueue_nd_range(&queue)?;
+                        Ok(queue.finish()?)
+                    };
+                    run()?;
+                    let start = Instant::now();
+                    for _ in 0..ROUNDS {
+                        run()?;
+                    }
+                    let millis = start.elapsed().as_secs_f64() * 1e3 / f64::from(ROUNDS);
+                    Ok(result(millis, "ms", false))
+                }
+            }
+            Self::WriteBandwidth => {
+                let n = 64 << 20;
+                let data = vec![0u8; n];
+                // SAFETY: the buffer holds `n` bytes and all writes are blocking
+                unsafe {
+                    let mut buffer =
+                        Buffer::<u8>::create(&context, CL_MEM_READ_WRITE, n, std::ptr::null_mut())?;
+                    queue.enqueue_write_buffer(&mut buffer, CL_BLOCKING, 0, &data, &[])?;
+                    let start = Instant::now();
+                    for _ in 0..ROUNDS {
+                        queue.enqueue_write_buffer(&mut buffer, CL_BLOCKING, 0, &data, &[])?;
+                    }
+                    let bytes = n as f64 * f64::from(ROUNDS);
+                    Ok(result(
+                        bytes / start.elapsed().as_secs_f64() / 1e9,
+                        "GB/s",
+                        true,
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// First word of every SPIR-V module, in the byte order of the file
+const SPIRV_MAGIC: [u8; 4] = [0x03, 0x02, 0x23, 0x07];
+
+/// Argument of a [KernelBenchmark], given in the order of the parameters of the kernel
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum KernelArg {
+    /// Global buffer of this many bytes, filled with zeros before the first run
+    Buffer(usize),
+    /// Local memory of this many bytes
+    Local(usize),
+    /// Scalar of type `int`
+    Int(i32),
+    /// Scalar of type `uint`
+    Uint(u32),
+    /// Scalar of type `long`
+    Long(i64),
+    /// Scalar of type `ulong`
+    Ulong(u64),
+    /// Scalar of type `float`
+    Float(f32),
+    /// Scalar of type `double`
+    Double(f64),
+}
+
+/// Default of [KernelBenchmark::iterations]
+#[cfg(feature = "serde")]
+fn default_iterations() -> u32 {
+    ROUNDS
+}
+
+/// Kernel of the user which is timed like the built-in [Benchmark]s
+///
+/// Ranks devices by the performance of the actual workload rather than generic
+/// microbenchmarks. The source is OpenCL C or a SPIR-V module, which is recognized by its magic
+/// number. The result is the mean runtime in `ms` of `iterations` runs after one untimed
+/// warm-up, stored under `name`.
+/// ```
+/// use opencl3_select::{KernelArg, KernelBenchmark};
+/// let harness: KernelBenchmark = toml::from_str(r#"
+///     name = "stencil"
+///     source = "kernels/stencil.cl"
+///     kernel = "stencil_2d"
+///     build_options = "-cl-fast-relaxed-math"
+///     global_size = [1024, 1024]
+///     local_size = [16, 16]
+///     args = [{ buffer = 4194304 }, { buffer = 4194304 }, { float = 0.25 }]
+/// "#)?;
+/// assert_eq!(harness.iterations, 10);
+/// assert_eq!(harness.args[2], KernelArg::Float(0.25));
+/// # Ok::<(), toml::de::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct KernelBenchmark {
+    /// Name under which the results are stored, eg. `stencil`
+    pub name: String,
+    /// File containing the OpenCL C source or SPIR-V module
+    pub source: PathBuf,
+    /// Name of the kernel function which is timed
+    pub kernel: String,
+    /// Options passed to the compiler, eg. `-cl-std=CL2.0`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub build_options: String,
+    /// Number of work items in each of up to three dimensions
+    pub global_size: Vec<usize>,
+    /// Size of the work groups in each dimension, chosen by the driver if not given
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub local_size: Option<Vec<usize>>,
+    /// Number of timed runs
+    #[cfg_attr(feature = "serde", serde(default = "default_iterations"))]
+    pub iterations: u32,
+    /// Arguments of the kernel
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub args: Vec<KernelArg>,
+}
+
+impl KernelBenchmark {
+    /// Reads a harness from a file whose format is taken from its extension
+    ///
+    /// A relative [source](KernelBenchmark::source) is resolved against the directory of the
+    /// harness.
+    #[cfg(feature = "storage")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage")))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let mut harness: Self = crate::load(path)?;
+        if let Some(dir) = path.parent() {
+            harness.source = dir.join(&harness.source);
+        }
+        Ok(harness)
+    }
+
+    /// Builds and times the kernel on the device, blocking until it is done
+    ///
+    /// Fails with [BenchmarkError::InvalidKernel] if the harness does not fit the kernel, eg. if
+    /// the build fails or the number of arguments differs.
+    pub fn run(&self, device: &DeviceInfo) -> crate::Result<BenchmarkResult> {
+        let invalid = |message: String| BenchmarkError::InvalidKernel {
+            kernel: self.name.clone(),
+            message,
+        };
+        if !(1..=3).contains(&self.global_size.len()) {
+            return Err(invalid("global_size needs one to three dimensions".into()).into());
+        }
+        if let Some(local_size) = &self.local_size {
+            if local_size.len() != self.global_size.len() {
+                return Err(
+                    invalid("local_size and global_size differ in dimensions".into()).into(),
+                );
+            }
+        }
+        let source = std::fs::read(&self.source).map_err(StorageError::Io)?;
+        let (context, queue) = device.create_queue_with_properties(0, 0)?;
+        let mut program = match source.starts_with(&SPIRV_MAGIC) {
+            true => Program::create_from_il(&context, &source)?,
+            false => Program::create_from_source(&context, &String::from_utf8_lossy(&source))?,
+        };
+        if program
+            .build(context.devices(), &self.build_options)
+            .is_err()
+        {
+            let log = program
+                .get_build_log(context.devices()[0])
+                .unwrap_or_default();
+            return Err(invalid(format!("build failed\n{log}")).into());
+        }
+        let kernel = Kernel::create(&program, &self.kernel)?;
+        let parameters = kernel.num_args()? as usize;
+        if parameters != self.args.len() {
+            return Err(invalid(format!(
+                "kernel takes {parameters} arguments but {} are given",
+                self.args.len()
+            ))
+            .into());
+        }
+        // SAFETY: every argument is set with the size of its declared type, buffers are written
+        // before the first run and outlive all commands, which are finished before returning
+        unsafe {
+            let mut buffers = vec![];
+            for (index, arg) in self.args.iter().enumerate() {
+                let index = index as cl_uint;
+                match arg {
+                    KernelArg::Buffer(size) => {
+                        let mut buffer = Buffer::<u8>::create(
+                            &context,
+                            CL_MEM_READ_WRITE,
+                            *size,
+                            std::ptr::null_mut(),
+                        )?;
+                        let zeros = vec![0u8; *size];
+                        queue.enqueue_write_buffer(&mut buffer, CL_BLOCKING, 0, &zeros, &[])?;
+                        kernel.set_arg(index, &buffer.get())?;
+                        buffers.push(buffer);
+                    }
+                    KernelArg::Local(size) => kernel.set_arg_local_buffer(index, *size)?,
+                    KernelArg::Int(value) => kernel.set_arg(index, value)?,
+                    KernelArg::Uint(value) => kernel.set_arg(index, value)?,
+                    KernelArg::Long(value) => kernel.set_arg(index, value)?,
+                    KernelArg::Ulong(value) => kernel.set_arg(index, value)?,
+                    KernelArg::Float(value) => kernel.set_arg(index, value)?,
+                    KernelArg::Double(value) => kernel.set_arg(index, value)?,
+                }
+            }
+            let local_size = self
+                .local_size
+                .as_ref()
+                .map_or(std::ptr::null(), |local_size| local_size.as_ptr());
+            let run = || -> crate::Result<()> {
+                queue.enqueue_nd_range_kernel(
+                    kernel.get(),
+                    self.global_size.len() as cl_uint,
+                    std::ptr::null(),
+                    self.global_size.as_ptr(),
+                    local_size,
+                    &[],
+                )?;
+                Ok(queue.finish()?)
+            };
+            run()?;
+            let iterations = self.iterations.max(1);
+            let start = Instant::now();
+            for _ in 0..iterations {
+                run()?;
+            }
+            Ok(BenchmarkResult {
+                name: self.name.clone(),
+                value: start.elapsed().as_secs_f64() * 1e3 / f64::from(iterations),
+                unit: "ms".into(),
+                higher_is_better: false,
+            })
+        }
+    }
+}
+
+/// Runs all [Benchmark]s on the devices in the background and reports their progress
+///
+/// See [spawn_benchmarks_with] to run kernels of the user as well.
+///
+/// Devices which share their memory with the host, ie. CPUs and integrated GPUs, would disturb
+/// each others measurements and are therefore benchmarked one after another on a single thread.
+/// Every other device gets a thread of its own. Events refer to devices by their index in
+/// `devices` and the receiver disconnects once all benchmarks are completed.
+/// ```no_run
+/// use opencl3_select::{spawn_benchmarks, Benchmark, BenchmarkProgress};
+/// let devices = opencl3_select::get_setup()?.get_all_devices();
+/// let mut progress = vec![BenchmarkProgress::new(Benchmark::ALL.len()); devices.len()];
+/// for event in spawn_benchmarks(devices.clone()) {
+///     progress[event.device()].apply(event);
+/// }
+/// for (device, progress) in devices.iter().zip(progress) {
+///     println!("{}: {:?}", device.name(), progress.results);
+/// }
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+pub fn spawn_benchmarks(devices: Vec<DeviceInfo>) -> mpsc::Receiver<BenchmarkEvent> {
+    spawn_benchmarks_with(devices, vec![])
+}
+
+/// Variant of [spawn_benchmarks] which runs the given [KernelBenchmark]s after the built-in
+/// [Benchmark]s
+pub fn spawn_benchmarks_with(
+    devices: Vec<DeviceInfo>,
+    kernels: Vec<KernelBenchmark>,
+) -> mpsc::Receiver<BenchmarkEvent> {
+    let (sender, receiver) = mpsc::channel();
+    let kernels = Arc::new(kernels);
+    let (shared, dedicated): (Vec<_>, Vec<_>) =
+        devices.into_iter().enumerate().partition(|(_, device)| {
+            device.type_text_ref().ends_with("CPU") || device.host_unified_memory() == Some(true)
+        });
+    let groups = dedicated
+        .into_iter()
+        .map(|device| vec![device])
+        .chain((!shared.is_empty()).then_some(shared));
+    for group in groups {
+        let sender = sender.clone();
+        let kernels = Arc::clone(&kernels);
+        std::thread::spawn(move || {
+            for (index, device) in group {
+                for benchmark in Benchmark::ALL {
+                    let name = benchmark.name().to_string();
+                    if !report(&sender, index, name, || benchmark.run(&device)) {
+                        return;
+                    }
+                }
+                for kernel in kernels.iter() {
+                    if !report(&sender, index, kernel.name.clone(), || kernel.run(&device)) {
+                        return;
+                    }
+                }
+            }
+        });
+    }
+    receiver
+}
+
+/// Runs one benchmark and sends its events, returns `false` once the receiver is gone
+fn report(
+    sender: &mpsc::Sender<BenchmarkEvent>,
+    device: usize,
+    benchmark: String,
+    run: impl FnOnce() -> crate::Result<BenchmarkResult>,
+) -> bool {
+    let started = BenchmarkEvent::Started {
+        device,
+        benchmark: benchmark.clone(),
+    };
+    if sender.send(started).is_err() {
+        return false;
+    }
+    let event = match run() {
+        Ok(result) => BenchmarkEvent::Finished { device, result },
+        Err(error) => BenchmarkEvent::Failed {
+            device,
+            benchmark,
+            message: error_message(&error),
+        },
+    };
+    sender.send(event).is_ok()
+}
+
+/// Describes the error including its underlying causes
+fn error_message(error: &crate::ClSelectError) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message += &format!(": {cause}");
+        source = cause.source();
+    }
+    message
+}
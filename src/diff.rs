@@ -0,0 +1,170 @@
+//! Differences between two snapshots of the OpenCL setup, eg. before and after a driver rollout
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ClState, DeviceFingerprint, DeviceInfo};
+
+/// Property of a device whose value differs between two snapshots
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PropertyChange {
+    /// Name of the property as serialized, eg. `driver_version`
+    pub property: String,
+    /// Value in the old snapshot, empty if it was not queried
+    pub old: String,
+    /// Value in the new snapshot, empty if it was not queried
+    pub new: String,
+}
+
+/// Device present in both snapshots whose properties differ
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DeviceChange {
+    /// The device as found in the new snapshot
+    pub device: DeviceFingerprint,
+    /// Properties which differ, ordered by their name
+    pub changes: Vec<PropertyChange>,
+}
+
+/// Devices which were added, removed or changed between two snapshots
+///
+/// Devices are matched by their UUID if both snapshots know it and by their platform, vendor
+/// and name otherwise, such that a new driver shows up as a changed version rather than as a
+/// removed and an added device.
+/// ```
+/// use opencl3_select::{ClState, DeviceInfo, PlatformInfo};
+/// let snapshot = |driver: &str, memory: u64| {
+///     ClState::from_platforms(vec![PlatformInfo::builder()
+///         .name("CUDA")
+///         .device(
+///             DeviceInfo::builder()
+///                 .name("RTX 4090")
+///                 .driver_version(driver)
+///                 .global_mem_size(memory)
+///                 .build(),
+///         )
+///         .build()])
+/// };
+/// let diff = snapshot("535.104", 24 << 30).diff(&snapshot("545.29", 24 << 30));
+/// assert!(diff.added.is_empty() && diff.removed.is_empty());
+/// assert_eq!(diff.changed[0].changes[0].property, "driver_version");
+/// assert_eq!(
+///     diff.to_string(),
+///     "~ RTX 4090 (CUDA)\n    driver_version: 535.104 -> 545.29\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateDiff {
+    /// Devices which are only part of the new snapshot
+    pub added: Vec<DeviceFingerprint>,
+    /// Devices which are only part of the old snapshot
+    pub removed: Vec<DeviceFingerprint>,
+    /// Devices which are part of both snapshots but differ
+    pub changed: Vec<DeviceChange>,
+}
+
+impl StateDiff {
+    /// Checks if both snapshots describe the same devices
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl ClState {
+    /// Compares this snapshot as the old one with a newer snapshot
+    pub fn diff(&self, new: &ClState) -> StateDiff {
+        let mut unmatched = new.get_all_devices();
+        let mut diff = StateDiff::default();
+        for old_device in self.get_all_devices() {
+            match unmatched
+                .iter()
+                .position(|new_device| same_device(&old_device, new_device))
+            {
+                Some(i) => {
+                    let new_device = unmatched.remove(i);
+                    let changes = property_changes(&old_device, &new_device);
+                    if !changes.is_empty() {
+                        diff.changed.push(DeviceChange {
+                            device: new_device.fingerprint(),
+                            changes,
+                        });
+                    }
+                }
+                None => diff.removed.push(old_device.fingerprint()),
+            }
+        }
+        diff.added = unmatched.iter().map(DeviceInfo::fingerprint).collect();
+        diff
+    }
+}
+
+/// Checks if two devices of different snapshots are the same physical device
+fn same_device(old: &DeviceInfo, new: &DeviceInfo) -> bool {
+    match (old.uuid(), new.uuid()) {
+        (Some(old), Some(new)) => old == new,
+        _ => {
+            old.platform_ref() == new.platform_ref()
+                && old.vendor_id() == new.vendor_id()
+                && old.name_ref() == new.name_ref()
+        }
+    }
+}
+
+/// Serialized properties of the device as text, see [to_csv_with](crate::to_csv_with)
+fn properties(device: &DeviceInfo) -> std::collections::BTreeMap<String, String> {
+    let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(device) else {
+        return Default::default();
+    };
+    fields
+        .into_iter()
+        .map(|(property, value)| {
+            let value = match value {
+                serde_json::Value::Null => String::new(),
+                serde_json::Value::String(text) => text,
+                other => other.to_string(),
+            };
+            (property, value)
+        })
+        .collect()
+}
+
+/// Properties whose values differ between the devices, missing ones count as empty
+fn property_changes(old: &DeviceInfo, new: &DeviceInfo) -> Vec<PropertyChange> {
+    let old = properties(old);
+    let mut new = properties(new);
+    let mut changes: Vec<_> = old
+        .into_iter()
+        .map(|(property, old)| {
+            let new = new.remove(&property).unwrap_or_default();
+            PropertyChange { property, old, new }
+        })
+        .collect();
+    changes.extend(new.into_iter().map(|(property, new)| PropertyChange {
+        property,
+        old: String::new(),
+        new,
+    }));
+    changes.retain(|change| change.old != change.new);
+    changes.sort_by(|a, b| a.property.cmp(&b.property));
+    changes
+}
+
+impl std::fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for device in &self.added {
+            writeln!(f, "+ {} ({})", device.name, device.platform)?;
+        }
+        for device in &self.removed {
+            writeln!(f, "- {} ({})", device.name, device.platform)?;
+        }
+        for change in &self.changed {
+            writeln!(f, "~ {} ({})", change.device.name, change.device.platform)?;
+            for property in &change.changes {
+                writeln!(
+                    f,
+                    "    {}: {} -> {}",
+                    property.property, property.old, property.new
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,13 +1,295 @@
+//! Terminal display of the opencl state
+
 use crossterm::{
-    event::{self, Event, KeyCode},
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
     ExecutableCommand,
 };
 use ratatui::{prelude::*, widgets::*};
-use std::io::{self, stdout};
+use std::io::{self, stdout, Stdout};
+use std::panic::PanicHookInfo;
+use std::sync::Arc;
+
+use crate::DisplayError;
+
+/// Features of the terminal which differ between platforms and terminal emulators
+///
+/// The legacy Windows console host (conhost) draws with fonts which lack arrows and most other
+/// symbols, while Windows Terminal, ConEmu and the terminals of other platforms render them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TerminalCapabilities {
+    /// Whether the TUI is drawn on the alternate screen, otherwise it is drawn on the cleared
+    /// main screen
+    pub alternate_screen: bool,
+    /// Whether symbols beyond ASCII and box drawing are rendered, see [ascii_fallback]
+    pub unicode: bool,
+}
+
+impl TerminalCapabilities {
+    /// Capabilities of the terminal this process runs in
+    pub fn detect() -> Self {
+        Self::from_env(cfg!(windows), |variable| std::env::var(variable).ok())
+    }
+
+    /// Capabilities of a terminal on Windows or another platform with the given environment
+    /// ```
+    /// use opencl3_select::TerminalCapabilities;
+    /// let conhost = TerminalCapabilities::from_env(true, |_| None);
+    /// assert!(!conhost.unicode);
+    ///
+    /// let windows_terminal = TerminalCapabilities::from_env(true, |variable| {
+    ///     (variable == "WT_SESSION").then(|| "0b1d".to_owned())
+    /// });
+    /// assert!(windows_terminal.unicode);
+    /// assert!(TerminalCapabilities::from_env(false, |_| None).unicode);
+    /// ```
+    pub fn from_env(windows: bool, var: impl Fn(&str) -> Option<String>) -> Self {
+        let set = |variable| var(variable).is_some_and(|value| !value.is_empty());
+        let conhost = windows
+            && !set("WT_SESSION")
+            && !set("TERM_PROGRAM")
+            && var("ConEmuANSI").as_deref() != Some("ON");
+        Self {
+            alternate_screen: true,
+            unicode: !conhost,
+        }
+    }
+}
+
+/// Keeps the terminal in raw mode and on the alternate screen until it is dropped
+///
+/// Dropping the guard restores the terminal on every way out of a TUI, including errors and
+/// panics, such that embedders never leave the terminal of their users broken. A panic hook
+/// restores it before the panic message is printed, which would otherwise be lost on the
+/// alternate screen. The guard dereferences to the [Terminal] to draw on.
+///
+/// Terminals without an alternate screen, eg. some serial consoles and old Windows consoles,
+/// get the TUI drawn on their cleared main screen instead.
+/// ```no_run
+/// use opencl3_select::{DisplayError, TerminalGuard};
+/// use ratatui::widgets::Paragraph;
+/// let mut terminal = TerminalGuard::new()?;
+/// terminal
+///     .draw(|frame| frame.render_widget(Paragraph::new("Hello"), frame.size()))
+///     .map_err(DisplayError::Terminal)?;
+/// terminal.restore()?;
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+pub struct TerminalGuard {
+    /// Terminal to draw on
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+    /// Capabilities of the terminal, with the alternate screen only if it was entered
+    capabilities: TerminalCapabilities,
+    /// Whether the terminal still has to be restored
+    active: bool,
+    /// Panic hook which was installed before the guard and is put back once it is restored
+    previous_hook: Arc<PanicHook>,
+}
+
+/// Panic hook as taken by [std::panic::take_hook]
+type PanicHook = dyn Fn(&PanicHookInfo<'_>) + Send + Sync + 'static;
+
+impl TerminalGuard {
+    /// Enters raw mode and the alternate screen, undoing both if setting up the terminal fails
+    pub fn new() -> crate::Result<Self> {
+        let mut capabilities = TerminalCapabilities::detect();
+        enable_raw_mode().map_err(DisplayError::Setup)?;
+        let mut setup = || -> io::Result<_> {
+            enter_screen(&mut stdout(), &mut capabilities)?;
+            Terminal::new(CrosstermBackend::new(stdout()))
+        };
+        let terminal = match setup() {
+            Ok(terminal) => terminal,
+            Err(error) => {
+                let _ = restore_terminal(capabilities.alternate_screen);
+                return Err(DisplayError::Setup(error).into());
+            }
+        };
+        let alternate_screen = capabilities.alternate_screen;
+        let previous_hook: Arc<PanicHook> = Arc::from(std::panic::take_hook());
+        let hook = Arc::clone(&previous_hook);
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = restore_terminal(alternate_screen);
+            hook(info);
+        }));
+        Ok(Self {
+            terminal,
+            capabilities,
+            active: true,
+            previous_hook,
+        })
+    }
+
+    /// Replaces the hook restoring the terminal by the one which was installed before
+    ///
+    /// Hooks can not be changed while panicking, the wrapper then stays in place.
+    fn reinstall_previous_hook(&self) {
+        if std::thread::panicking() {
+            return;
+        }
+        let hook = Arc::clone(&self.previous_hook);
+        std::panic::set_hook(Box::new(move |info| hook(info)));
+    }
+
+    /// Capabilities of the terminal, see [TerminalCapabilities]
+    pub fn capabilities(&self) -> TerminalCapabilities {
+        self.capabilities
+    }
+
+    /// Restores the terminal, unlike dropping the guard this reports failures
+    pub fn restore(mut self) -> crate::Result<()> {
+        self.active = false;
+        // The previous hook does not write escape codes into later output
+        self.reinstall_previous_hook();
+        restore_terminal(self.capabilities.alternate_screen)
+    }
+}
+
+impl std::ops::Deref for TerminalGuard {
+    type Target = Terminal<CrosstermBackend<Stdout>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl std::ops::DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if self.active {
+            self.reinstall_previous_hook();
+            let _ = restore_terminal(self.capabilities.alternate_screen);
+        }
+    }
+}
+
+/// Enters the alternate screen or clears the main screen if the terminal lacks the former
+///
+/// [TerminalCapabilities::alternate_screen] is unset when falling back to the main screen.
+pub(crate) fn enter_screen(
+    writer: &mut impl io::Write,
+    capabilities: &mut TerminalCapabilities,
+) -> io::Result<()> {
+    if capabilities.alternate_screen && writer.execute(EnterAlternateScreen).is_err() {
+        #[cfg(feature = "tracing")]
+        tracing::warn!("alternate screen is unsupported, drawing on the main screen");
+        capabilities.alternate_screen = false;
+    }
+    if !capabilities.alternate_screen {
+        writer.execute(Clear(ClearType::All))?;
+    }
+    Ok(())
+}
+
+/// Leaves raw mode and the alternate screen if it was entered
+fn restore_terminal(alternate_screen: bool) -> crate::Result<()> {
+    disable_raw_mode().map_err(DisplayError::Restore)?;
+    if alternate_screen {
+        stdout()
+            .execute(LeaveAlternateScreen)
+            .map_err(DisplayError::Restore)?;
+    }
+    Ok(())
+}
+
+/// The key event a TUI should react to, `None` for events which only duplicate another one
+///
+/// Windows reports releasing a key as an event of its own, while other platforms only report
+/// presses unless asked otherwise. Windows also reports characters typed with AltGr, eg. `@`
+/// or `{` on German layouts, as pressed together with Ctrl and Alt, and Shift+Tab as Tab with
+/// Shift held. These are mapped to the events other platforms report.
+/// ```
+/// use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+/// use opencl3_select::normalize_key_event;
+/// let press = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+/// assert_eq!(normalize_key_event(press), Some(press));
+///
+/// let release = KeyEvent::new_with_kind(
+///     KeyCode::Char('j'),
+///     KeyModifiers::NONE,
+///     KeyEventKind::Release,
+/// );
+/// assert_eq!(normalize_key_event(release), None);
+///
+/// let alt_gr = KeyEvent::new(KeyCode::Char('@'), KeyModifiers::CONTROL | KeyModifiers::ALT);
+/// assert_eq!(normalize_key_event(alt_gr).unwrap().modifiers, KeyModifiers::NONE);
+///
+/// let shift_tab = KeyEvent::new(KeyCode::Tab, KeyModifiers::SHIFT);
+/// assert_eq!(normalize_key_event(shift_tab).unwrap().code, KeyCode::BackTab);
+/// ```
+pub fn normalize_key_event(mut event: KeyEvent) -> Option<KeyEvent> {
+    if event.kind == KeyEventKind::Release {
+        return None;
+    }
+    // Held keys repeat as presses, whether or not the terminal tells them apart
+    event.kind = KeyEventKind::Press;
+    let alt_gr = KeyModifiers::CONTROL | KeyModifiers::ALT;
+    match event.code {
+        KeyCode::Char(_) if event.modifiers.contains(alt_gr) => event.modifiers.remove(alt_gr),
+        KeyCode::Tab if event.modifiers.contains(KeyModifiers::SHIFT) => {
+            event.code = KeyCode::BackTab;
+        }
+        _ => {}
+    }
+    Some(event)
+}
+
+/// Symbols which terminals without unicode support are unable to render and their ASCII
+/// replacements of the same width
+const ASCII_FALLBACKS: [(&str, &str); 13] = [
+    ("↑", "^"),
+    ("↓", "v"),
+    ("←", "<"),
+    ("→", ">"),
+    ("↕", "*"),
+    ("›", ">"),
+    ("▏", "|"),
+    ("·", "-"),
+    ("•", "*"),
+    ("◆", "*"),
+    ("✓", "+"),
+    ("✗", "x"),
+    ("…", "~"),
+];
+
+/// Replaces the symbols in a rendered buffer which terminals without unicode support are
+/// unable to render, see [TerminalCapabilities::unicode]
+///
+/// Applied to the buffer of a frame after all widgets were rendered. Box drawing characters
+/// are kept as even the legacy Windows console renders them.
+/// ```
+/// use opencl3_select::ascii_fallback;
+/// use ratatui::{backend::TestBackend, buffer::Buffer, widgets::Paragraph, Terminal};
+/// let mut terminal = Terminal::new(TestBackend::new(16, 1)).unwrap();
+/// terminal
+///     .draw(|frame| {
+///         frame.render_widget(Paragraph::new("↓↑ move · ✓ ok"), frame.size());
+///         ascii_fallback(frame.buffer_mut());
+///     })
+///     .unwrap();
+/// terminal.backend().assert_buffer(&Buffer::with_lines(vec!["v^ move - + ok  "]));
+/// ```
+pub fn ascii_fallback(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if let Some((_, ascii)) = ASCII_FALLBACKS
+            .iter()
+            .find(|(symbol, _)| *symbol == cell.symbol())
+        {
+            cell.set_symbol(ascii);
+        }
+    }
+}
 
 /// Display the found [ClState](crate::clinfo::ClState)
-pub fn display_opencl_state(cl_state: &crate::clinfo::ClState) -> std::io::Result<()> {
+pub fn display_opencl_state(cl_state: &crate::clinfo::ClState) -> crate::Result<()> {
     let platforms = cl_state.get_platforms();
     println!("Found {} platforms", platforms.len());
     for platform in platforms.iter() {
@@ -18,43 +300,63 @@ pub fn display_opencl_state(cl_state: &crate::clinfo::ClState) -> std::io::Resul
         }
     }
 
-    enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
-    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    let mut terminal = TerminalGuard::new()?;
 
+    let unicode = terminal.capabilities().unicode;
     let mut should_quit = false;
-    let ui_drawer = |frame: &mut Frame| {
-        frame.render_widget(
-            Block::new().borders(Borders::TOP).title("opencl3_select"),
-            frame.size(),
-        );
-        let list = ratatui::widgets::List::new(platforms.iter().map(|platform| platform.name()))
-            .block(
-                Block::default()
-                    .title("opencl3_select")
-                    .borders(Borders::ALL),
-            )
+    while !should_quit {
+        terminal
+            .draw(|frame| draw_platforms(frame, &platforms, unicode))
+            .map_err(DisplayError::Terminal)?;
+        should_quit = handle_events().map_err(DisplayError::Terminal)?;
+    }
+
+    terminal.restore()
+}
+
+/// Draws the list of platforms, or hints to set up OpenCL if there are none
+///
+/// Symbols are replaced by [ascii_fallback] on terminals without unicode support.
+pub(crate) fn draw_platforms(frame: &mut Frame, platforms: &[crate::PlatformInfo], unicode: bool) {
+    frame.render_widget(
+        Block::new().borders(Borders::TOP).title("opencl3_select"),
+        frame.size(),
+    );
+    if platforms.is_empty() {
+        let lines: Vec<Line> = std::iter::once(Line::from("No OpenCL platforms found").bold())
+            .chain(crate::SETUP_HINTS.map(|hint| Line::from(format!("• {hint}"))))
+            .collect();
+        let paragraph = Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(list_block());
+        frame.render_widget(paragraph, frame.size());
+    } else {
+        let list = List::new(platforms.iter().map(|platform| platform.name()))
+            .block(list_block())
             .style(Style::default().fg(Color::White))
             .highlight_style(Style::default().add_modifier(Modifier::BOLD))
             .highlight_symbol(">>")
             .repeat_highlight_symbol(true)
             .direction(ListDirection::TopToBottom);
         frame.render_widget(list, frame.size());
-    };
-    while !should_quit {
-        terminal.draw(ui_drawer)?;
-        should_quit = handle_events()?;
     }
+    if !unicode {
+        ascii_fallback(frame.buffer_mut());
+    }
+}
 
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
-    Ok(())
+/// Frame around the list of platforms
+fn list_block() -> Block<'static> {
+    Block::default()
+        .title("opencl3_select")
+        .borders(Borders::ALL)
 }
 
+/// Returns `true` if the user requested to quit
 fn handle_events() -> io::Result<bool> {
     if event::poll(std::time::Duration::from_millis(50))? {
         if let Event::Key(key) = event::read()? {
-            if key.kind == event::KeyEventKind::Press && key.code == KeyCode::Char('q') {
+            if normalize_key_event(key).is_some_and(|key| key.code == KeyCode::Char('q')) {
                 return Ok(true);
             }
         }
@@ -62,36 +364,236 @@ fn handle_events() -> io::Result<bool> {
     Ok(false)
 }
 
-fn ui(frame: &mut Frame) {
-    let main_layout = Layout::new(
-        Direction::Vertical,
-        [
-            Constraint::Length(1),
-            Constraint::Min(0),
-            Constraint::Length(1),
-        ],
-    )
-    .split(frame.size());
-    frame.render_widget(
-        Block::new().borders(Borders::TOP).title("Title Bar"),
-        main_layout[0],
-    );
-    frame.render_widget(
-        Block::new().borders(Borders::TOP).title("Status Bar"),
-        main_layout[2],
-    );
+/// Key properties of the device shown at verbosity 2 and above
+fn tree_properties(device: &crate::DeviceInfo) -> Vec<(&'static str, String)> {
+    [
+        ("Id", Some(device.id().to_string())),
+        ("Vendor", Some(device.vendor())),
+        ("OpenCL C", device.opencl_c_version()),
+        ("Driver", device.driver_version()),
+        (
+            "Global memory",
+            device.global_mem_size().map(crate::format_bytes),
+        ),
+        (
+            "Cache",
+            device.global_mem_cache_size().map(crate::format_bytes),
+        ),
+        ("SVM", device.svm_capabilities().map(|svm| svm.to_string())),
+        ("Host CPU", device.host_cpu_ref().map(|cpu| cpu.to_string())),
+        (
+            "Capabilities",
+            device.extensions_ref().map(|_| {
+                device
+                    .capabilities()
+                    .into_iter()
+                    .filter(|(_, supported)| *supported)
+                    .map(|(capability, _)| capability.label)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(label, value)| Some((label, value?)))
+    .collect()
+}
 
-    let inner_layout = Layout::new(
-        Direction::Horizontal,
-        [Constraint::Percentage(50), Constraint::Percentage(50)],
-    )
-    .split(main_layout[1]);
-    frame.render_widget(
-        Block::default().borders(Borders::ALL).title("Left"),
-        inner_layout[0],
-    );
-    frame.render_widget(
-        Block::default().borders(Borders::ALL).title("Right"),
-        inner_layout[1],
-    );
+/// Renders platforms and their devices as an aligned tree like `clinfo -l`
+///
+/// Verbosity `0` lists the names and types of devices, `1` adds their version and `2` or more
+/// adds their key properties. Names are highlighted with terminal colors if `color` is set.
+#[cfg_attr(feature = "serde", doc = "```")]
+#[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+/// use opencl3_select::{format_tree, ClState};
+/// let state: ClState = serde_json::from_str(r#"{"platforms": [{
+///     "name": "PoCL", "vendor": "The pocl project", "version": "OpenCL 3.0 PoCL",
+///     "profile": "FULL_PROFILE", "extensions": "", "devices": [{
+///         "platform": "PoCL", "vendor": "AuthenticAMD", "vendor_id": 4098,
+///         "vendor_id_text": "AMD", "name": "cpu-haswell", "version": "OpenCL 3.0 PoCL",
+///         "type": 2, "type_text": "CL_DEVICE_TYPE_CPU", "global_mem_size": 17179869184
+///     }]
+/// }]}"#)?;
+/// assert_eq!(format_tree(&state, 0, false), "PoCL\n└── cpu-haswell  CPU\n");
+/// assert!(format_tree(&state, 2, false).contains("    └── Global memory  16.0 GiB\n"));
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn format_tree(cl_state: &crate::ClState, verbosity: u8, color: bool) -> String {
+    use crossterm::style::{StyledContent, Stylize};
+    let paint = |text: String, style: fn(String) -> StyledContent<String>| match color {
+        true => style(text).to_string(),
+        false => text,
+    };
+    let mut out = String::new();
+    for platform in cl_state.platforms_ref() {
+        let mut line = paint(platform.name(), Stylize::bold);
+        if verbosity >= 1 {
+            line += &format!("  ({}, {})", platform.vendor_ref(), platform.version_ref());
+        }
+        out += &format!("{line}\n");
+
+        let devices = platform.devices_ref();
+        let name_width = devices
+            .iter()
+            .map(|device| device.name_ref().chars().count())
+            .max()
+            .unwrap_or(0);
+        for (i, device) in devices.iter().enumerate() {
+            let last = i + 1 == devices.len();
+            let (branch, indent) = match last {
+                true => ("└── ", "    "),
+                false => ("├── ", "│   "),
+            };
+            let kind = device
+                .type_text_ref()
+                .trim_start_matches("CL_DEVICE_TYPE_")
+                .to_string();
+            let name = format!("{:name_width$}", device.name_ref());
+            let mut line = format!("{branch}{}  {kind}", paint(name, Stylize::cyan));
+            if verbosity >= 1 {
+                line += &format!("  {}", device.version_ref());
+            }
+            out += line.trim_end();
+            out += "\n";
+
+            if verbosity >= 2 {
+                let properties = tree_properties(device);
+                let label_width = properties
+                    .iter()
+                    .map(|(label, _)| label.len())
+                    .max()
+                    .unwrap_or(0);
+                for (j, (label, value)) in properties.iter().enumerate() {
+                    let branch = match j + 1 == properties.len() {
+                        true => "└── ",
+                        false => "├── ",
+                    };
+                    let label = format!("{label:label_width$}");
+                    out += &format!("{indent}{branch}{}  {value}\n", paint(label, Stylize::dim));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Prints the tree of [format_tree] to stdout
+///
+/// Colors are used if stdout is a terminal and `NO_COLOR` is not set.
+pub fn print_tree(cl_state: &crate::ClState, verbosity: u8) {
+    use std::io::IsTerminal;
+    let color =
+        stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty());
+    print!("{}", format_tree(cl_state, verbosity, color));
+}
+
+/// Lets the user pick devices by their number without raw mode or an alternate screen
+///
+/// Fallback for dumb terminals, SSH sessions without a proper `TERM` and minimal containers
+/// where the TUI cannot start. The devices are listed on stderr such that stdout stays free
+/// for the selection, see [prompt_select_with] for the details.
+pub fn prompt_select(cl_state: &crate::ClState) -> io::Result<Option<Vec<crate::DeviceInfo>>> {
+    prompt_select_with(cl_state, io::stdin().lock(), io::stderr())
+}
+
+/// Lists the devices numbered and reads the chosen numbers from `input`
+///
+/// Several numbers separated by spaces or commas select several devices in the given order of
+/// priority. Invalid answers and answers without any number are rejected and asked again. Returns `None` if the user enters an
+/// empty line, `q` or closes the input, or if there are no devices.
+/// ```
+/// use opencl3_select::{prompt_select_with, ClState, DeviceInfo, PlatformInfo};
+/// let state = ClState::from_platforms(vec![PlatformInfo::builder()
+///     .name("PoCL")
+///     .device(DeviceInfo::builder().name("cpu-haswell").build())
+///     .device(DeviceInfo::builder().name("cpu-skylake").build())
+///     .build()]);
+/// let mut output = vec![];
+/// let selection = prompt_select_with(&state, "3\n2, 1\n".as_bytes(), &mut output)?.unwrap();
+/// assert_eq!(selection[0].name(), "cpu-skylake");
+/// assert_eq!(selection[1].name(), "cpu-haswell");
+/// let output = String::from_utf8(output).unwrap();
+/// assert!(output.contains("  1) cpu-haswell"));
+/// assert!(output.contains("invalid choice \"3\""));
+///
+/// // Answers consisting of separators only are asked again
+/// let mut output = vec![];
+/// let selection = prompt_select_with(&state, ", ,\n2\n".as_bytes(), &mut output)?.unwrap();
+/// assert_eq!(selection.len(), 1);
+/// assert!(String::from_utf8(output).unwrap().contains("no device chosen"));
+///
+/// assert_eq!(prompt_select_with(&state, "\n".as_bytes(), vec![])?, None);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn prompt_select_with(
+    cl_state: &crate::ClState,
+    mut input: impl io::BufRead,
+    mut output: impl io::Write,
+) -> io::Result<Option<Vec<crate::DeviceInfo>>> {
+    let devices = cl_state.get_all_devices();
+    if devices.is_empty() {
+        writeln!(output, "No OpenCL devices found")?;
+        for hint in crate::SETUP_HINTS {
+            writeln!(output, "• {hint}")?;
+        }
+        return Ok(None);
+    }
+    let name_width = devices
+        .iter()
+        .map(|device| device.name_ref().chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut number = 0;
+    for platform in cl_state.platforms_ref() {
+        writeln!(output, "{}", platform.name_ref())?;
+        for device in platform.devices_ref() {
+            number += 1;
+            let kind = device.type_text_ref().trim_start_matches("CL_DEVICE_TYPE_");
+            let line = format!("{number:>3}) {:name_width$}  {kind}", device.name_ref());
+            writeln!(output, "{}", line.trim_end())?;
+        }
+    }
+    loop {
+        write!(
+            output,
+            "Select devices by number in order of priority, eg. \"2 1\", or press enter to abort: "
+        )?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim();
+        if line.is_empty() || line.eq_ignore_ascii_case("q") {
+            return Ok(None);
+        }
+        let choices: Result<Vec<_>, _> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|choice| !choice.is_empty())
+            .map(|choice| match choice.parse::<usize>() {
+                Ok(n) if (1..=devices.len()).contains(&n) => Ok(n - 1),
+                _ => Err(choice),
+            })
+            .collect();
+        match choices {
+            Ok(choices) if choices.is_empty() => writeln!(
+                output,
+                "no device chosen, enter numbers between 1 and {}",
+                devices.len()
+            )?,
+            Ok(mut choices) => {
+                // Keep the first occurrence of numbers given several times
+                let mut seen = std::collections::BTreeSet::new();
+                choices.retain(|&choice| seen.insert(choice));
+                return Ok(Some(
+                    choices.into_iter().map(|i| devices[i].clone()).collect(),
+                ));
+            }
+            Err(choice) => writeln!(
+                output,
+                "invalid choice \"{choice}\", enter numbers between 1 and {}",
+                devices.len()
+            )?,
+        }
+    }
 }
@@ -0,0 +1,119 @@
+//! Headless tests of the terminal handling, rendered into a [TestBackend]
+//!
+//! Cover the fallbacks for terminals without alternate screen or unicode support, which the
+//! machines running the tests usually lack, such that they are otherwise never exercised.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{backend::TestBackend, Terminal};
+use std::io;
+
+use crate::display::{draw_platforms, enter_screen};
+use crate::{normalize_key_event, PlatformInfo, TerminalCapabilities, SETUP_HINTS};
+
+/// Environment of a terminal with the given variables set
+fn env<'a>(variables: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+    |variable| {
+        variables
+            .iter()
+            .find(|(name, _)| *name == variable)
+            .map(|(_, value)| value.to_string())
+    }
+}
+
+#[test]
+fn conhost_detection() {
+    let unicode =
+        |windows, variables| TerminalCapabilities::from_env(windows, env(variables)).unicode;
+    assert!(!unicode(true, &[]));
+    assert!(!unicode(true, &[("WT_SESSION", "")]));
+    assert!(!unicode(true, &[("ConEmuANSI", "OFF")]));
+    assert!(unicode(true, &[("WT_SESSION", "5e6b")]));
+    assert!(unicode(true, &[("TERM_PROGRAM", "vscode")]));
+    assert!(unicode(true, &[("ConEmuANSI", "ON")]));
+    assert!(unicode(false, &[]));
+    assert!(TerminalCapabilities::from_env(true, env(&[])).alternate_screen);
+}
+
+/// Writer of a terminal which fails on the escape code entering the alternate screen
+#[derive(Default)]
+struct NoAlternateScreen(Vec<u8>);
+
+impl io::Write for NoAlternateScreen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf == b"\x1b[?1049h" {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "no alternate screen",
+            ));
+        }
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn main_screen_fallback() {
+    let mut capabilities = TerminalCapabilities::from_env(false, env(&[]));
+    let mut writer = NoAlternateScreen::default();
+    enter_screen(&mut writer, &mut capabilities).unwrap();
+    assert!(!capabilities.alternate_screen);
+    assert_eq!(writer.0, b"\x1b[2J");
+
+    let mut capabilities = TerminalCapabilities::from_env(false, env(&[]));
+    let mut writer = Vec::new();
+    enter_screen(&mut writer, &mut capabilities).unwrap();
+    assert!(capabilities.alternate_screen);
+    assert_eq!(writer, b"\x1b[?1049h");
+}
+
+/// Rendered lines of the platform list
+fn render(platforms: &[PlatformInfo], unicode: bool) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(120, 8)).unwrap();
+    terminal
+        .draw(|frame| draw_platforms(frame, platforms, unicode))
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn setup_hints_in_ascii() {
+    let lines = render(&[], false);
+    assert!(lines[1].contains("No OpenCL platforms found"));
+    assert!(lines[2].starts_with(&format!("│* {}", &SETUP_HINTS[0][..20])));
+    assert!(lines.iter().all(|line| !line.contains('•')));
+
+    let lines = render(&[], true);
+    assert!(lines[2].starts_with("│• "));
+}
+
+#[test]
+fn platform_list() {
+    let platforms = [
+        PlatformInfo::builder()
+            .name("Portable Computing Language")
+            .build(),
+        PlatformInfo::builder().name("NVIDIA CUDA").build(),
+    ];
+    let lines = render(&platforms, false);
+    assert!(lines[0].starts_with("┌opencl3_select"));
+    assert!(lines[1].starts_with("│Portable Computing Language "));
+    assert!(lines[2].starts_with("│NVIDIA CUDA "));
+}
+
+#[test]
+fn repeated_and_released_keys() {
+    let key = |kind| KeyEvent::new_with_kind(KeyCode::Down, KeyModifiers::NONE, kind);
+    let press = KeyEvent::new(KeyCode::Down, KeyModifiers::NONE);
+    assert_eq!(normalize_key_event(key(KeyEventKind::Repeat)), Some(press));
+    assert_eq!(normalize_key_event(key(KeyEventKind::Release)), None);
+}
@@ -1,24 +1,352 @@
+//! Error and result types used throughout the crate
+
 use thiserror::Error;
 
+use crate::DeviceSpec;
+
 /// Wrapper for [std::result::Result] containing custom [ClSelectError].
 pub type Result<T> = std::result::Result<T, ClSelectError>;
 
-/// Contains all possible error variants. Generated with [thiserror]
+/// Lists the known context of an [OpenCL](ClinfoError::OpenCL) error, eg.
+/// ` (property: name, device: Oclgrind Simulator, platform: Oclgrind)`
+#[cfg(feature = "opencl")]
+fn context(
+    property: &Option<&'static str>,
+    platform: &Option<String>,
+    device: &Option<String>,
+) -> String {
+    let parts: Vec<_> = [
+        property.map(|property| format!("property: {property}")),
+        device.as_ref().map(|device| format!("device: {device}")),
+        platform
+            .as_ref()
+            .map(|platform| format!("platform: {platform}")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    match parts.is_empty() {
+        true => String::new(),
+        false => format!(" ({})", parts.join(", ")),
+    }
+}
+
+/// Failures while enumerating the platforms and devices of the current machine
 #[derive(Error, Debug)]
-pub enum ClSelectError {
+pub enum ClinfoError {
     /// unable to get opencl info
-    #[error("unable to get opencl info")]
-    OpenCL(#[from] opencl3::error_codes::ClError),
+    ///
+    /// Contains as much context as was known when the error occurred.
+    #[error("unable to get opencl info{}", context(.property, .platform, .device))]
+    #[cfg(feature = "opencl")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "opencl")))]
+    OpenCL {
+        /// Error code returned by OpenCL
+        #[source]
+        source: opencl3::error_codes::ClError,
+        /// Name of the platform which was queried
+        platform: Option<String>,
+        /// Name of the device which was queried
+        device: Option<String>,
+        /// Property whose query failed
+        property: Option<&'static str>,
+    },
 
-    /// failed to display
-    #[error("failed to display")]
-    #[cfg(feature = "ratatui")]
-    #[cfg_attr(doc_cfg, doc(cfg(feature = "ratatui")))]
-    Display(#[from] std::io::Error),
+    /// no opencl platforms are installed
+    #[error("no opencl platforms found")]
+    NoPlatforms,
+
+    /// enumeration of a platform was given up after its timeout expired
+    #[error("enumeration of platform \"{0}\" was cancelled")]
+    EnumerationCancelled(String),
+}
+
+/// Failures while reading or writing files
+#[derive(Error, Debug)]
+pub enum StorageError {
+    /// unable to read from or write to storage
+    #[error("unable to read from or write to storage")]
+    Io(#[source] std::io::Error),
+
+    /// unable to write report
+    #[error("unable to write report")]
+    Report(#[source] std::io::Error),
 
     /// error during (de)serialization
     #[cfg(feature = "serde")]
     #[error("error during (de)serialization")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
     Deserialize(#[from] serde::de::value::Error),
+
+    /// error during (de)serialization of json
+    #[cfg(feature = "serde")]
+    #[error("error during (de)serialization of json")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+    Json(#[from] serde_json::Error),
+
+    /// error during (de)serialization of yaml
+    #[cfg(feature = "storage-yaml")]
+    #[error("error during (de)serialization of yaml")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage-yaml")))]
+    Yaml(#[from] serde_yaml::Error),
+
+    /// error during serialization of toml
+    #[cfg(feature = "storage-toml")]
+    #[error("error during serialization of toml")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage-toml")))]
+    TomlSerialize(#[from] toml::ser::Error),
+
+    /// error during deserialization of toml
+    #[cfg(feature = "storage-toml")]
+    #[error("error during deserialization of toml")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage-toml")))]
+    TomlDeserialize(#[from] toml::de::Error),
+
+    /// could not determine storage format from file extension
+    #[cfg(feature = "serde")]
+    #[error("could not determine storage format of {0:?}")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+    UnknownFormat(std::path::PathBuf),
+
+    /// column of the csv export is no property of a device
+    #[cfg(feature = "serde")]
+    #[error("unknown csv column \"{0}\"")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+    UnknownColumn(String),
+
+    /// stored file does not match its checksum
+    #[cfg(feature = "serde")]
+    #[error("{0:?} is corrupt, its contents do not match the stored checksum")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+    Corrupt(std::path::PathBuf),
+
+    /// cached state no longer describes the current machine
+    #[cfg(feature = "serde")]
+    #[error("cached state {0:?} is stale")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+    CacheStale(std::path::PathBuf),
+
+    /// fixture standing in for the OpenCL setup could not be read
+    #[cfg(feature = "serde")]
+    #[error("unable to parse fixture {path:?}: {message}")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+    FixtureParse {
+        /// Path of the fixture
+        path: std::path::PathBuf,
+        /// Description of the problem
+        message: String,
+    },
+}
+
+/// Failures of the terminal user interface
+#[cfg(feature = "tui")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "tui")))]
+#[derive(Error, Debug)]
+pub enum DisplayError {
+    /// failed to display
+    #[error("failed to display")]
+    Terminal(#[from] std::io::Error),
+
+    /// raw mode or the alternate screen could not be entered
+    #[error("unable to set up the terminal")]
+    Setup(#[source] std::io::Error),
+
+    /// raw mode or the alternate screen could not be left
+    #[error("unable to restore the terminal")]
+    Restore(#[source] std::io::Error),
+}
+
+/// Failures of the kernel benchmarks supplied by the user
+#[cfg(feature = "diagnostics")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "diagnostics")))]
+#[derive(Error, Debug)]
+pub enum BenchmarkError {
+    /// benchmark harness of the user does not fit its kernel
+    #[error("kernel benchmark \"{kernel}\" is invalid: {message}")]
+    InvalidKernel {
+        /// Name of the benchmark
+        kernel: String,
+        /// Description of the problem, eg. the build log
+        message: String,
+    },
+}
+
+/// Failures while picking devices
+#[derive(Error, Debug)]
+pub enum SelectionError {
+    /// the requested device is not present on this machine
+    #[error("could not find device \"{0}\" on this machine")]
+    DeviceNotFound(DeviceSpec),
+
+    /// several devices fit where exactly one was requested
+    #[error("\"{spec}\" matches several devices: {}", .candidates.join(", "))]
+    AmbiguousDevice {
+        /// The requested device
+        spec: DeviceSpec,
+        /// Name and platform of every device which fits
+        candidates: Vec<String>,
+    },
+
+    /// no device fulfills the requirements
+    #[error("no device fulfills the requirements")]
+    NoMatchingDevice,
+
+    /// invalid regular expression
+    #[error("invalid regular expression")]
+    Regex(#[from] regex::Error),
+}
+
+/// Contains all possible errors, grouped by the part of the crate they stem from.
+/// Generated with [thiserror]
+///
+/// Errors of the underlying libraries convert into the matching group, eg. a
+/// [serde_json::Error] becomes a [StorageError::Json].
+#[cfg_attr(feature = "storage", doc = "```")]
+#[cfg_attr(not(feature = "storage"), doc = "```ignore")]
+/// use opencl3_select::{ClSelectError, SelectionError, StorageError};
+/// let error: ClSelectError = regex::Regex::new("(").unwrap_err().into();
+/// assert!(matches!(error, ClSelectError::Selection(SelectionError::Regex(_))));
+///
+/// let error = opencl3_select::load::<Vec<u8>>("selection.unknown").unwrap_err();
+/// assert!(matches!(error, ClSelectError::Storage(StorageError::UnknownFormat(_))));
+/// ```
+#[derive(Error, Debug)]
+pub enum ClSelectError {
+    /// failure while enumerating platforms and devices
+    #[error(transparent)]
+    Clinfo(#[from] ClinfoError),
+
+    /// failure while reading or writing files
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    /// failure of the terminal user interface
+    #[error(transparent)]
+    #[cfg(feature = "tui")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "tui")))]
+    Display(#[from] DisplayError),
+
+    /// failure while picking devices
+    #[error(transparent)]
+    Selection(#[from] SelectionError),
+
+    /// failure of a kernel benchmark
+    #[error(transparent)]
+    #[cfg(feature = "diagnostics")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "diagnostics")))]
+    Benchmark(#[from] BenchmarkError),
+
+    /// background task could not be completed
+    #[cfg(feature = "tokio")]
+    #[error("background task could not be completed")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "tokio")))]
+    Join(#[from] tokio::task::JoinError),
+}
+
+/// Converts errors of the underlying libraries into [ClSelectError] via the given group
+macro_rules! impl_from_via(
+    ($($(#[$meta:meta])* $source:ty => $group:ident,)+) => {
+        $(
+            $(#[$meta])*
+            impl From<$source> for ClSelectError {
+                fn from(error: $source) -> Self {
+                    $group::from(error).into()
+                }
+            }
+        )*
+    }
+);
+
+impl_from_via!(
+    regex::Error => SelectionError,
+    #[cfg(feature = "serde")]
+    serde::de::value::Error => StorageError,
+    #[cfg(feature = "serde")]
+    serde_json::Error => StorageError,
+    #[cfg(feature = "storage-yaml")]
+    serde_yaml::Error => StorageError,
+    #[cfg(feature = "storage-toml")]
+    toml::ser::Error => StorageError,
+    #[cfg(feature = "storage-toml")]
+    toml::de::Error => StorageError,
+);
+
+#[cfg(feature = "opencl")]
+impl From<opencl3::error_codes::ClError> for ClSelectError {
+    fn from(source: opencl3::error_codes::ClError) -> Self {
+        ClinfoError::OpenCL {
+            source,
+            platform: None,
+            device: None,
+            property: None,
+        }
+        .into()
+    }
+}
+
+impl ClSelectError {
+    /// Name of the platform which was queried when the error occurred
+    pub fn platform(&self) -> Option<&str> {
+        match self {
+            #[cfg(feature = "opencl")]
+            Self::Clinfo(ClinfoError::OpenCL { platform, .. }) => platform.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Name of the device which was queried when the error occurred
+    pub fn device(&self) -> Option<&str> {
+        match self {
+            #[cfg(feature = "opencl")]
+            Self::Clinfo(ClinfoError::OpenCL { device, .. }) => device.as_deref(),
+            Self::Selection(SelectionError::DeviceNotFound(DeviceSpec::Name(device))) => {
+                Some(device)
+            }
+            _ => None,
+        }
+    }
+
+    /// Property whose query failed
+    pub fn property(&self) -> Option<&'static str> {
+        match self {
+            #[cfg(feature = "opencl")]
+            Self::Clinfo(ClinfoError::OpenCL { property, .. }) => *property,
+            _ => None,
+        }
+    }
+
+    /// Records the platform if it is not known yet
+    #[cfg(feature = "opencl")]
+    pub(crate) fn with_platform(mut self, name: &str) -> Self {
+        if let Self::Clinfo(ClinfoError::OpenCL { platform, .. }) = &mut self {
+            platform.get_or_insert_with(|| name.to_string());
+        }
+        self
+    }
+
+    /// Records the device if it is not known yet
+    #[cfg(feature = "opencl")]
+    pub(crate) fn with_device(mut self, name: &str) -> Self {
+        if let Self::Clinfo(ClinfoError::OpenCL { device, .. }) = &mut self {
+            device.get_or_insert_with(|| name.to_string());
+        }
+        self
+    }
+}
+
+/// Attaches the name of the queried property to the error of an OpenCL query
+#[cfg(feature = "opencl")]
+pub(crate) fn query<T>(
+    property: &'static str,
+    result: std::result::Result<T, opencl3::error_codes::ClError>,
+) -> Result<T> {
+    result.map_err(|source| {
+        ClinfoError::OpenCL {
+            source,
+            platform: None,
+            device: None,
+            property: Some(property),
+        }
+        .into()
+    })
 }
@@ -0,0 +1,239 @@
+//! Formats in which the platforms and devices of a [ClState] can be written out
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::{ClState, ReportFormat, Result, StorageError, StorageFormat, DEFAULT_CSV_COLUMNS};
+
+/// Writes a [ClState] in one particular format, eg. JSON or Markdown
+///
+/// Exporters are looked up by their name or extension in an [ExporterRegistry]. Implement this
+/// trait to add a format of your own:
+/// ```
+/// use opencl3_select::{ClState, Exporter, ExporterRegistry};
+/// struct Names;
+///
+/// impl Exporter for Names {
+///     fn name(&self) -> &str {
+///         "names"
+///     }
+///
+///     fn extension(&self) -> &str {
+///         "txt"
+///     }
+///
+///     fn write(&self, state: &ClState, out: &mut dyn std::io::Write) -> opencl3_select::Result<()> {
+///         for device in state.get_all_devices() {
+///             writeln!(out, "{}", device.name())
+///                 .map_err(opencl3_select::StorageError::Io)?;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let mut registry = ExporterRegistry::default();
+/// registry.register(Names);
+/// assert_eq!(registry.for_path("devices.txt").unwrap().name(), "names");
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+pub trait Exporter: Send + Sync {
+    /// Name by which the format is chosen, eg. `json`
+    fn name(&self) -> &str;
+
+    /// Extension of files in this format without the leading dot, eg. `md`
+    fn extension(&self) -> &str;
+
+    /// Writes the platforms and devices of the state
+    fn write(&self, state: &ClState, out: &mut dyn Write) -> Result<()>;
+
+    /// Renders the platforms and devices of the state into a string
+    fn export(&self, state: &ClState) -> Result<String> {
+        let mut out = Vec::new();
+        self.write(state, &mut out)?;
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+}
+
+impl Exporter for StorageFormat {
+    fn name(&self) -> &str {
+        StorageFormat::name(self)
+    }
+
+    fn extension(&self) -> &str {
+        self.name()
+    }
+
+    fn write(&self, state: &ClState, out: &mut dyn Write) -> Result<()> {
+        let contents = self.encode(state)?;
+        Ok(writeln!(out, "{contents}").map_err(StorageError::Io)?)
+    }
+}
+
+impl Exporter for ReportFormat {
+    fn name(&self) -> &str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Html => "html",
+        }
+    }
+
+    fn extension(&self) -> &str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+
+    /// Writes the [report](ClState::report) without a selection
+    fn write(&self, state: &ClState, out: &mut dyn Write) -> Result<()> {
+        let report = state.report(*self, &[]);
+        Ok(out
+            .write_all(report.as_bytes())
+            .map_err(StorageError::Report)?)
+    }
+}
+
+/// One row per device with the given columns, see [to_csv_with](crate::to_csv_with)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CsvExporter {
+    /// Columns named like the serialized fields of [DeviceInfo](crate::DeviceInfo)
+    pub columns: Vec<String>,
+}
+
+impl Default for CsvExporter {
+    fn default() -> Self {
+        Self {
+            columns: DEFAULT_CSV_COLUMNS.map(String::from).to_vec(),
+        }
+    }
+}
+
+impl Exporter for CsvExporter {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn extension(&self) -> &str {
+        "csv"
+    }
+
+    fn write(&self, state: &ClState, out: &mut dyn Write) -> Result<()> {
+        let csv = crate::to_csv_with(state, &self.columns)?;
+        Ok(out.write_all(csv.as_bytes()).map_err(StorageError::Io)?)
+    }
+}
+
+/// Collection of [Exporter]s which are chosen by name or file extension
+///
+/// The default registry contains JSON, TOML, YAML, CSV, Markdown and HTML. Registering an
+/// exporter with the name of an existing one replaces it.
+#[cfg_attr(
+    all(
+        feature = "storage-json",
+        feature = "storage-toml",
+        feature = "storage-yaml"
+    ),
+    doc = "```"
+)]
+#[cfg_attr(
+    not(all(
+        feature = "storage-json",
+        feature = "storage-toml",
+        feature = "storage-yaml"
+    )),
+    doc = "```ignore"
+)]
+/// use opencl3_select::{ClState, CsvExporter, ExporterRegistry};
+/// let state: ClState = serde_json::from_str(r#"{"platforms": [{
+///     "name": "PoCL", "vendor": "The pocl project", "version": "OpenCL 3.0 PoCL",
+///     "profile": "FULL_PROFILE", "extensions": "", "devices": [{
+///         "platform": "PoCL", "vendor": "AuthenticAMD", "vendor_id": 4098,
+///         "vendor_id_text": "AMD", "name": "cpu-haswell", "version": "OpenCL 3.0 PoCL",
+///         "type": 2, "type_text": "CL_DEVICE_TYPE_CPU"
+///     }]
+/// }]}"#)?;
+/// let mut registry = ExporterRegistry::default();
+/// assert_eq!(registry.names(), ["json", "toml", "yaml", "csv", "markdown", "html"]);
+/// assert!(registry.get("markdown").unwrap().export(&state)?.contains("| cpu-haswell |"));
+///
+/// registry.register(CsvExporter { columns: vec!["name".into()] });
+/// assert_eq!(registry.get("CSV").unwrap().export(&state)?, "name\ncpu-haswell\n");
+/// assert!(registry.get("xml").is_none());
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+pub struct ExporterRegistry {
+    /// Registered exporters in the order in which they are listed
+    exporters: Vec<Box<dyn Exporter>>,
+}
+
+impl Default for ExporterRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        for &format in StorageFormat::ALL {
+            registry.register(format);
+        }
+        registry.register(CsvExporter::default());
+        registry.register(ReportFormat::Markdown);
+        registry.register(ReportFormat::Html);
+        registry
+    }
+}
+
+impl ExporterRegistry {
+    /// Registry without any exporters
+    pub fn empty() -> Self {
+        Self {
+            exporters: Vec::new(),
+        }
+    }
+
+    /// Adds the exporter or replaces the one with the same name
+    pub fn register(&mut self, exporter: impl Exporter + 'static) {
+        let exporter = Box::new(exporter);
+        match self
+            .exporters
+            .iter()
+            .position(|known| known.name().eq_ignore_ascii_case(exporter.name()))
+        {
+            Some(index) => self.exporters[index] = exporter,
+            None => self.exporters.push(exporter),
+        }
+    }
+
+    /// The exporter with the given name, ignoring case
+    pub fn get(&self, name: &str) -> Option<&dyn Exporter> {
+        self.exporters
+            .iter()
+            .find(|exporter| exporter.name().eq_ignore_ascii_case(name))
+            .map(Box::as_ref)
+    }
+
+    /// The exporter whose extension matches the extension of the path
+    pub fn for_path(&self, path: impl AsRef<Path>) -> Option<&dyn Exporter> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        self.exporters
+            .iter()
+            .find(|exporter| exporter.extension().eq_ignore_ascii_case(extension))
+            .map(Box::as_ref)
+    }
+
+    /// Names of all registered exporters
+    pub fn names(&self) -> Vec<&str> {
+        self.exporters
+            .iter()
+            .map(|exporter| exporter.name())
+            .collect()
+    }
+
+    /// Writes the state to the path in the format given by its extension
+    ///
+    /// Fails with [StorageError::UnknownFormat] if no exporter handles the extension.
+    pub fn save(&self, state: &ClState, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let exporter = self
+            .for_path(path)
+            .ok_or_else(|| StorageError::UnknownFormat(path.to_path_buf()))?;
+        let mut file = std::fs::File::create(path).map_err(StorageError::Io)?;
+        exporter.write(state, &mut file)
+    }
+}
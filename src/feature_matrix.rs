@@ -0,0 +1,116 @@
+//! Checks of the features on their own, such that slim builds keep compiling and working
+//!
+//! Tests only exist if their feature is enabled. The module is meant to be run once per
+//! feature without the default features, eg. in CI:
+//! ```text
+//! for feature in serde storage-json storage-toml storage-yaml tui cache bench server; do
+//!     cargo test --lib --no-default-features --features $feature feature_matrix
+//!     cargo test --doc --no-default-features --features $feature
+//! done
+//! ```
+//! Examples which need other features are marked `ignore` in such builds.
+//! Features which need an OpenCL library to link, like `opencl`, `diagnostics` and `pick`,
+//! are only checked with `cargo check` there.
+
+use crate::{ClState, DeviceInfo, PlatformInfo};
+
+/// One platform with a single CPU device
+fn fixture() -> ClState {
+    ClState::from_platforms(vec![PlatformInfo::builder()
+        .name("Portable Computing Language")
+        .device(
+            DeviceInfo::builder()
+                .name("cpu-haswell")
+                .r#type(1 << 1)
+                .driver_version("5.0")
+                .build(),
+        )
+        .build()])
+}
+
+#[test]
+fn core_without_features() {
+    let cl_state = fixture();
+    assert_eq!(cl_state.get_all_devices()[0].name(), "cpu-haswell");
+    assert_eq!(cl_state.summary().devices, ["cpu-haswell"]);
+}
+
+/// Saves the fixture into a file with the extension and loads it again
+#[cfg(feature = "storage")]
+fn round_trip(extension: &str) {
+    let path = std::env::temp_dir().join(format!("opencl3_select_feature_matrix.{extension}"));
+    crate::save(&fixture(), &path).unwrap();
+    let loaded: ClState = crate::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.get_all_devices(), fixture().get_all_devices());
+}
+
+#[cfg(feature = "storage-json")]
+#[test]
+fn storage_json() {
+    round_trip("json");
+}
+
+#[cfg(feature = "storage-toml")]
+#[test]
+fn storage_toml() {
+    round_trip("toml");
+}
+
+#[cfg(feature = "storage-yaml")]
+#[test]
+fn storage_yaml() {
+    round_trip("yaml");
+}
+
+#[cfg(all(feature = "storage", not(feature = "storage-yaml")))]
+#[test]
+fn storage_without_yaml() {
+    assert_eq!(crate::StorageFormat::from_path("selection.yaml"), None);
+    assert!("yaml".parse::<crate::StorageFormat>().is_err());
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn tui() {
+    assert_eq!(
+        crate::format_tree(&fixture(), 0, false),
+        "Portable Computing Language\n└── cpu-haswell  CPU\n"
+    );
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn cache() {
+    let path = std::env::temp_dir().join("opencl3_select_feature_matrix_cache.json");
+    let cache = crate::EnumerationCache::new(&path);
+    cache.store(&fixture()).unwrap();
+    let cached = cache.load().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(cached.get_all_devices(), fixture().get_all_devices());
+}
+
+#[cfg(feature = "bench")]
+#[test]
+fn bench() {
+    use crate::{BenchmarkHistory, BenchmarkResult};
+    let device = &fixture().get_all_devices()[0];
+    let result = |value| BenchmarkResult {
+        name: "saxpy".into(),
+        value,
+        unit: "ms".into(),
+        higher_is_better: false,
+    };
+    let mut history = BenchmarkHistory::default();
+    assert!(history.record(device, vec![result(2.0)]).is_empty());
+    let deltas = history.record(device, vec![result(3.0)]);
+    assert!(deltas[0].is_regression(0.05));
+}
+
+#[cfg(feature = "server")]
+#[test]
+fn server() {
+    let response = crate::respond(&fixture(), "/auto-select?count=1");
+    assert_eq!(response.status, 200);
+    assert!(response.body.contains(r#""name":"cpu-haswell""#));
+}
@@ -0,0 +1,302 @@
+//! Typed representations of bitfield and versioned device queries, mostly introduced with
+//! OpenCL 3.0
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Packed version as reported by `CL_DEVICE_NUMERIC_VERSION` and the `*_WITH_VERSION` queries
+///
+/// The raw `cl_version` stores 10 bits for the major, 10 bits for the minor and 12 bits for the
+/// patch version.
+/// ```
+/// use opencl3_select::NumericVersion;
+/// let version = NumericVersion::from_raw((3 << 22) | (1 << 12) | 5);
+/// assert_eq!(version, NumericVersion { major: 3, minor: 1, patch: 5 });
+/// assert_eq!(version.to_string(), "3.1.5");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct NumericVersion {
+    /// Major version
+    pub major: u32,
+    /// Minor version
+    pub minor: u32,
+    /// Patch version
+    pub patch: u32,
+}
+
+impl NumericVersion {
+    /// Unpacks a raw `cl_version`
+    pub fn from_raw(version: u32) -> Self {
+        Self {
+            major: version >> 22,
+            minor: (version >> 12) & 0x3ff,
+            patch: version & 0xfff,
+        }
+    }
+}
+
+impl std::fmt::Display for NumericVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Name of an intermediate language, extension or feature together with its version
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct NameVersion {
+    /// Name, eg. `SPIR-V` or `__opencl_c_fp64`
+    pub name: String,
+    /// Version of the named item
+    pub version: NumericVersion,
+}
+
+#[cfg(feature = "opencl")]
+impl From<&opencl3::device::cl_name_version> for NameVersion {
+    fn from(value: &opencl3::device::cl_name_version) -> Self {
+        let end = value
+            .name
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(value.name.len());
+        Self {
+            name: String::from_utf8_lossy(&value.name[..end]).into_owned(),
+            version: NumericVersion::from_raw(value.version),
+        }
+    }
+}
+
+impl std::fmt::Display for NameVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.name, self.version)
+    }
+}
+
+/// Bitfield of `CL_DEVICE_ATOMIC_MEMORY_CAPABILITIES` or `CL_DEVICE_ATOMIC_FENCE_CAPABILITIES`
+/// ```
+/// use opencl3_select::AtomicCapabilities;
+/// let capabilities = AtomicCapabilities(0b0011001);
+/// assert!(capabilities.contains(AtomicCapabilities::ORDER_RELAXED));
+/// assert!(!capabilities.contains(AtomicCapabilities::ORDER_SEQ_CST));
+/// assert_eq!(capabilities.labels(), ["relaxed", "work_item", "work_group"]);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(transparent))]
+pub struct AtomicCapabilities(pub u64);
+
+impl AtomicCapabilities {
+    /// Relaxed memory ordering
+    pub const ORDER_RELAXED: u64 = 1 << 0;
+    /// Acquire-release memory ordering
+    pub const ORDER_ACQ_REL: u64 = 1 << 1;
+    /// Sequentially consistent memory ordering
+    pub const ORDER_SEQ_CST: u64 = 1 << 2;
+    /// Work-item scope
+    pub const SCOPE_WORK_ITEM: u64 = 1 << 3;
+    /// Work-group scope
+    pub const SCOPE_WORK_GROUP: u64 = 1 << 4;
+    /// Device scope
+    pub const SCOPE_DEVICE: u64 = 1 << 5;
+    /// Scope of all devices in the context
+    pub const SCOPE_ALL_DEVICES: u64 = 1 << 6;
+
+    /// All known bits together with their labels
+    const LABELS: [(u64, &'static str); 7] = [
+        (Self::ORDER_RELAXED, "relaxed"),
+        (Self::ORDER_ACQ_REL, "acq_rel"),
+        (Self::ORDER_SEQ_CST, "seq_cst"),
+        (Self::SCOPE_WORK_ITEM, "work_item"),
+        (Self::SCOPE_WORK_GROUP, "work_group"),
+        (Self::SCOPE_DEVICE, "device"),
+        (Self::SCOPE_ALL_DEVICES, "all_devices"),
+    ];
+
+    /// Checks if all given bits are set
+    pub fn contains(&self, bits: u64) -> bool {
+        self.0 & bits == bits
+    }
+
+    /// Labels of the set orderings followed by the set scopes
+    pub fn labels(&self) -> Vec<&'static str> {
+        Self::LABELS
+            .iter()
+            .filter(|(bit, _)| self.contains(*bit))
+            .map(|(_, label)| *label)
+            .collect()
+    }
+}
+
+impl std::fmt::Display for AtomicCapabilities {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.labels().join(" "))
+    }
+}
+
+/// Decoded `CL_DEVICE_SVM_CAPABILITIES`, the shared virtual memory supported by a device
+/// ```
+/// use opencl3_select::SvmCapabilities;
+/// let capabilities = SvmCapabilities::from_raw(0b1011);
+/// assert!(capabilities.coarse_grain_buffer && capabilities.fine_grain_buffer);
+/// assert!(!capabilities.fine_grain_system);
+/// assert_eq!(capabilities.to_string(), "coarse_grain_buffer fine_grain_buffer atomics");
+/// assert_eq!(SvmCapabilities::from_raw(0).to_string(), "none");
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct SvmCapabilities {
+    /// Buffers can be shared at the granularity of the whole buffer
+    pub coarse_grain_buffer: bool,
+    /// Buffers can be shared at the granularity of individual loads and stores
+    pub fine_grain_buffer: bool,
+    /// The entire host memory can be shared
+    pub fine_grain_system: bool,
+    /// Atomic operations keep memory consistent between host and device
+    pub atomics: bool,
+}
+
+impl SvmCapabilities {
+    /// Bit of `CL_DEVICE_SVM_COARSE_GRAIN_BUFFER`
+    pub const COARSE_GRAIN_BUFFER: u64 = 1 << 0;
+    /// Bit of `CL_DEVICE_SVM_FINE_GRAIN_BUFFER`
+    pub const FINE_GRAIN_BUFFER: u64 = 1 << 1;
+    /// Bit of `CL_DEVICE_SVM_FINE_GRAIN_SYSTEM`
+    pub const FINE_GRAIN_SYSTEM: u64 = 1 << 2;
+    /// Bit of `CL_DEVICE_SVM_ATOMICS`
+    pub const ATOMICS: u64 = 1 << 3;
+
+    /// Decodes the raw bitfield
+    pub fn from_raw(bits: u64) -> Self {
+        Self {
+            coarse_grain_buffer: bits & Self::COARSE_GRAIN_BUFFER != 0,
+            fine_grain_buffer: bits & Self::FINE_GRAIN_BUFFER != 0,
+            fine_grain_system: bits & Self::FINE_GRAIN_SYSTEM != 0,
+            atomics: bits & Self::ATOMICS != 0,
+        }
+    }
+
+    /// Labels of the supported kinds of shared virtual memory
+    pub fn labels(&self) -> Vec<&'static str> {
+        [
+            (self.coarse_grain_buffer, "coarse_grain_buffer"),
+            (self.fine_grain_buffer, "fine_grain_buffer"),
+            (self.fine_grain_system, "fine_grain_system"),
+            (self.atomics, "atomics"),
+        ]
+        .into_iter()
+        .filter(|(supported, _)| *supported)
+        .map(|(_, label)| label)
+        .collect()
+    }
+}
+
+impl std::fmt::Display for SvmCapabilities {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.labels() {
+            labels if labels.is_empty() => write!(f, "none"),
+            labels => write!(f, "{}", labels.join(" ")),
+        }
+    }
+}
+
+/// Way in which a device can be split into sub-devices, see `CL_DEVICE_PARTITION_PROPERTIES`
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum PartitionScheme {
+    /// Into as many sub-devices as possible with the same number of compute units each
+    Equally,
+    /// Into sub-devices with the given numbers of compute units
+    ByCounts,
+    /// Along the given affinity domain, see [AffinityDomains]
+    ByAffinityDomain,
+}
+
+impl PartitionScheme {
+    /// Decodes a raw `cl_device_partition_property`, `None` for unknown values
+    /// ```
+    /// use opencl3_select::PartitionScheme;
+    /// assert_eq!(PartitionScheme::from_raw(0x1086), Some(PartitionScheme::Equally));
+    /// assert_eq!(PartitionScheme::from_raw(0), None);
+    /// ```
+    pub fn from_raw(property: isize) -> Option<Self> {
+        match property {
+            0x1086 => Some(Self::Equally),
+            0x1087 => Some(Self::ByCounts),
+            0x1088 => Some(Self::ByAffinityDomain),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PartitionScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Equally => write!(f, "equally"),
+            Self::ByCounts => write!(f, "by_counts"),
+            Self::ByAffinityDomain => write!(f, "by_affinity_domain"),
+        }
+    }
+}
+
+/// Bitfield of `CL_DEVICE_PARTITION_AFFINITY_DOMAIN`
+/// ```
+/// use opencl3_select::AffinityDomains;
+/// let domains = AffinityDomains(AffinityDomains::NUMA | AffinityDomains::L2_CACHE);
+/// assert!(domains.contains(AffinityDomains::NUMA));
+/// assert_eq!(domains.to_string(), "numa l2_cache");
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(transparent))]
+pub struct AffinityDomains(pub u64);
+
+impl AffinityDomains {
+    /// Compute units sharing a NUMA node
+    pub const NUMA: u64 = 1 << 0;
+    /// Compute units sharing a level 4 cache
+    pub const L4_CACHE: u64 = 1 << 1;
+    /// Compute units sharing a level 3 cache
+    pub const L3_CACHE: u64 = 1 << 2;
+    /// Compute units sharing a level 2 cache
+    pub const L2_CACHE: u64 = 1 << 3;
+    /// Compute units sharing a level 1 cache
+    pub const L1_CACHE: u64 = 1 << 4;
+    /// The next domain of the above along which the device can be split
+    pub const NEXT_PARTITIONABLE: u64 = 1 << 5;
+
+    /// All known bits together with their labels
+    const LABELS: [(u64, &'static str); 6] = [
+        (Self::NUMA, "numa"),
+        (Self::L4_CACHE, "l4_cache"),
+        (Self::L3_CACHE, "l3_cache"),
+        (Self::L2_CACHE, "l2_cache"),
+        (Self::L1_CACHE, "l1_cache"),
+        (Self::NEXT_PARTITIONABLE, "next_partitionable"),
+    ];
+
+    /// Checks if all given bits are set
+    pub fn contains(&self, bits: u64) -> bool {
+        self.0 & bits == bits
+    }
+
+    /// Labels of the set domains
+    pub fn labels(&self) -> Vec<&'static str> {
+        Self::LABELS
+            .iter()
+            .filter(|(bit, _)| self.contains(*bit))
+            .map(|(_, label)| *label)
+            .collect()
+    }
+}
+
+impl std::fmt::Display for AffinityDomains {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.labels().as_slice() {
+            [] => write!(f, "none"),
+            labels => write!(f, "{}", labels.join(" ")),
+        }
+    }
+}
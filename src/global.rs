@@ -0,0 +1,81 @@
+//! Process-wide [Selector] for libraries which can not be handed the selected device
+
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+use crate::{SelectionSpec, Selector};
+
+/// Environment variable naming the device of the global selector, unless the application
+/// installed a selector of its own with [set_global]
+pub const GLOBAL_ENV_OVERRIDE: &str = "OPENCL_DEVICE";
+
+/// Selector returned by [current]
+static GLOBAL: OnceLock<RwLock<Selector>> = OnceLock::new();
+
+/// The global selector, created on first use
+///
+/// Similar to a global logger, the application decides how devices are chosen by calling
+/// [set_global] early, and libraries deep in a call stack read the chosen device from here.
+/// Unless an application installed one, the selector resolves the default [SelectionSpec]
+/// overridden by [GLOBAL_ENV_OVERRIDE]. Nothing is resolved until the selector is
+/// [updated](Selector::update), see [current_device].
+/// ```
+/// use opencl3_select::{ClState, DeviceInfo, PlatformInfo};
+/// let state = ClState::from_platforms(vec![PlatformInfo::builder()
+///     .name("PoCL")
+///     .device(DeviceInfo::builder().name("cpu-haswell").build())
+///     .build()]);
+/// assert!(opencl3_select::current().read().unwrap().current().is_none());
+///
+/// opencl3_select::current().write().unwrap().update(&state)?;
+/// let selector = opencl3_select::current().read().unwrap();
+/// assert_eq!(selector.current().unwrap().name(), "cpu-haswell");
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+pub fn current() -> &'static RwLock<Selector> {
+    GLOBAL.get_or_init(|| {
+        RwLock::new(Selector::new(SelectionSpec::default()).env_override(GLOBAL_ENV_OVERRIDE))
+    })
+}
+
+/// Installs the selector returned by [current]
+///
+/// Fails with the given selector if the global one was already used or installed.
+/// ```
+/// use opencl3_select::{Selector, SelectionSpec};
+/// let selector = Selector::new(SelectionSpec::default()).env_override("MY_APP_DEVICE");
+/// assert!(opencl3_select::set_global(selector).is_ok());
+/// let again = Selector::new(SelectionSpec::default());
+/// assert!(opencl3_select::set_global(again).is_err());
+/// ```
+#[allow(clippy::result_large_err)]
+pub fn set_global(selector: Selector) -> Result<(), Selector> {
+    GLOBAL
+        .set(RwLock::new(selector))
+        .map_err(|lock| lock.into_inner().unwrap_or_else(PoisonError::into_inner))
+}
+
+/// The device of the global selector, enumerating the devices of this machine if none was
+/// resolved yet
+///
+/// Returns `None` if no device fulfills the spec, the devices are enumerated again on the next
+/// call in that case.
+/// ```no_run
+/// if let Some(device) = opencl3_select::current_device()? {
+///     println!("running on {}", device.name());
+/// }
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+#[cfg(feature = "opencl")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "opencl")))]
+pub fn current_device() -> crate::Result<Option<crate::DeviceInfo>> {
+    if let Some(device) = current()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .current()
+    {
+        return Ok(Some(device.clone()));
+    }
+    let cl_state = crate::get_setup()?;
+    let mut selector = current().write().unwrap_or_else(PoisonError::into_inner);
+    Ok(selector.update(&cl_state)?.cloned())
+}
@@ -0,0 +1,348 @@
+//! Named groups of devices managed by the user, eg. `compute` or `display`
+
+use std::collections::BTreeMap;
+
+use crate::{ClState, DeviceInfo};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Identifies a device across runs and machines with the same setup
+///
+/// Devices are matched by their UUID if both sides report one and by their platform, vendor,
+/// name and version otherwise.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct DeviceFingerprint {
+    /// Name of the platform the device was found on
+    pub platform: String,
+    /// Unique identifier of the vendor
+    pub vendor_id: u32,
+    /// Name of the device
+    pub name: String,
+    /// OpenCL version string of the device
+    pub version: String,
+    /// Universally unique identifier of the device if known
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub uuid: Option<[u8; 16]>,
+}
+
+impl DeviceFingerprint {
+    /// Checks if the fingerprint describes the given device
+    pub fn matches(&self, device: &DeviceInfo) -> bool {
+        match (self.uuid, device.uuid()) {
+            (Some(uuid), Some(other)) => uuid == other,
+            _ => {
+                self.platform == device.platform_ref()
+                    && self.vendor_id == device.vendor_id()
+                    && self.name == device.name_ref()
+                    && self.version == device.version_ref()
+            }
+        }
+    }
+}
+
+impl DeviceInfo {
+    /// Fingerprint by which the device is recognized in [DeviceGroups]
+    pub fn fingerprint(&self) -> DeviceFingerprint {
+        DeviceFingerprint {
+            platform: self.platform(),
+            vendor_id: self.vendor_id(),
+            name: self.name(),
+            version: self.version(),
+            uuid: self.uuid(),
+        }
+    }
+}
+
+/// Named groups of devices such as `compute`, `display` or `ci`
+///
+/// A device can be part of any number of groups. Groups without devices are removed.
+#[cfg_attr(feature = "serde", doc = "```")]
+#[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+/// use opencl3_select::{ClState, DeviceGroups};
+/// let state: ClState = serde_json::from_str(r#"{"platforms": [{
+///     "name": "PoCL", "vendor": "The pocl project", "version": "OpenCL 3.0 PoCL",
+///     "profile": "FULL_PROFILE", "extensions": "", "devices": [{
+///         "platform": "PoCL", "vendor": "AuthenticAMD", "vendor_id": 4098,
+///         "vendor_id_text": "AMD", "name": "cpu-haswell", "version": "OpenCL 3.0 PoCL",
+///         "type": 2, "type_text": "CL_DEVICE_TYPE_CPU"
+///     }]
+/// }]}"#)?;
+/// let device = &state.get_all_devices()[0];
+/// let mut groups = DeviceGroups::default();
+/// assert!(groups.toggle("ci", device));
+/// assert_eq!(groups.groups_of(device), ["ci"]);
+/// assert_eq!(state.devices_in_group(&groups, "ci").len(), 1);
+/// assert!(!groups.toggle("ci", device));
+/// assert!(state.devices_in_group(&groups, "ci").is_empty());
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(default))]
+pub struct DeviceGroups {
+    /// Devices of every group by its name
+    pub groups: BTreeMap<String, Vec<DeviceFingerprint>>,
+}
+
+impl DeviceGroups {
+    /// Loads the groups from the given path or returns no groups if it does not exist
+    #[cfg(feature = "storage")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage")))]
+    pub fn load_or_default(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        match path.exists() {
+            true => crate::load(path),
+            false => Ok(Self::default()),
+        }
+    }
+
+    /// Checks if the device is part of the group
+    pub fn contains(&self, group: &str, device: &DeviceInfo) -> bool {
+        self.groups
+            .get(group)
+            .is_some_and(|members| members.iter().any(|member| member.matches(device)))
+    }
+
+    /// Adds the device to the group, returns `false` if it already was a member
+    pub fn tag(&mut self, group: &str, device: &DeviceInfo) -> bool {
+        if self.contains(group, device) {
+            return false;
+        }
+        self.groups
+            .entry(group.to_string())
+            .or_default()
+            .push(device.fingerprint());
+        true
+    }
+
+    /// Removes the device from the group, returns `false` if it was no member
+    pub fn untag(&mut self, group: &str, device: &DeviceInfo) -> bool {
+        let Some(members) = self.groups.get_mut(group) else {
+            return false;
+        };
+        let len = members.len();
+        members.retain(|member| !member.matches(device));
+        let removed = members.len() < len;
+        if members.is_empty() {
+            self.groups.remove(group);
+        }
+        removed
+    }
+
+    /// Adds the device to the group or removes it if it already was a member
+    ///
+    /// Returns whether the device is a member afterwards.
+    pub fn toggle(&mut self, group: &str, device: &DeviceInfo) -> bool {
+        !self.untag(group, device) && self.tag(group, device)
+    }
+
+    /// Names of all groups the device is part of
+    pub fn groups_of(&self, device: &DeviceInfo) -> Vec<&str> {
+        self.groups
+            .keys()
+            .filter(|group| self.contains(group, device))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+impl ClState {
+    /// All present devices which are part of the named group
+    pub fn devices_in_group(&self, groups: &DeviceGroups, group: &str) -> Vec<DeviceInfo> {
+        self.get_all_devices()
+            .into_iter()
+            .filter(|device| groups.contains(group, device))
+            .collect()
+    }
+}
+
+/// Nicknames such as `big-gpu` or `laptop-igpu` given to devices by the user
+///
+/// Every device has at most one nickname and every nickname belongs to one device. Nicknames
+/// can be used wherever a [DeviceSpec](crate::DeviceSpec) is parsed, written as
+/// `name:<nickname>`.
+/// ```
+/// use opencl3_select::{ClState, DeviceInfo, DeviceNicknames, DeviceSpec, PlatformInfo};
+/// let state = ClState::from_platforms(vec![PlatformInfo::builder()
+///     .name("PoCL")
+///     .device(DeviceInfo::builder().name("cpu-haswell").build())
+///     .device(DeviceInfo::builder().name("NVIDIA GeForce RTX 3060").build())
+///     .build()]);
+/// let device = &state.get_all_devices()[1];
+/// let mut nicknames = DeviceNicknames::default();
+/// nicknames.set("big-gpu", device);
+/// assert_eq!(nicknames.nickname_of(device), Some("big-gpu"));
+///
+/// let spec = DeviceSpec::parse("name:big-gpu", &nicknames);
+/// assert_eq!(spec.to_string(), "name:big-gpu");
+/// assert_eq!(spec.find(&state)?.name(), "NVIDIA GeForce RTX 3060");
+///
+/// assert_eq!(nicknames.remove(device).as_deref(), Some("big-gpu"));
+/// assert_eq!(nicknames.nickname_of(device), None);
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(default))]
+pub struct DeviceNicknames {
+    /// Device of every nickname
+    pub nicknames: BTreeMap<String, DeviceFingerprint>,
+}
+
+impl DeviceNicknames {
+    /// Loads the nicknames from the given path or returns none if it does not exist
+    #[cfg(feature = "storage")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage")))]
+    pub fn load_or_default(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        match path.exists() {
+            true => crate::load(path),
+            false => Ok(Self::default()),
+        }
+    }
+
+    /// The device with the given nickname
+    pub fn get(&self, nickname: &str) -> Option<&DeviceFingerprint> {
+        self.nicknames.get(nickname)
+    }
+
+    /// Nickname of the device if it has one
+    pub fn nickname_of(&self, device: &DeviceInfo) -> Option<&str> {
+        self.nicknames
+            .iter()
+            .find(|(_, fingerprint)| fingerprint.matches(device))
+            .map(|(nickname, _)| nickname.as_str())
+    }
+
+    /// Gives the device the nickname, replacing its previous one
+    ///
+    /// A device which had this nickname before loses it.
+    pub fn set(&mut self, nickname: &str, device: &DeviceInfo) {
+        self.remove(device);
+        self.nicknames
+            .insert(nickname.to_string(), device.fingerprint());
+    }
+
+    /// Removes the nickname of the device and returns it
+    pub fn remove(&mut self, device: &DeviceInfo) -> Option<String> {
+        let nickname = self.nickname_of(device)?.to_string();
+        self.nicknames.remove(&nickname);
+        Some(nickname)
+    }
+}
+
+/// Free-text notes of the user about devices, eg. `thermal throttles under sustained load`
+///
+/// Every device has at most one note, which is kept across runs like the other data of the user.
+/// ```
+/// use opencl3_select::{ClState, DeviceInfo, DeviceNotes, PlatformInfo};
+/// let state = ClState::from_platforms(vec![PlatformInfo::builder()
+///     .name("NVIDIA CUDA")
+///     .device(DeviceInfo::builder().name("NVIDIA GeForce RTX 3060").build())
+///     .build()]);
+/// let device = &state.get_all_devices()[0];
+/// let mut notes = DeviceNotes::default();
+/// notes.set(device, "shared with display");
+/// notes.set(device, "thermal throttles under sustained load");
+/// assert_eq!(notes.note_of(device), Some("thermal throttles under sustained load"));
+///
+/// assert!(notes.remove(device).is_some());
+/// assert_eq!(notes.note_of(device), None);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(default))]
+pub struct DeviceNotes {
+    /// Note of every device
+    pub notes: Vec<(DeviceFingerprint, String)>,
+}
+
+impl DeviceNotes {
+    /// Loads the notes from the given path or returns none if it does not exist
+    #[cfg(feature = "storage")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage")))]
+    pub fn load_or_default(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        match path.exists() {
+            true => crate::load(path),
+            false => Ok(Self::default()),
+        }
+    }
+
+    /// Note of the device if it has one
+    pub fn note_of(&self, device: &DeviceInfo) -> Option<&str> {
+        self.notes
+            .iter()
+            .find(|(fingerprint, _)| fingerprint.matches(device))
+            .map(|(_, note)| note.as_str())
+    }
+
+    /// Attaches the note to the device, replacing its previous one
+    pub fn set(&mut self, device: &DeviceInfo, note: &str) {
+        self.remove(device);
+        self.notes.push((device.fingerprint(), note.to_string()));
+    }
+
+    /// Removes the note of the device and returns it
+    pub fn remove(&mut self, device: &DeviceInfo) -> Option<String> {
+        let i = self
+            .notes
+            .iter()
+            .position(|(fingerprint, _)| fingerprint.matches(device))?;
+        Some(self.notes.remove(i).1)
+    }
+}
+
+/// Devices which are left out of lists and selections, eg. phantom devices of a broken ICD
+/// ```
+/// use opencl3_select::{ClState, DeviceInfo, HiddenDevices, PlatformInfo};
+/// let mut state = ClState::from_platforms(vec![PlatformInfo::builder()
+///     .device(DeviceInfo::builder().name("phantom").build())
+///     .device(DeviceInfo::builder().name("gpu").build())
+///     .build()]);
+/// let mut hidden = HiddenDevices::default();
+/// assert!(hidden.toggle(&state.get_all_devices()[0]));
+/// hidden.apply(&mut state);
+/// assert_eq!(state.get_all_devices()[0].name(), "gpu");
+/// assert_eq!(state.get_all_devices().len(), 1);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(default))]
+pub struct HiddenDevices {
+    /// Hidden devices in the order in which they were hidden
+    pub devices: Vec<DeviceFingerprint>,
+}
+
+impl HiddenDevices {
+    /// Loads the hidden devices from the given path or returns none if it does not exist
+    #[cfg(feature = "storage")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage")))]
+    pub fn load_or_default(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        match path.exists() {
+            true => crate::load(path),
+            false => Ok(Self::default()),
+        }
+    }
+
+    /// Checks if the device is hidden
+    pub fn contains(&self, device: &DeviceInfo) -> bool {
+        self.devices.iter().any(|hidden| hidden.matches(device))
+    }
+
+    /// Hides the device or shows it again if it already was hidden
+    ///
+    /// Returns whether the device is hidden afterwards.
+    pub fn toggle(&mut self, device: &DeviceInfo) -> bool {
+        let len = self.devices.len();
+        self.devices.retain(|hidden| !hidden.matches(device));
+        if self.devices.len() < len {
+            return false;
+        }
+        self.devices.push(device.fingerprint());
+        true
+    }
+
+    /// Removes the hidden devices from the state
+    pub fn apply(&self, cl_state: &mut ClState) {
+        cl_state.retain_devices(|device| !self.contains(device));
+    }
+}
@@ -0,0 +1,194 @@
+//! Information about the machine the platforms and devices were found on
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Operating system, architecture and OpenCL ICD loader of a machine
+///
+/// Attached to every [ClState](crate::ClState) obtained by enumeration such that saved snapshots
+/// and reports identify the machine they came from.
+/// ```
+/// use opencl3_select::HostInfo;
+/// let host = HostInfo::current();
+/// assert_eq!(host.os, std::env::consts::OS);
+/// assert_eq!(host.arch, std::env::consts::ARCH);
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct HostInfo {
+    /// Operating system, eg. `linux` or `windows`
+    pub os: String,
+    /// Architecture of the CPU, eg. `x86_64` or `aarch64`
+    pub arch: String,
+    /// Name of the machine if it could be determined
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hostname: Option<String>,
+    /// Version of the OpenCL ICD loader if it could be determined
+    ///
+    /// Currently only detected on Linux, from the file name of the loaded `libOpenCL.so`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub icd_loader_version: Option<String>,
+}
+
+impl HostInfo {
+    /// Describes the machine the program is running on
+    pub fn current() -> Self {
+        Self {
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            hostname: hostname(),
+            icd_loader_version: icd_loader_version(),
+        }
+    }
+}
+
+impl std::fmt::Display for HostInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(hostname) = &self.hostname {
+            write!(f, "{hostname} ")?;
+        }
+        write!(f, "({} {})", self.os, self.arch)?;
+        if let Some(version) = &self.icd_loader_version {
+            write!(f, ", ICD loader {version}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Name of the machine as reported by the kernel or the environment
+fn hostname() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Version suffix of the `libOpenCL.so` mapped into the current process, eg. `1.0.0`
+fn icd_loader_version() -> Option<String> {
+    let maps = std::fs::read_to_string("/proc/self/maps").ok()?;
+    maps.lines()
+        .filter_map(|line| line.rsplit('/').next())
+        .filter_map(|file| file.strip_prefix("libOpenCL.so."))
+        .max_by_key(|version| version.len())
+        .map(str::to_string)
+}
+
+/// Processor of the machine, attached to CPU devices whose OpenCL names are often generic
+///
+/// Queried with the `sysinfo` feature, see [current](CpuInfo::current).
+/// ```
+/// use opencl3_select::CpuInfo;
+/// let cpu = CpuInfo {
+///     model: Some("AMD Ryzen 9 7950X 16-Core Processor".into()),
+///     cores: Some(16),
+///     threads: 32,
+///     numa_nodes: Some(1),
+/// };
+/// assert_eq!(
+///     cpu.to_string(),
+///     "AMD Ryzen 9 7950X 16-Core Processor (16 cores, 32 threads, 1 NUMA node)"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct CpuInfo {
+    /// Model name, eg. `AMD Ryzen 9 7950X 16-Core Processor`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub model: Option<String>,
+    /// Number of physical cores if it could be determined
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cores: Option<u32>,
+    /// Number of logical processors, ie. hardware threads
+    pub threads: u32,
+    /// Number of NUMA nodes if it could be determined
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub numa_nodes: Option<u32>,
+}
+
+#[cfg(feature = "sysinfo")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "sysinfo")))]
+impl CpuInfo {
+    /// Describes the processor of the machine the program is running on
+    ///
+    /// Model, cores and NUMA nodes are read from `/proc/cpuinfo` and `/sys` on Linux. Elsewhere
+    /// only the model is known on Windows and the number of threads on every system.
+    pub fn current() -> Self {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get() as u32);
+        let Ok(cpuinfo) = std::fs::read_to_string("/proc/cpuinfo") else {
+            return Self {
+                model: std::env::var("PROCESSOR_IDENTIFIER").ok(),
+                threads,
+                ..Default::default()
+            };
+        };
+        let mut cpu = Self::from_cpuinfo(&cpuinfo);
+        cpu.threads = cpu.threads.max(threads);
+        cpu.numa_nodes = numa_nodes();
+        cpu
+    }
+
+    /// Model, cores and threads listed in the contents of `/proc/cpuinfo`
+    fn from_cpuinfo(cpuinfo: &str) -> Self {
+        let mut model = None;
+        let mut threads = 0;
+        let mut cores = std::collections::BTreeSet::new();
+        // Every logical processor is described by a block of lines separated by an empty one
+        for processor in cpuinfo.split("\n\n") {
+            let field = |name: &str| {
+                processor.lines().find_map(|line| {
+                    let (key, value) = line.split_once(':')?;
+                    (key.trim() == name).then(|| value.trim())
+                })
+            };
+            if field("processor").is_none() {
+                continue;
+            }
+            threads += 1;
+            model = model.or_else(|| field("model name").or(field("Model")).map(str::to_string));
+            if let (Some(package), Some(core)) = (field("physical id"), field("core id")) {
+                cores.insert((package.to_string(), core.to_string()));
+            }
+        }
+        Self {
+            model,
+            cores: (!cores.is_empty()).then_some(cores.len() as u32),
+            threads,
+            numa_nodes: None,
+        }
+    }
+}
+
+/// Number of NUMA nodes as listed by the Linux kernel
+#[cfg(feature = "sysinfo")]
+fn numa_nodes() -> Option<u32> {
+    let nodes = std::fs::read_dir("/sys/devices/system/node")
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            name.to_str()
+                .and_then(|name| name.strip_prefix("node"))
+                .is_some_and(|index| !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()))
+        })
+        .count();
+    (nodes > 0).then_some(nodes as u32)
+}
+
+impl std::fmt::Display for CpuInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |n: u32, unit: &str| match n {
+            1 => format!("1 {unit}"),
+            n => format!("{n} {unit}s"),
+        };
+        let mut counts = vec![];
+        counts.extend(self.cores.map(|cores| plural(cores, "core")));
+        counts.push(plural(self.threads, "thread"));
+        counts.extend(self.numa_nodes.map(|nodes| plural(nodes, "NUMA node")));
+        match &self.model {
+            Some(model) => write!(f, "{model} ({})", counts.join(", ")),
+            None => write!(f, "{}", counts.join(", ")),
+        }
+    }
+}
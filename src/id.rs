@@ -0,0 +1,144 @@
+//! Short, stable identifiers of platforms and devices
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{ClState, DeviceFingerprint, DeviceInfo, PlatformInfo};
+
+/// Hashes the parts with 64 bit FNV-1a, which unlike [std::hash::DefaultHasher] is guaranteed to
+/// give the same value on every machine and with every version of Rust
+fn stable_hash<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    parts.into_iter().fold(OFFSET_BASIS, |hash, part| {
+        // Terminate every part such that eg. ("ab", "c") and ("a", "bc") differ
+        part.iter().chain([0xff].iter()).fold(hash, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+        })
+    })
+}
+
+/// Defines an identifier displayed, parsed and serialized as 16 hexadecimal digits
+macro_rules! define_id {
+    ($(#[$meta:meta])* $id:ident, $kind:literal) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        #[cfg_attr(
+            feature = "serde",
+            derive(Deserialize, Serialize),
+            serde(into = "String", try_from = "String")
+        )]
+        pub struct $id(u64);
+
+        impl std::fmt::Display for $id {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.pad(&format!("{:016x}", self.0))
+            }
+        }
+
+        impl std::str::FromStr for $id {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.len() {
+                    16 => u64::from_str_radix(s, 16).map(Self).ok(),
+                    _ => None,
+                }
+                .ok_or_else(|| format!("invalid {} id \"{s}\", expected 16 hex digits", $kind))
+            }
+        }
+
+        impl From<$id> for String {
+            fn from(id: $id) -> Self {
+                id.to_string()
+            }
+        }
+
+        impl TryFrom<String> for $id {
+            type Error = String;
+
+            fn try_from(id: String) -> Result<Self, Self::Error> {
+                id.parse()
+            }
+        }
+    };
+}
+
+define_id!(
+    /// Identifies a platform by its name, vendor and version, see [PlatformInfo::id]
+    PlatformId,
+    "platform"
+);
+
+define_id!(
+    /// Identifies a device across runs and machines, derived from its [DeviceFingerprint]
+    ///
+    /// Ids are cheap to copy, hash and compare, which makes them suitable as keys of maps or for
+    /// passing devices between processes. Devices without UUID which only differ in properties
+    /// outside their fingerprint, eg. two identical GPUs, share the same id.
+    /// ```
+    /// use opencl3_select::{ClState, DeviceId, DeviceInfo, PlatformInfo};
+    /// let state = ClState::from_platforms(vec![PlatformInfo::builder()
+    ///     .name("PoCL")
+    ///     .device(DeviceInfo::builder().name("cpu-haswell").build())
+    ///     .build()]);
+    /// let device = &state.get_all_devices()[0];
+    /// let id = device.id();
+    /// assert_eq!(id, device.fingerprint().id());
+    /// assert_eq!(id.to_string().parse::<DeviceId>(), Ok(id));
+    /// assert_eq!(state.device(id), Some(device));
+    /// ```
+    DeviceId,
+    "device"
+);
+
+impl DeviceFingerprint {
+    /// Identifier of the fingerprinted device, see [DeviceId]
+    pub fn id(&self) -> DeviceId {
+        let id = match &self.uuid {
+            Some(uuid) => stable_hash([b"uuid".as_slice(), uuid]),
+            None => stable_hash([
+                self.platform.as_bytes(),
+                &self.vendor_id.to_le_bytes(),
+                self.name.as_bytes(),
+                self.version.as_bytes(),
+            ]),
+        };
+        DeviceId(id)
+    }
+}
+
+impl DeviceInfo {
+    /// Identifier of the device, see [DeviceId]
+    pub fn id(&self) -> DeviceId {
+        self.fingerprint().id()
+    }
+}
+
+impl PlatformInfo {
+    /// Identifier of the platform, see [PlatformId]
+    pub fn id(&self) -> PlatformId {
+        PlatformId(stable_hash([
+            self.name_ref().as_bytes(),
+            self.vendor_ref().as_bytes(),
+            self.version_ref().as_bytes(),
+        ]))
+    }
+}
+
+impl ClState {
+    /// The first device with the given id
+    pub fn device(&self, id: DeviceId) -> Option<&DeviceInfo> {
+        self.platforms_ref()
+            .iter()
+            .flat_map(|platform| platform.devices_ref())
+            .find(|device| device.id() == id)
+    }
+
+    /// The platform with the given id
+    pub fn platform(&self, id: PlatformId) -> Option<&PlatformInfo> {
+        self.platforms_ref()
+            .iter()
+            .find(|platform| platform.id() == id)
+    }
+}
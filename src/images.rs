@@ -0,0 +1,220 @@
+//! Image formats supported by a device, see `clGetSupportedImageFormats`
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Order and number of the channels of an image, eg. `RGBA`
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[allow(missing_docs)]
+pub enum ChannelOrder {
+    R,
+    A,
+    Rg,
+    Ra,
+    Rgb,
+    Rgba,
+    Bgra,
+    Argb,
+    Intensity,
+    Luminance,
+    Rx,
+    Rgx,
+    Rgbx,
+    Depth,
+    DepthStencil,
+    Srgb,
+    Srgbx,
+    Srgba,
+    Sbgra,
+    Abgr,
+}
+
+/// Raw `cl_channel_order` and name without the `CL_` prefix of every [ChannelOrder]
+const CHANNEL_ORDERS: [(ChannelOrder, u32, &str); 20] = [
+    (ChannelOrder::R, 0x10B0, "R"),
+    (ChannelOrder::A, 0x10B1, "A"),
+    (ChannelOrder::Rg, 0x10B2, "RG"),
+    (ChannelOrder::Ra, 0x10B3, "RA"),
+    (ChannelOrder::Rgb, 0x10B4, "RGB"),
+    (ChannelOrder::Rgba, 0x10B5, "RGBA"),
+    (ChannelOrder::Bgra, 0x10B6, "BGRA"),
+    (ChannelOrder::Argb, 0x10B7, "ARGB"),
+    (ChannelOrder::Intensity, 0x10B8, "INTENSITY"),
+    (ChannelOrder::Luminance, 0x10B9, "LUMINANCE"),
+    (ChannelOrder::Rx, 0x10BA, "Rx"),
+    (ChannelOrder::Rgx, 0x10BB, "RGx"),
+    (ChannelOrder::Rgbx, 0x10BC, "RGBx"),
+    (ChannelOrder::Depth, 0x10BD, "DEPTH"),
+    (ChannelOrder::DepthStencil, 0x10BE, "DEPTH_STENCIL"),
+    (ChannelOrder::Srgb, 0x10BF, "sRGB"),
+    (ChannelOrder::Srgbx, 0x10C0, "sRGBx"),
+    (ChannelOrder::Srgba, 0x10C1, "sRGBA"),
+    (ChannelOrder::Sbgra, 0x10C2, "sBGRA"),
+    (ChannelOrder::Abgr, 0x10C3, "ABGR"),
+];
+
+/// Data type of a single channel of an image, eg. `FLOAT`
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[allow(missing_docs)]
+pub enum ChannelType {
+    SnormInt8,
+    SnormInt16,
+    UnormInt8,
+    UnormInt16,
+    UnormShort565,
+    UnormShort555,
+    UnormInt101010,
+    SignedInt8,
+    SignedInt16,
+    SignedInt32,
+    UnsignedInt8,
+    UnsignedInt16,
+    UnsignedInt32,
+    HalfFloat,
+    Float,
+    UnormInt24,
+    UnormInt101010_2,
+}
+
+/// Raw `cl_channel_type` and name without the `CL_` prefix of every [ChannelType]
+const CHANNEL_TYPES: [(ChannelType, u32, &str); 17] = [
+    (ChannelType::SnormInt8, 0x10D0, "SNORM_INT8"),
+    (ChannelType::SnormInt16, 0x10D1, "SNORM_INT16"),
+    (ChannelType::UnormInt8, 0x10D2, "UNORM_INT8"),
+    (ChannelType::UnormInt16, 0x10D3, "UNORM_INT16"),
+    (ChannelType::UnormShort565, 0x10D4, "UNORM_SHORT_565"),
+    (ChannelType::UnormShort555, 0x10D5, "UNORM_SHORT_555"),
+    (ChannelType::UnormInt101010, 0x10D6, "UNORM_INT_101010"),
+    (ChannelType::SignedInt8, 0x10D7, "SIGNED_INT8"),
+    (ChannelType::SignedInt16, 0x10D8, "SIGNED_INT16"),
+    (ChannelType::SignedInt32, 0x10D9, "SIGNED_INT32"),
+    (ChannelType::UnsignedInt8, 0x10DA, "UNSIGNED_INT8"),
+    (ChannelType::UnsignedInt16, 0x10DB, "UNSIGNED_INT16"),
+    (ChannelType::UnsignedInt32, 0x10DC, "UNSIGNED_INT32"),
+    (ChannelType::HalfFloat, 0x10DD, "HALF_FLOAT"),
+    (ChannelType::Float, 0x10DE, "FLOAT"),
+    (ChannelType::UnormInt24, 0x10DF, "UNORM_INT24"),
+    (ChannelType::UnormInt101010_2, 0x10E0, "UNORM_INT_101010_2"),
+];
+
+/// Implements decoding, [Display](std::fmt::Display) and [FromStr](std::str::FromStr) of a
+/// channel enum from its table of raw values and names
+macro_rules! impl_channel(
+    ($channel:ident, $table:ident, $what:literal) => {
+        impl $channel {
+            /// Decodes the raw OpenCL value, `None` for unknown values
+            pub fn from_raw(raw: u32) -> Option<Self> {
+                $table
+                    .iter()
+                    .find(|(_, known, _)| *known == raw)
+                    .map(|(channel, _, _)| *channel)
+            }
+
+            /// Name without the `CL_` prefix
+            pub fn name(&self) -> &'static str {
+                $table
+                    .iter()
+                    .find(|(channel, _, _)| channel == self)
+                    .map_or("", |(_, _, name)| name)
+            }
+        }
+
+        impl std::fmt::Display for $channel {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.name())
+            }
+        }
+
+        impl std::str::FromStr for $channel {
+            type Err = String;
+
+            /// Parses the name with or without the `CL_` prefix, ignoring case
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let name = match s.get(..3) {
+                    Some(prefix) if prefix.eq_ignore_ascii_case("CL_") => &s[3..],
+                    _ => s,
+                };
+                $table
+                    .iter()
+                    .find(|(_, _, known)| known.eq_ignore_ascii_case(name))
+                    .map(|(channel, _, _)| *channel)
+                    .ok_or_else(|| format!(concat!("unknown ", $what, " \"{}\""), s))
+            }
+        }
+    }
+);
+
+impl_channel!(ChannelOrder, CHANNEL_ORDERS, "channel order");
+impl_channel!(ChannelType, CHANNEL_TYPES, "channel type");
+
+/// Layout of the pixels of an image as reported by `clGetSupportedImageFormats`
+///
+/// Written as order and type separated by a slash, eg. `RGBA/FLOAT`, which is also its
+/// serialized form.
+/// ```
+/// use opencl3_select::{ChannelOrder, ChannelType, ImageFormat};
+/// let format: ImageFormat = "rgba/cl_float".parse()?;
+/// assert_eq!(format, ImageFormat::new(ChannelOrder::Rgba, ChannelType::Float));
+/// assert_eq!(format.to_string(), "RGBA/FLOAT");
+/// assert_eq!(ImageFormat::from_raw(0x10B5, 0x10D2).unwrap().to_string(), "RGBA/UNORM_INT8");
+/// assert!("RGBA".parse::<ImageFormat>().is_err());
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct ImageFormat {
+    /// Order of the channels
+    pub order: ChannelOrder,
+    /// Data type of every channel
+    pub data_type: ChannelType,
+}
+
+impl ImageFormat {
+    /// Format with the given channel order and data type
+    pub fn new(order: ChannelOrder, data_type: ChannelType) -> Self {
+        Self { order, data_type }
+    }
+
+    /// Decodes a raw `cl_image_format`, `None` if the order or type is unknown
+    pub fn from_raw(order: u32, data_type: u32) -> Option<Self> {
+        Some(Self::new(
+            ChannelOrder::from_raw(order)?,
+            ChannelType::from_raw(data_type)?,
+        ))
+    }
+}
+
+impl std::fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.order, self.data_type)
+    }
+}
+
+impl std::str::FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (order, data_type) = s
+            .split_once('/')
+            .ok_or_else(|| format!("image format \"{s}\" is not written as ORDER/TYPE"))?;
+        Ok(Self::new(order.trim().parse()?, data_type.trim().parse()?))
+    }
+}
+
+impl TryFrom<String> for ImageFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ImageFormat> for String {
+    fn from(value: ImageFormat) -> Self {
+        value.to_string()
+    }
+}
@@ -0,0 +1,142 @@
+//! Common interface of [PlatformInfo] and [DeviceInfo]
+
+use crate::{DeviceInfo, PlatformInfo};
+
+/// Properties shared by platforms and devices
+///
+/// Display code, exporters and search can be written once for both types.
+#[cfg_attr(feature = "serde", doc = "```")]
+#[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+/// use opencl3_select::{DeviceInfo, Info};
+/// let device: DeviceInfo = serde_json::from_str(r#"{
+///     "vendor": "University of Bristol", "vendor_id": 0, "vendor_id_text": "",
+///     "name": "Oclgrind Simulator", "version": "OpenCL 1.2 (Oclgrind 21.10)", "type": 4,
+///     "type_text": "CL_DEVICE_TYPE_GPU"
+/// }"#)?;
+/// assert_eq!(Info::name(&device), "Oclgrind Simulator");
+/// assert_eq!(device.kind(), "CL_DEVICE_TYPE_GPU");
+/// assert!(device.matches("bristol"));
+/// assert!(device.properties().contains(&("Vendor".into(), "University of Bristol".into())));
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub trait Info {
+    /// Name of the platform or device
+    fn name(&self) -> &str;
+
+    /// Vendor of the platform or device
+    fn vendor(&self) -> &str;
+
+    /// OpenCL version string of the platform or device
+    fn version(&self) -> &str;
+
+    /// `Platform` for platforms and the human-readable device type for devices
+    fn kind(&self) -> &str;
+
+    /// Labelled properties in the order in which they should be displayed
+    fn properties(&self) -> Vec<(String, String)>;
+
+    /// Checks if the name or the vendor contains the query, ignoring case
+    fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.name().to_lowercase().contains(&query) || self.vendor().to_lowercase().contains(&query)
+    }
+}
+
+impl Info for PlatformInfo {
+    fn name(&self) -> &str {
+        self.name_ref()
+    }
+
+    fn vendor(&self) -> &str {
+        self.vendor_ref()
+    }
+
+    fn version(&self) -> &str {
+        self.version_ref()
+    }
+
+    fn kind(&self) -> &str {
+        "Platform"
+    }
+
+    fn properties(&self) -> Vec<(String, String)> {
+        vec![
+            ("Name".into(), self.name_ref().into()),
+            ("Vendor".into(), self.vendor_ref().into()),
+            ("Version".into(), self.version_ref().into()),
+            ("Profile".into(), self.profile_ref().into()),
+            ("Devices".into(), self.devices_ref().len().to_string()),
+            ("Extensions".into(), self.extensions_ref().into()),
+        ]
+        .into_iter()
+        .chain(self.extra_ref().clone())
+        .collect()
+    }
+}
+
+impl Info for DeviceInfo {
+    fn name(&self) -> &str {
+        self.name_ref()
+    }
+
+    fn vendor(&self) -> &str {
+        self.vendor_ref()
+    }
+
+    fn version(&self) -> &str {
+        self.version_ref()
+    }
+
+    fn kind(&self) -> &str {
+        self.type_text_ref()
+    }
+
+    fn properties(&self) -> Vec<(String, String)> {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        vec![
+            ("Name".into(), self.name_ref().into()),
+            ("Platform".into(), self.platform_ref().into()),
+            ("Vendor".into(), self.vendor_ref().into()),
+            ("Version".into(), self.version_ref().into()),
+            ("Type".into(), self.type_text_ref().into()),
+            ("Profile".into(), optional(self.profile())),
+            ("OpenCL C Version".into(), optional(self.opencl_c_version())),
+            (
+                "Global Memory".into(),
+                optional(self.global_mem_size().map(crate::format_bytes)),
+            ),
+            (
+                "Max Allocation Size".into(),
+                optional(self.max_mem_alloc_size().map(crate::format_bytes)),
+            ),
+            (
+                "Local Memory".into(),
+                optional(self.local_mem_size().map(crate::format_bytes)),
+            ),
+            (
+                "Compute Units".into(),
+                optional(self.max_compute_units().map(|units| units.to_string())),
+            ),
+            (
+                "Max Clock Frequency".into(),
+                optional(self.max_clock_frequency().map(crate::format_frequency)),
+            ),
+            (
+                "Max Work Group Size".into(),
+                optional(self.max_work_group_size().map(|size| size.to_string())),
+            ),
+            (
+                "Image Support".into(),
+                optional(self.image_support().map(|support| support.to_string())),
+            ),
+            ("Extensions".into(), optional(self.extensions())),
+        ]
+        .into_iter()
+        .chain(
+            self.host_cpu_ref()
+                .map(|cpu| ("Host CPU".into(), cpu.to_string())),
+        )
+        .chain(self.extra_ref().clone())
+        .collect()
+    }
+}
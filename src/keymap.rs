@@ -0,0 +1,443 @@
+//! Actions of the TUI and the keys which trigger them
+
+use std::collections::{BTreeMap, HashMap};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+/// Everything the user can do while navigating the platforms, devices and priorities
+///
+/// Actions are named in `snake_case` in the config file, eg. `half_page_down`, `priority_3` or
+/// `view_2`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Action {
+    /// Leave without a selection
+    Abort,
+    /// Accept the current selection
+    Accept,
+    /// Highlight the next entry
+    Down,
+    /// Highlight the previous entry
+    Up,
+    /// Go one level up the hierarchy
+    Left,
+    /// Go one level down the hierarchy
+    Right,
+    /// Highlight the first entry
+    Top,
+    /// Highlight the last entry
+    Bottom,
+    /// Move down by half a page
+    HalfPageDown,
+    /// Move up by half a page
+    HalfPageUp,
+    /// Move down by a page
+    PageDown,
+    /// Move up by a page
+    PageUp,
+    /// Focus the next pane
+    NextPane,
+    /// Focus the previous pane
+    PreviousPane,
+    /// Start typing a filter
+    Filter,
+    /// Show the summary
+    Summary,
+    /// Show the history of selections
+    History,
+    /// Cycle the ordering of devices
+    Sort,
+    /// Mark or unmark the highlighted device for comparison
+    ToggleCompare,
+    /// Compare the marked devices
+    OpenCompare,
+    /// Copy all properties of the highlighted device
+    CopyInfo,
+    /// Copy the name of the highlighted device
+    CopyName,
+    /// Scroll the details down
+    ScrollDetailsDown,
+    /// Scroll the details up
+    ScrollDetailsUp,
+    /// Move the divider between the panes to the left
+    ShrinkLeft,
+    /// Move the divider between the panes to the right
+    GrowLeft,
+    /// Move the divider between the panes back to its configured position
+    ResetDivider,
+    /// Remove all devices from the priority list
+    ClearPriorities,
+    /// Grab the highlighted priority such that up and down move it, or drop it again
+    Grab,
+    /// Give the highlighted entry the priority, `0` being the highest
+    Priority(u8),
+    /// Enumerate the platforms and devices again
+    Refresh,
+    /// Remove the devices which disappeared in a refresh from the lists and the priorities
+    PruneMissing,
+    /// Save the selection to the `--output` file without leaving
+    Save,
+    /// Add the highlighted device to a group or remove it
+    Tag,
+    /// Give the highlighted device a nickname or remove it
+    Nickname,
+    /// Edit the note of the highlighted device
+    Note,
+    /// Jump to the next entry starting with the letter typed next
+    Jump,
+    /// Benchmark the devices with a priority or the highlighted one and show the progress
+    Benchmark,
+    /// Hide the highlighted device from now on
+    Hide,
+    /// Show the hidden devices
+    Hidden,
+    /// Switch to the view with the given number of the tab bar, starting at `1`
+    View(u8),
+    /// Switch to the next view of the tab bar
+    NextView,
+    /// Show all keys and the actions they trigger
+    Help,
+}
+
+/// Names of all actions except [Action::Priority] and [Action::View]
+const ACTION_NAMES: [(Action, &str); 41] = [
+    (Action::Abort, "abort"),
+    (Action::Accept, "accept"),
+    (Action::Down, "down"),
+    (Action::Up, "up"),
+    (Action::Left, "left"),
+    (Action::Right, "right"),
+    (Action::Top, "top"),
+    (Action::Bottom, "bottom"),
+    (Action::HalfPageDown, "half_page_down"),
+    (Action::HalfPageUp, "half_page_up"),
+    (Action::PageDown, "page_down"),
+    (Action::PageUp, "page_up"),
+    (Action::NextPane, "next_pane"),
+    (Action::PreviousPane, "previous_pane"),
+    (Action::Filter, "filter"),
+    (Action::Summary, "summary"),
+    (Action::History, "history"),
+    (Action::Sort, "sort"),
+    (Action::ToggleCompare, "toggle_compare"),
+    (Action::OpenCompare, "open_compare"),
+    (Action::CopyInfo, "copy_info"),
+    (Action::CopyName, "copy_name"),
+    (Action::ScrollDetailsDown, "scroll_details_down"),
+    (Action::ScrollDetailsUp, "scroll_details_up"),
+    (Action::ShrinkLeft, "shrink_left"),
+    (Action::GrowLeft, "grow_left"),
+    (Action::ResetDivider, "reset_divider"),
+    (Action::ClearPriorities, "clear_priorities"),
+    (Action::Grab, "grab"),
+    (Action::Refresh, "refresh"),
+    (Action::PruneMissing, "prune_missing"),
+    (Action::Save, "save"),
+    (Action::Tag, "tag"),
+    (Action::Nickname, "nickname"),
+    (Action::Note, "note"),
+    (Action::Jump, "jump"),
+    (Action::Benchmark, "benchmark"),
+    (Action::Hide, "hide"),
+    (Action::Hidden, "hidden"),
+    (Action::NextView, "next_view"),
+    (Action::Help, "help"),
+];
+
+impl std::str::FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(n) = s.strip_prefix("priority_") {
+            return match n.parse() {
+                Ok(n) if n <= 9 => Ok(Self::Priority(n)),
+                _ => Err(format!("unknown priority \"{n}\", expected 0 to 9")),
+            };
+        }
+        if let Some(n) = s.strip_prefix("view_") {
+            return match n.parse() {
+                Ok(n) if (1..=4).contains(&n) => Ok(Self::View(n)),
+                _ => Err(format!("unknown view \"{n}\", expected 1 to 4")),
+            };
+        }
+        ACTION_NAMES
+            .iter()
+            .find(|(_, name)| *name == s)
+            .map(|(action, _)| *action)
+            .ok_or_else(|| format!("unknown action \"{s}\""))
+    }
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Priority(n) => write!(f, "priority_{n}"),
+            Self::View(n) => write!(f, "view_{n}"),
+            action => {
+                let (_, name) = ACTION_NAMES
+                    .iter()
+                    .find(|(known, _)| known == action)
+                    .expect("every action except priorities and views is named");
+                write!(f, "{name}")
+            }
+        }
+    }
+}
+
+impl Serialize for Action {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Action {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A key together with whether Ctrl was held
+///
+/// Written as in the config file, eg. `j`, `C-d`, `PageDown`, `Enter` or `F1`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Key {
+    /// The pressed key
+    pub code: KeyCode,
+    /// Whether Ctrl was held
+    pub ctrl: bool,
+}
+
+impl Key {
+    /// Key without modifiers
+    const fn plain(code: KeyCode) -> Self {
+        Self { code, ctrl: false }
+    }
+
+    /// Key pressed while holding Ctrl
+    const fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            ctrl: true,
+        }
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Self {
+            code: event.code,
+            ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+        }
+    }
+}
+
+/// Names of keys which are no single character
+const KEY_NAMES: [(KeyCode, &str); 12] = [
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Esc, "Esc"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::BackTab, "BackTab"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Char(' '), "Space"),
+];
+
+impl std::str::FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ctrl, name) = match s.strip_prefix("C-") {
+            Some(name) if !name.is_empty() => (true, name),
+            _ => (false, s),
+        };
+        let mut chars = name.chars();
+        let function_key = name
+            .strip_prefix('F')
+            .and_then(|n| n.parse().ok())
+            .filter(|n| (1..=12).contains(n));
+        let code = match (chars.next(), chars.next(), function_key) {
+            (Some(c), None, _) => KeyCode::Char(c),
+            (_, _, Some(n)) => KeyCode::F(n),
+            _ => KEY_NAMES
+                .iter()
+                .find(|(_, known)| known.eq_ignore_ascii_case(name))
+                .map(|(code, _)| *code)
+                .ok_or_else(|| format!("unknown key \"{s}\""))?,
+        };
+        Ok(Self { code, ctrl })
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            write!(f, "C-")?;
+        }
+        match KEY_NAMES.iter().find(|(code, _)| *code == self.code) {
+            Some((_, name)) => write!(f, "{name}"),
+            None => match self.code {
+                KeyCode::Char(c) => write!(f, "{c}"),
+                KeyCode::F(n) => write!(f, "F{n}"),
+                code => write!(f, "{code:?}"),
+            },
+        }
+    }
+}
+
+impl Serialize for Key {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Built-in bindings of the [KeyMap]
+const DEFAULT_BINDINGS: [(Key, Action); 60] = {
+    use Action::*;
+    use KeyCode::Char;
+    [
+        (Key::plain(Char('q')), Abort),
+        (Key::plain(KeyCode::Esc), Abort),
+        (Key::plain(KeyCode::Enter), Accept),
+        (Key::plain(Char('j')), Down),
+        (Key::plain(KeyCode::Down), Down),
+        (Key::plain(Char('k')), Up),
+        (Key::plain(KeyCode::Up), Up),
+        (Key::plain(Char('h')), Left),
+        (Key::plain(KeyCode::Left), Left),
+        (Key::plain(Char('l')), Right),
+        (Key::plain(KeyCode::Right), Right),
+        (Key::plain(Char('g')), Top),
+        (Key::plain(Char('G')), Bottom),
+        (Key::ctrl('d'), HalfPageDown),
+        (Key::ctrl('u'), HalfPageUp),
+        (Key::plain(KeyCode::PageDown), PageDown),
+        (Key::plain(KeyCode::PageUp), PageUp),
+        (Key::plain(KeyCode::Tab), NextPane),
+        (Key::plain(KeyCode::BackTab), PreviousPane),
+        (Key::plain(Char('/')), Filter),
+        (Key::plain(Char('S')), Summary),
+        (Key::plain(Char('r')), History),
+        (Key::plain(Char('s')), Sort),
+        (Key::plain(Char('c')), ToggleCompare),
+        (Key::plain(Char('C')), OpenCompare),
+        (Key::plain(Char('y')), CopyInfo),
+        (Key::plain(Char('Y')), CopyName),
+        (Key::plain(Char('J')), ScrollDetailsDown),
+        (Key::plain(Char('K')), ScrollDetailsUp),
+        (Key::plain(Char('H')), ShrinkLeft),
+        (Key::plain(Char('L')), GrowLeft),
+        (Key::plain(Char('=')), ResetDivider),
+        (Key::plain(Char('x')), ClearPriorities),
+        (Key::plain(Char(' ')), Grab),
+        (Key::plain(Char('R')), Refresh),
+        (Key::plain(Char('P')), PruneMissing),
+        (Key::plain(Char('w')), Save),
+        (Key::plain(Char('t')), Tag),
+        (Key::plain(Char('a')), Nickname),
+        (Key::plain(Char('n')), Note),
+        (Key::plain(Char('f')), Jump),
+        (Key::plain(Char('b')), Benchmark),
+        (Key::plain(Char('d')), Hide),
+        (Key::plain(Char('D')), Hidden),
+        (Key::plain(KeyCode::F(1)), View(1)),
+        (Key::plain(KeyCode::F(2)), View(2)),
+        (Key::plain(KeyCode::F(3)), View(3)),
+        (Key::plain(KeyCode::F(4)), View(4)),
+        (Key::plain(Char('v')), NextView),
+        (Key::plain(Char('?')), Help),
+        (Key::plain(Char('0')), Priority(0)),
+        (Key::plain(Char('1')), Priority(1)),
+        (Key::plain(Char('2')), Priority(2)),
+        (Key::plain(Char('3')), Priority(3)),
+        (Key::plain(Char('4')), Priority(4)),
+        (Key::plain(Char('5')), Priority(5)),
+        (Key::plain(Char('6')), Priority(6)),
+        (Key::plain(Char('7')), Priority(7)),
+        (Key::plain(Char('8')), Priority(8)),
+        (Key::plain(Char('9')), Priority(9)),
+    ]
+};
+
+/// Maps keys to the [Action] they trigger
+#[derive(Clone, Debug)]
+pub struct KeyMap {
+    /// Action of every bound key
+    bindings: HashMap<Key, Action>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            bindings: DEFAULT_BINDINGS.into_iter().collect(),
+        }
+    }
+}
+
+impl KeyMap {
+    /// Built-in bindings changed by the config
+    ///
+    /// `keys` lets a key act as another built-in key, eg. `n = "j"`, while `bindings` binds keys
+    /// to actions directly, eg. `"C-n" = "down"`. [Config::load](crate::config::Config::load)
+    /// only accepts one of them, here bindings take precedence.
+    pub fn new(keys: &BTreeMap<char, char>, bindings: &HashMap<Key, Action>) -> Self {
+        let defaults = Self::default();
+        let mut map = Self::default();
+        for (&key, &target) in keys {
+            if let Some(action) = defaults.action(Key::plain(KeyCode::Char(target))) {
+                map.bindings.insert(Key::plain(KeyCode::Char(key)), action);
+            }
+        }
+        map.bindings.extend(bindings);
+        map
+    }
+
+    /// Every action which is bound to a key together with its keys, in the order of the
+    /// action names followed by the priorities and views
+    ///
+    /// Keys of an action are sorted such that single characters come first, eg. `j, Down`.
+    pub fn bound_actions(&self) -> Vec<(Action, Vec<Key>)> {
+        ACTION_NAMES
+            .iter()
+            .map(|(action, _)| *action)
+            .chain((0..=9).map(Action::Priority))
+            .chain((1..=4).map(Action::View))
+            .filter_map(|action| {
+                let mut keys: Vec<Key> = self
+                    .bindings
+                    .iter()
+                    .filter(|(_, bound)| **bound == action)
+                    .map(|(key, _)| *key)
+                    .collect();
+                keys.sort_by_key(|key| {
+                    let name = key.to_string();
+                    (name.chars().count(), name)
+                });
+                (!keys.is_empty()).then_some((action, keys))
+            })
+            .collect()
+    }
+
+    /// Action bound to the key, falling back to the key without Ctrl
+    pub fn action(&self, key: impl Into<Key>) -> Option<Action> {
+        let key = key.into();
+        self.bindings
+            .get(&key)
+            .or_else(|| self.bindings.get(&Key { ctrl: false, ..key }))
+            .copied()
+    }
+}
@@ -5,23 +5,135 @@
 //! Manage your opencl3 devices and platforms
 //!
 //! # Features
-//! - [serde] support for (de)serialization
-//! - [ratatui] provides a CLI display
+//! Library users only pay for the parts they enable, `default-features = false` together with
+//! a few of the features below keeps the dependency tree small. `full` enables all of them.
+//! - [opencl3] enumerates the devices of the current machine (default). Without it saved
+//!   [ClState] snapshots can still be loaded and displayed on machines without OpenCL.
+//! - [serde] support for (de)serialization of all types
+//! - `storage-json`, `storage-toml` and `storage-yaml` save and load values in the respective
+//!   `StorageFormat`. Each enables `storage` with the functions shared by all formats, such as
+//!   `save` and `load`, which is of no use without a format.
+//! - `tui` renders the setup with [ratatui] and [crossterm], `ratatui` is its former name
+//! - `cache` keeps the enumerated setup in an `EnumerationCache` until the drivers change
+//! - `bench` stores benchmark results and compares new runs against them
+//! - `diagnostics` compiles test kernels and runs the built-in benchmarks on devices
+//! - [tokio] async variants of enumeration and storage
+//! - `tracing` emits spans and events for enumeration, storage and selection
+//! - `clipboard` lets the binary copy device information to the system clipboard
+//! - `vulkan-interop` matches OpenCL devices to Vulkan physical devices by their UUID
+//! - `sysinfo` queries the current utilization and memory usage of NVIDIA and AMD GPUs and
+//!   describes the host processor of CPU devices
+//! - `server` serves the devices of a node over HTTP, eg. for cluster inventories
+//! - `global` provides a process-wide `Selector` through `current()`, similar to a global logger
+//! - `pick` provides `pick_device()` which chooses a device with the default pipeline in a
+//!   single call
+//! - `cli` builds the `opencl3-select` binary (default)
 
+#[cfg(all(
+    feature = "storage",
+    not(any(
+        feature = "storage-json",
+        feature = "storage-toml",
+        feature = "storage-yaml"
+    ))
+))]
+compile_error!("storage requires a format, enable storage-json, storage-toml or storage-yaml");
+
+#[cfg(feature = "bench")]
+mod benchmark;
+#[cfg(feature = "cache")]
+mod cache;
+mod capabilities;
 mod clinfo;
-#[cfg(feature = "ratatui")]
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+#[cfg(feature = "serde")]
+mod diff;
+#[cfg(feature = "tui")]
 mod display;
+#[cfg(all(test, feature = "tui"))]
+mod display_tests;
 mod error;
+#[cfg(feature = "storage")]
+mod export;
+#[cfg(test)]
+mod feature_matrix;
+mod features;
+#[cfg(feature = "global")]
+mod global;
+mod groups;
+mod host;
+mod id;
+mod images;
+mod info;
+#[cfg(feature = "pick")]
+mod pick;
 mod priority;
-#[cfg(feature = "serde")]
+mod profile;
+mod report;
+mod select;
+mod selector;
+#[cfg(feature = "server")]
+mod server;
+mod snippet;
+mod spec;
+#[cfg(feature = "storage")]
 mod storage;
+mod summary;
+mod units;
+mod usage;
+#[cfg(feature = "vulkan-interop")]
+mod vulkan;
 
+#[cfg(feature = "bench")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "bench")))]
+pub use benchmark::*;
+#[cfg(feature = "cache")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "cache")))]
+pub use cache::*;
+pub use capabilities::*;
 pub use clinfo::*;
-#[cfg(feature = "ratatui")]
-#[cfg_attr(doc_cfg, doc(cfg(feature = "ratatui")))]
+#[cfg(feature = "diagnostics")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "diagnostics")))]
+pub use diagnostics::*;
+#[cfg(feature = "serde")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+pub use diff::*;
+#[cfg(feature = "tui")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "tui")))]
 pub use display::*;
 pub use error::*;
+#[cfg(feature = "storage")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "storage")))]
+pub use export::*;
+pub use features::*;
+#[cfg(feature = "global")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "global")))]
+pub use global::*;
+pub use groups::*;
+pub use host::*;
+pub use id::*;
+pub use images::*;
+pub use info::*;
+#[cfg(feature = "pick")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "pick")))]
+pub use pick::*;
 pub use priority::*;
-#[cfg(feature = "serde")]
-#[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+pub use profile::*;
+pub use report::*;
+pub use select::*;
+pub use selector::*;
+#[cfg(feature = "server")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "server")))]
+pub use server::*;
+pub use snippet::*;
+pub use spec::*;
+#[cfg(feature = "storage")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "storage")))]
 pub use storage::*;
+pub use summary::*;
+pub use units::*;
+pub use usage::*;
+#[cfg(feature = "vulkan-interop")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "vulkan-interop")))]
+pub use vulkan::*;
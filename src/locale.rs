@@ -0,0 +1,210 @@
+//! Translations of the texts shown by the TUI
+
+use serde::{Deserialize, Serialize};
+
+/// Language of the titles, key hints and guide of the TUI
+///
+/// Messages are looked up by their English text, which is shown if a translation is missing.
+/// Placeholders such as `{name}` are filled in by [Locale::format].
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// English, the language the messages are written in
+    #[default]
+    En,
+    /// German
+    De,
+}
+
+impl Locale {
+    /// Translation of the English message
+    pub fn tr(&self, message: &'static str) -> &'static str {
+        let catalog: &[(&str, &str)] = match self {
+            Locale::En => return message,
+            Locale::De => &GERMAN,
+        };
+        catalog
+            .iter()
+            .find(|(english, _)| *english == message)
+            .map_or(message, |(_, translation)| translation)
+    }
+
+    /// Translation of the English message with its placeholders replaced by the arguments
+    pub fn format(&self, message: &'static str, arguments: &[(&str, &str)]) -> String {
+        arguments
+            .iter()
+            .fold(self.tr(message).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{name}}}"), value)
+            })
+    }
+}
+
+/// Catalog of [Locale::De]
+const GERMAN: [(&str, &str); 99] = [
+    // Titles
+    ("Devices", "Geräte"),
+    ("Platforms", "Plattformen"),
+    ("Priorities", "Prioritäten"),
+    ("Benchmarks", "Benchmarks"),
+    ("Diagnostics", "Diagnose"),
+    ("Details", "Details"),
+    ("Compare", "Vergleich"),
+    ("Hidden", "Ausgeblendet"),
+    ("History", "Verlauf"),
+    ("Summary", "Übersicht"),
+    ("Help", "Hilfe"),
+    ("Confirm", "Bestätigen"),
+    ("Welcome", "Willkommen"),
+    (
+        "{title} ({selected}/{count} selected)",
+        "{title} ({selected}/{count} ausgewählt)",
+    ),
+    // Confirmations
+    ("Clear the priority list?", "Prioritätenliste leeren?"),
+    (
+        "Replace the priority list by this selection?",
+        "Prioritätenliste durch diese Auswahl ersetzen?",
+    ),
+    (
+        "Overwrite the existing output file?",
+        "Vorhandene Ausgabedatei überschreiben?",
+    ),
+    // Footer
+    ("Filter", "Filter"),
+    ("Tag", "Gruppe"),
+    ("Nickname", "Spitzname"),
+    ("Note", "Notiz"),
+    ("Jump to", "Springe zu"),
+    ("Sort", "Sortierung"),
+    ("{count} selected", "{count} ausgewählt"),
+    ("abort", "abbrechen"),
+    ("accept", "übernehmen"),
+    ("apply", "anwenden"),
+    ("benchmark", "Benchmark"),
+    ("cancel", "abbrechen"),
+    ("choose manually", "selbst wählen"),
+    ("clear", "leeren"),
+    ("clear priorities", "Prioritäten leeren"),
+    ("close", "schließen"),
+    ("confirm", "bestätigen"),
+    ("copy info/name", "Info/Name kopieren"),
+    ("devices", "Geräte"),
+    ("drop", "ablegen"),
+    ("filter", "filtern"),
+    ("filter devices", "Geräte filtern"),
+    ("grab", "greifen"),
+    ("group name", "Gruppenname"),
+    ("half page", "halbe Seite"),
+    ("help", "Hilfe"),
+    ("hide/hidden", "ausblenden/ausgeblendet"),
+    ("history", "Verlauf"),
+    ("jump to entry", "zum Eintrag springen"),
+    ("jump to letter", "zu Buchstabe springen"),
+    ("mark/compare", "markieren/vergleichen"),
+    ("move", "bewegen"),
+    ("move entry", "Eintrag verschieben"),
+    ("next", "weiter"),
+    ("next pane", "nächster Bereich"),
+    ("nickname", "Spitzname"),
+    ("note", "Notiz"),
+    ("page", "Seite"),
+    ("platforms", "Plattformen"),
+    ("prune missing", "Fehlende entfernen"),
+    ("refresh", "aktualisieren"),
+    ("resize/reset", "Größe ändern/zurücksetzen"),
+    ("run benchmarks", "Benchmarks starten"),
+    ("save", "speichern"),
+    ("save, empty removes", "speichern, leer entfernt"),
+    ("scroll", "blättern"),
+    ("scroll details", "Details blättern"),
+    ("set platform priority", "Plattformpriorität setzen"),
+    ("set priority", "Priorität setzen"),
+    ("skip guide", "Anleitung überspringen"),
+    ("sort", "sortieren"),
+    ("summary", "Übersicht"),
+    ("switch view", "Ansicht wechseln"),
+    ("tag", "Gruppe"),
+    ("tag/untag", "hinzufügen/entfernen"),
+    ("top/bottom", "Anfang/Ende"),
+    ("unhide", "einblenden"),
+    ("up/down", "hoch/runter"),
+    // Empty lists
+    (
+        "No OpenCL platforms found",
+        "Keine OpenCL-Plattformen gefunden",
+    ),
+    (
+        "This platform reports no devices",
+        "Diese Plattform meldet keine Geräte",
+    ),
+    (
+        "Devices may be hidden by the config or the smoke test",
+        "Geräte können durch die Konfiguration oder den Smoke-Test ausgeblendet sein",
+    ),
+    (
+        "No device matches the filter",
+        "Kein Gerät passt zum Filter",
+    ),
+    ("Press / and Esc to clear it", "/ und Esc leeren ihn"),
+    // Guide
+    ("Welcome (1/3)", "Willkommen (1/3)"),
+    ("Benchmarks (2/3)", "Benchmarks (2/3)"),
+    ("Recommendation (3/3)", "Empfehlung (3/3)"),
+    (
+        "No device was selected on this machine yet. This guide recommends one for the setup \
+         below.",
+        "Auf diesem Rechner wurde noch kein Gerät ausgewählt. Diese Anleitung empfiehlt eines \
+         für die folgende Einrichtung.",
+    ),
+    (
+        "Quick benchmarks take a few seconds per device and let the recommendation prefer the \
+         fastest one.",
+        "Kurze Benchmarks dauern einige Sekunden pro Gerät und lassen die Empfehlung das \
+         schnellste bevorzugen.",
+    ),
+    (
+        "Press b to run them or Enter to skip them.",
+        "b startet sie, Enter überspringt sie.",
+    ),
+    ("Recommended: ", "Empfohlen: "),
+    (" on {platform}", " auf {platform}"),
+    ("Chosen because {reason}.", "Gewählt, weil {reason}."),
+    (
+        "it was the fastest in the saxpy benchmark with {millis} ms",
+        "es im saxpy-Benchmark mit {millis} ms am schnellsten war",
+    ),
+    (
+        "it is a {device_type} with {memory} of memory",
+        "es ein {device_type} mit {memory} Speicher ist",
+    ),
+    (
+        "Save it as your selection? It is used by auto and preselected from now on.",
+        "Als Auswahl speichern? Sie wird ab jetzt von auto genutzt und vorausgewählt.",
+    ),
+    (
+        "No device was found, press n to look at the platforms.",
+        "Kein Gerät gefunden, n zeigt die Plattformen.",
+    ),
+    (
+        "S shows the summary, b benchmarks and Enter accepts the selection",
+        "S zeigt die Übersicht, b Benchmarks und Enter übernimmt die Auswahl",
+    ),
+    // Views
+    (
+        "No benchmarks were run yet, press b to benchmark the devices with a priority",
+        "Noch keine Benchmarks, b misst die Geräte mit einer Priorität",
+    ),
+    ("Enumeration timings", "Dauer der Abfrage"),
+    (
+        "none recorded, the setup was not enumerated by this run",
+        "keine erfasst, die Einrichtung wurde in diesem Lauf nicht abgefragt",
+    ),
+    (
+        "No platform took longer than {slow}",
+        "Keine Plattform brauchte länger als {slow}",
+    ),
+    (
+        "{count} platform(s) took longer than {slow} and slow down every OpenCL application",
+        "{count} Plattform(en) brauchten länger als {slow} und bremsen jede OpenCL-Anwendung",
+    ),
+];
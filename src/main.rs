@@ -1,228 +1,3040 @@
-mod clinfo;
-mod display;
-mod error;
-mod storage;
+mod config;
+mod keymap;
+mod locale;
+#[cfg(unix)]
+mod serve;
+#[cfg(test)]
+mod ui_tests;
 
-use clinfo::DeviceInfo;
-use error::Result;
-use opencl3_select::UniquePriorityList;
+use config::{ColorChoice, Config, SortOrder, Theme, UiState};
+use keymap::{Action, KeyMap};
+use locale::Locale;
+use opencl3_select::{
+    ascii_fallback, compile_test, format_frequency, normalize_key_event, spawn_benchmarks_with,
+    AutoSelect, Benchmark, BenchmarkDelta, BenchmarkError, BenchmarkEvent, BenchmarkHistory,
+    BenchmarkProgress, Bytes, ClSelectError, ClState, ClinfoError, CsvExporter, DeviceGroups,
+    DeviceId, DeviceInfo, DeviceNicknames, DeviceNotes, DeviceScorer, DeviceSpec, DeviceUsage,
+    DisplayError, EnumerationCache, ExporterRegistry, HiddenDevices, ImageFormat, Info,
+    KernelBenchmark, NameVersion, PlatformInfo, Priority, Profile, ReportFormat, Requirements,
+    Result, SelectConfig, SelectionError, SelectionHistory, SelectionSpec, SnippetFormat,
+    StorageError, StorageFormat, Summary, TerminalGuard, UniquePriorityList, SETUP_HINTS,
+    SMOKE_TEST_KERNEL,
+};
 
-use std::{io, io::stdout};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use std::{io, io::stdout, io::IsTerminal};
 
-use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-    ExecutableCommand,
-};
-use ratatui::{prelude::*, style::palette::tailwind, style::Stylize, widgets::*};
+use clap::{CommandFactory, Parser, Subcommand};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use ratatui::{prelude::*, style::Stylize, widgets::*};
+
+/// Part of the screen which currently receives navigation input
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Pane {
+    /// Level of the platform → device → sub-device hierarchy at the given depth
+    Hierarchy(usize),
+    Priorities,
+}
+
+impl Pane {
+    /// List of all platforms
+    const PLATFORMS: Pane = Pane::Hierarchy(0);
+    /// Devices of the highlighted platform
+    const DEVICES: Pane = Pane::Hierarchy(1);
+}
+
+/// Steps of the guide which is shown if no device was selected on this machine before
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum WizardStep {
+    /// Overview of the OpenCL setup
+    Summary,
+    /// Optional quick benchmarks of all devices
+    Benchmarks,
+    /// Recommended device which is saved once confirmed
+    Recommend,
+}
+
+/// Full-screen views which are switched between with the tab bar
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum View {
+    /// Platforms and devices above the priorities and the details of the highlighted device
+    Devices,
+    /// Devices with a priority next to the details of the highlighted one
+    Priorities,
+    /// Progress and results of the most recent benchmark run
+    Benchmarks,
+    /// Enumeration timings and errors of the platforms, see the `doctor` subcommand
+    Diagnostics,
+}
+
+impl View {
+    /// All views in the order of the tab bar
+    const ALL: [View; 4] = [
+        View::Devices,
+        View::Priorities,
+        View::Benchmarks,
+        View::Diagnostics,
+    ];
+
+    /// Label in the tab bar
+    fn title(&self) -> &'static str {
+        match self {
+            View::Devices => "Devices",
+            View::Priorities => "Priorities",
+            View::Benchmarks => "Benchmarks",
+            View::Diagnostics => "Diagnostics",
+        }
+    }
+}
+
+/// Action which discards data and therefore has to be confirmed by the user
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Confirm {
+    /// Remove all devices from the priority list
+    ClearPriorities,
+    /// Replace the priority list by the highlighted entry of the history
+    ApplyHistory,
+    /// Accept the selection and overwrite the existing output file
+    Overwrite,
+}
+
+impl Confirm {
+    /// Question shown in the confirmation popup
+    fn question(&self) -> &'static str {
+        match self {
+            Confirm::ClearPriorities => "Clear the priority list?",
+            Confirm::ApplyHistory => "Replace the priority list by this selection?",
+            Confirm::Overwrite => "Overwrite the existing output file?",
+        }
+    }
+}
+
+/// State of the devices which is shown next to the entries of a [Level]
+struct Marks<'a> {
+    /// Devices marked for comparison
+    compare: &'a [(usize, usize)],
+    /// Devices which have been given a priority
+    priorities: &'a UniquePriorityList<(usize, usize)>,
+    /// Nicknames shown next to the names of the devices
+    nicknames: &'a DeviceNicknames,
+}
+
+impl Marks<'_> {
+    /// Rank of the device in the priority list starting at `1` for the highest priority
+    fn rank(&self, device: &(usize, usize)) -> Option<usize> {
+        match self.priorities.position_of(device) {
+            Some(Priority::Selected(n)) => Some(n + 1),
+            _ => None,
+        }
+    }
+}
+
+/// One level of the navigable hierarchy, eg. all platforms or the devices of one platform
+///
+/// Entries are addressed by their position in the displayed list.
+trait Level {
+    /// Title of the list
+    fn title(&self) -> &'static str;
+    /// Number of displayed entries
+    fn len(&self) -> usize;
+    /// Height of a single entry in lines
+    fn item_height(&self) -> u16;
+    fn state(&self) -> &ListState;
+    fn state_mut(&mut self) -> &mut ListState;
+    /// Name of the entry as shown in the breadcrumb
+    fn name(&self, i: usize) -> Option<&str>;
+    /// Renders all displayed entries together with their marks
+    fn list_items(&self, theme: &Theme, marks: &Marks) -> Vec<ListItem<'_>>;
+
+    /// Explanation shown instead of the entries if there are none
+    fn empty_message(&self, locale: Locale) -> Vec<Line<'static>>;
+
+    /// Title of the pane including the number of entries, eg. `Platforms (3)`
+    fn heading(&self, _marks: &Marks, locale: Locale) -> String {
+        format!("{} ({})", locale.tr(self.title()), self.len())
+    }
+
+    /// Level nested below the entry, eg. the devices of a platform
+    fn child(&self, _i: usize) -> Option<&dyn Level> {
+        None
+    }
+
+    /// See [child](Level::child)
+    fn child_mut(&mut self, _i: usize) -> Option<&mut dyn Level> {
+        None
+    }
+
+    /// Highlights the next entry, wrapping around at the end
+    fn next(&mut self) {
+        let len = self.len();
+        if len > 0 {
+            let i = self.state().selected().map_or(0, |i| (i + 1) % len);
+            self.state_mut().select(Some(i));
+        }
+    }
+
+    /// Highlights the next entry after the highlighted one whose name starts with the letter,
+    /// ignoring case and wrapping around at the end
+    ///
+    /// Returns `false` if no entry starts with the letter.
+    fn jump(&mut self, letter: char) -> bool {
+        let len = self.len();
+        let start = self.state().selected().map_or(0, |i| i + 1);
+        let letter = letter.to_lowercase().to_string();
+        let found = (start..start + len).map(|i| i % len).find(|&i| {
+            self.name(i)
+                .is_some_and(|name| name.to_lowercase().starts_with(&letter))
+        });
+        if let Some(i) = found {
+            self.state_mut().select(Some(i));
+        }
+        found.is_some()
+    }
+
+    /// Highlights the previous entry, wrapping around at the start
+    fn previous(&mut self) {
+        let len = self.len();
+        if len > 0 {
+            let i = match self.state().selected() {
+                Some(0) | None => len - 1,
+                Some(i) => i - 1,
+            };
+            self.state_mut().select(Some(i));
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PlatformItem {
+    info: PlatformInfo,
+    devices: DeviceList,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct DeviceItem {
+    info: DeviceInfo,
+    /// Load of the device when the list was built, see [opencl3_select::DeviceUsage]
+    usage: Option<DeviceUsage>,
+    /// Whether the device was highlighted or had a priority but disappeared in a refresh
+    missing: bool,
+}
+
+#[derive(Clone, Debug)]
+struct PlatformList {
+    state: ListState,
+    items: Vec<PlatformItem>,
+    /// Preferred order of platforms which provide the same device
+    priorities: UniquePriorityList<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct DeviceList {
+    /// Index of the platform the devices belong to
+    platform: usize,
+    state: ListState,
+    items: Vec<DeviceItem>,
+    /// Indices of items which match the current filter
+    visible: Vec<usize>,
+}
+
+/// Prefers devices by the throughput they reached in [Benchmark::Saxpy]
+struct SaxpyScorer<'a>(&'a BenchmarkRun);
+
+impl DeviceScorer for SaxpyScorer<'_> {
+    fn name(&self) -> &str {
+        "saxpy"
+    }
+
+    fn score(&self, device: &DeviceInfo) -> Option<f64> {
+        Some(
+            self.0
+                .saxpy_millis(device)
+                .map_or(0.0, |millis| 1.0 / millis),
+        )
+    }
+}
+
+/// Benchmarks started from the TUI and the progress of every device
+#[derive(Debug)]
+struct BenchmarkRun {
+    /// Benchmarked devices in the order their events refer to them
+    devices: Vec<(DeviceInfo, BenchmarkProgress)>,
+    /// Events of the running benchmarks, `None` once all are completed
+    events: Option<mpsc::Receiver<BenchmarkEvent>>,
+}
+
+impl BenchmarkRun {
+    /// Runtime of [Benchmark::Saxpy] on the device if it finished
+    fn saxpy_millis(&self, device: &DeviceInfo) -> Option<f64> {
+        let (_, progress) = self.devices.iter().find(|(other, _)| other == device)?;
+        progress
+            .results
+            .iter()
+            .find(|result| result.name == Benchmark::Saxpy.name())
+            .map(|result| result.value)
+    }
+}
+
+#[derive(Debug)]
+struct App {
+    pane: Pane,
+    items: PlatformList,
+    divider_percentage: u16,
+    /// Configured position of the divider which [ResetDivider](Action::ResetDivider) restores
+    default_divider: u16,
+    priority_list: UniquePriorityList<(usize, usize)>,
+    priority_state: ListState,
+    filter: String,
+    filter_mode: bool,
+    summary: Summary,
+    /// Scroll offset of the summary screen if it is shown
+    summary_scroll: Option<u16>,
+    /// Scroll offset of the overlay listing the key bindings if it is shown
+    help_scroll: Option<u16>,
+    /// Notifications shown above the status bar until they expire
+    toasts: Vec<Toast>,
+    /// Config to enumerate the platforms and devices again with, see [App::refresh]
+    reload: Option<SelectConfig>,
+    /// Drop devices failing the smoke test when refreshing
+    smoke_test: bool,
+    /// Index of the first property shown in the details table
+    details_scroll: usize,
+    /// Devices marked for comparison, at most two
+    compare: Vec<(usize, usize)>,
+    /// Whether the marked devices are shown side-by-side
+    show_compare: bool,
+    /// Height in lines of the platform, device and priority list as last rendered
+    list_heights: [u16; 3],
+    /// Previously accepted selections
+    history: SelectionHistory,
+    /// Selected entry of the history if it is shown
+    history_state: Option<ListState>,
+    sort: SortOrder,
+    theme: Theme,
+    /// Language of the titles, key hints and guide
+    locale: Locale,
+    keymap: KeyMap,
+    /// File the accepted selection is saved to
+    output: Option<PathBuf>,
+    /// Perform destructive actions without asking for confirmation
+    assume_yes: bool,
+    /// Action which waits for confirmation
+    confirm: Option<Confirm>,
+    /// User-defined groups of devices
+    groups: DeviceGroups,
+    /// File the groups are saved to whenever they change
+    groups_path: Option<PathBuf>,
+    /// Name of the group being typed to tag the highlighted device with
+    tag_input: Option<String>,
+    /// Nicknames the user gave to devices
+    nicknames: DeviceNicknames,
+    /// File the nicknames are saved to whenever they change
+    nicknames_path: Option<PathBuf>,
+    /// Nickname being typed for the highlighted device
+    nickname_input: Option<String>,
+    /// Notes the user attached to devices
+    notes: DeviceNotes,
+    /// File the notes are saved to whenever they change
+    notes_path: Option<PathBuf>,
+    /// Note being edited for the highlighted device, shown in a popup
+    note_input: Option<String>,
+    /// Whether the next typed letter selects the entry to jump to, see [Level::jump]
+    jump_pending: bool,
+    /// Whether the highlighted priority is grabbed, such that up and down move it
+    held: bool,
+    /// Whether the terminal renders symbols beyond ASCII, see [ascii_fallback]
+    unicode: bool,
+    /// Platforms and devices of the last enumeration including the hidden devices
+    cl_state: ClState,
+    /// Devices which are left out of the lists
+    hidden: HiddenDevices,
+    /// File the hidden devices are saved to whenever they change
+    hidden_path: Option<PathBuf>,
+    /// Highlighted hidden device if the hidden devices are shown
+    hidden_state: Option<ListState>,
+    /// Current step of the first-run guide if it is shown
+    wizard: Option<WizardStep>,
+    /// Most recent benchmark run, see [App::start_benchmarks]
+    benchmarks: Option<BenchmarkRun>,
+    /// View selected in the tab bar
+    view: View,
+    /// Scroll offset of the benchmarks and diagnostics views
+    view_scroll: u16,
+    /// File the results of completed benchmark runs are added to
+    benchmarks_path: Option<PathBuf>,
+    /// Harness files of the kernels which are benchmarked in addition to the built-in ones
+    benchmark_kernels: Vec<PathBuf>,
+}
+
+/// How long a [Toast] is shown
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+
+/// How long a [Toast] reporting an error is shown
+const ERROR_TOAST_DURATION: Duration = Duration::from_secs(6);
+
+/// Relative change beyond which a benchmark result is reported as regression
+const REGRESSION_TOLERANCE: f64 = 0.05;
+
+/// Enumeration time in milliseconds above which a platform or device is flagged as slow
+const SLOW_ENUMERATION_MS: u64 = 100;
+
+/// Interval in which the App wakes up without input to dismiss expired toasts
+const TICK: Duration = Duration::from_millis(250);
+
+/// Positions in percent the divider between the panes can take, such that no pane vanishes
+const DIVIDER_RANGE: std::ops::RangeInclusive<u16> = 10..=90;
+
+/// The position of the divider moved into [DIVIDER_RANGE]
+fn clamp_divider(percentage: u16) -> u16 {
+    percentage.clamp(*DIVIDER_RANGE.start(), *DIVIDER_RANGE.end())
+}
+
+/// Short notification shown in the corner of the App until it expires
+#[derive(Clone, Debug)]
+struct Toast {
+    text: String,
+    /// Whether the toast reports a failure
+    error: bool,
+    /// Point in time at which the toast is dismissed
+    until: Instant,
+}
+
+/// Exit codes of the binary which scripts can rely on
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Exit {
+    /// A selection was made or the command succeeded
+    Success = 0,
+    /// The user aborted the selection
+    Aborted = 1,
+    /// No OpenCL platforms or no matching device were found
+    NotFound = 2,
+    /// Reading or writing files or the terminal failed
+    Io = 3,
+    /// Any other error
+    Other = 4,
+}
+
+/// Documentation of [Exit] shown in the help message
+const EXIT_CODES: &str = "Exit codes:
+  0  a selection was made or the command succeeded
+  1  the user aborted the selection
+  2  no OpenCL platforms or no matching device were found
+  3  reading or writing files or the terminal failed
+  4  any other error
+
+Errors are written to stderr as a single line of JSON.";
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+impl Exit {
+    /// Classifies an error by the exit code and a short machine-readable kind
+    fn from_error(error: &ClSelectError) -> (Self, &'static str) {
+        match error {
+            ClSelectError::Clinfo(error) => match error {
+                ClinfoError::NoPlatforms => (Self::NotFound, "no_platforms"),
+                ClinfoError::OpenCL { .. } => (Self::Other, "opencl"),
+                ClinfoError::EnumerationCancelled(_) => (Self::Other, "enumeration_cancelled"),
+            },
+            ClSelectError::Selection(error) => match error {
+                SelectionError::NoMatchingDevice => (Self::NotFound, "no_matching_device"),
+                SelectionError::DeviceNotFound(_) => (Self::Other, "device_not_found"),
+                SelectionError::AmbiguousDevice { .. } => (Self::Other, "ambiguous_device"),
+                SelectionError::Regex(_) => (Self::Other, "regex"),
+            },
+            ClSelectError::Benchmark(error) => match error {
+                BenchmarkError::InvalidKernel { .. } => (Self::Other, "invalid_kernel"),
+            },
+            ClSelectError::Display(error) => match error {
+                DisplayError::Terminal(_) => (Self::Io, "display"),
+                DisplayError::Setup(_) => (Self::Io, "terminal_setup"),
+                DisplayError::Restore(_) => (Self::Io, "terminal_restore"),
+            },
+            ClSelectError::Storage(error) => match error {
+                StorageError::Io(_) | StorageError::UnknownFormat(_) => (Self::Io, "storage"),
+                StorageError::Corrupt(_) => (Self::Io, "corrupt"),
+                StorageError::CacheStale(_) => (Self::Io, "cache_stale"),
+                StorageError::FixtureParse { .. } => (Self::Io, "fixture_parse"),
+                StorageError::UnknownColumn(_) => (Self::Other, "unknown_column"),
+                StorageError::Report(_) => (Self::Io, "report"),
+                StorageError::Deserialize(_)
+                | StorageError::Json(_)
+                | StorageError::Yaml(_)
+                | StorageError::TomlSerialize(_)
+                | StorageError::TomlDeserialize(_) => (Self::Io, "serialization"),
+            },
+            #[cfg(feature = "tokio")]
+            ClSelectError::Join(_) => (Self::Other, "join"),
+        }
+    }
+}
+
+/// Describes the error including its underlying causes
+fn error_message(error: &ClSelectError) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message += &format!(": {cause}");
+        source = cause.source();
+    }
+    message
+}
+
+/// Writes an error as one line of JSON to stderr and returns the matching exit code
+fn report_error(exit: Exit, kind: &str, message: String) -> ExitCode {
+    let error = serde_json::json!({
+        "exit_code": exit as u8,
+        "kind": kind,
+        "message": message,
+    });
+    eprintln!("{error}");
+    exit.into()
+}
+
+/// Select and prioritize OpenCL devices
+#[derive(Parser)]
+#[command(version, about, after_help = EXIT_CODES)]
+struct Cli {
+    /// Path of the config file [default: <config dir>/opencl3_select/opencl3_select.toml]
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Build a trivial kernel on every device and hide devices on which it fails
+    #[arg(long, global = true)]
+    smoke_test: bool,
+    /// When to use colors [default: auto, which respects NO_COLOR]
+    #[arg(long, global = true, value_enum)]
+    color: Option<ColorChoice>,
+    /// Also save the accepted selection to this file, the format is taken from its extension
+    ///
+    /// Saving to `.opencl3_select.toml` pins the selection for the current directory and its
+    /// subdirectories, it then takes precedence over the history. `.rs` and `.env` files receive
+    /// constants naming the first device to bake it into a build. Press w to save the selection
+    /// without leaving.
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Select the device whose name, vendor or version matches this regular expression instead
+    /// of opening the TUI, fails listing the candidates if several match
+    #[arg(long)]
+    device_regex: Option<String>,
+    /// Only consider devices of the platform whose name, vendor or version matches this regular
+    /// expression, see --device-regex
+    #[arg(long)]
+    platform_regex: Option<String>,
+    /// Select the device with this id instead of opening the TUI, see `list -vv` for the ids
+    #[arg(long, conflicts_with_all = ["device_regex", "platform_regex"])]
+    device_id: Option<DeviceId>,
+    /// Select the device with this name instead of opening the TUI, `name:<nickname>` selects
+    /// the device given this nickname in the TUI
+    #[arg(long, conflicts_with_all = ["device_regex", "platform_regex", "device_id"])]
+    device: Option<String>,
+    /// Do not ask for confirmation before discarding priorities or overwriting files
+    #[arg(long, short, global = true)]
+    yes: bool,
+    /// Pick devices by their number in a plain prompt instead of the TUI, used automatically if
+    /// the terminal does not support the TUI
+    #[arg(long)]
+    plain: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print all platforms and their devices
+    List {
+        /// Output format (json, toml, yaml, csv, markdown, html, tree) [default: taken from the
+        /// config, tree if -v is given]
+        #[arg(long)]
+        format: Option<ListFormat>,
+        /// Show more properties in the tree, -v adds versions and -vv key properties
+        #[arg(short, long, action = clap::ArgAction::Count)]
+        verbose: u8,
+        /// Comma-separated columns of the csv output [default: taken from the config]
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+        /// Only print the devices of this group, see the t key of the TUI
+        #[arg(long)]
+        group: Option<String>,
+    },
+    /// Print an overview of device types, vendors, versions and extensions
+    Summary {
+        /// Output format (json, toml, yaml) [default: plain text]
+        #[arg(long)]
+        format: Option<StorageFormat>,
+    },
+    /// Write a Markdown or HTML report of the OpenCL setup
+    Report {
+        /// Path of the report
+        output: PathBuf,
+        /// Report format (markdown, html) [default: taken from the extension of the output]
+        #[arg(long)]
+        format: Option<ReportFormat>,
+        /// Previously stored selection which is included in the report
+        #[arg(long)]
+        selection: Option<PathBuf>,
+        /// Write only the table of devices and their capabilities, eg. for a README
+        #[arg(long, conflicts_with = "selection")]
+        matrix: bool,
+    },
+    /// Print the name of the best device without user interaction, eg. `DEV=$(opencl3-select auto)`
+    ///
+    /// Devices are ranked by the preferred types, the weights of the profile, the project-local
+    /// `.opencl3_select.toml` or else the most recent selection and finally by their type and
+    /// memory.
+    Auto {
+        /// Extension, capability or OpenCL C feature the device has to support, eg. fp64 or
+        /// __opencl_c_generic_address_space (repeatable)
+        #[arg(long)]
+        require: Vec<String>,
+        /// Preferred device type, eg. gpu (repeatable, from most to least preferred)
+        #[arg(long)]
+        prefer: Vec<String>,
+        /// Size in bytes of the largest buffer the device has to allocate at once
+        #[arg(long)]
+        min_alloc: Option<u64>,
+        /// Percentage by which --min-alloc is raised as safety margin, eg. 10
+        #[arg(long, default_value_t = 0, requires = "min_alloc")]
+        margin: u32,
+        /// Image format the device has to support, eg. RGBA/FLOAT (repeatable, needs the deep
+        /// query level)
+        #[arg(long)]
+        image_format: Vec<ImageFormat>,
+        /// Built-in requirements and weights (compute, graphics-interop, embedded)
+        #[arg(long)]
+        profile: Option<Profile>,
+        /// Print the full device information in this format (json, toml, yaml)
+        #[arg(long)]
+        format: Option<StorageFormat>,
+        /// Print to stderr which steps led to the device, eg. eliminated requirements
+        #[arg(long)]
+        explain: bool,
+    },
+    /// Resolve a selection spec without user interaction, eg. when provisioning many hosts
+    ///
+    /// The spec contains the allow and deny lists of the config, the `require` and `prefer` lists
+    /// of `auto`, the number of devices to select, the output file and environment variables to
+    /// export. The exports are printed as shell commands if there are any, the selection
+    /// otherwise.
+    Apply {
+        /// Path of the spec, the format is taken from its extension
+        spec: PathBuf,
+        /// Print to stderr which steps led to the best device, eg. eliminated requirements
+        #[arg(long)]
+        explain: bool,
+    },
+    /// Print the devices added, removed or changed between two snapshots saved with `list`
+    ///
+    /// Devices are matched by their UUID or else by their platform, vendor and name, such that
+    /// driver updates show up as changed properties. Nothing is printed if both snapshots
+    /// describe the same devices.
+    Diff {
+        /// Older snapshot, the format is taken from its extension
+        old: PathBuf,
+        /// Newer snapshot, the format is taken from its extension
+        new: PathBuf,
+        /// Output format (json, toml, yaml) [default: plain text]
+        #[arg(long)]
+        format: Option<StorageFormat>,
+    },
+    /// Benchmark all devices and rank them by every benchmark
+    ///
+    /// Kernels of the user are described by harness files giving the source, the kernel, its
+    /// arguments and the global size, eg.
+    /// `name = "stencil"`, `source = "stencil.cl"`, `kernel = "stencil_2d"`,
+    /// `global_size = [1024, 1024]`, `args = [{ buffer = 4194304 }, { float = 0.25 }]`.
+    Bench {
+        /// Harness file of a kernel to benchmark in addition to the built-in benchmarks
+        /// [default: taken from the config]
+        #[arg(long = "kernel")]
+        kernels: Vec<PathBuf>,
+    },
+    /// Diagnose the OpenCL setup and report slow platforms and devices
+    Doctor {
+        /// Enumeration time in milliseconds above which a platform or device is flagged
+        #[arg(long, default_value_t = SLOW_ENUMERATION_MS)]
+        slow_ms: u64,
+    },
+    /// Keep the OpenCL setup in memory and answer JSON queries on a unix domain socket
+    ///
+    /// Each line sent to the socket is one query, eg. `{"query": "list"}`,
+    /// `{"query": "auto", "require": ["fp64"]}` or `{"query": "resolve", "name": "RTX"}`.
+    #[cfg(unix)]
+    Serve {
+        /// Path of the socket [default: <runtime dir>/opencl3_select.sock]
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
+    /// Serve the OpenCL setup over HTTP for cluster inventories
+    ///
+    /// `GET /devices` returns all platforms and their devices, `GET /auto-select` the devices
+    /// fulfilling the query parameters, eg. `/auto-select?require=fp64&prefer=gpu&count=1`.
+    #[cfg(feature = "server")]
+    Http {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        listen: std::net::SocketAddr,
+    },
+    /// Print the saved selection without enumerating the devices
+    ///
+    /// The project-local selection takes precedence over the most recent one of the history.
+    /// Exits with 2 if nothing was selected yet.
+    Status {
+        /// Print only a compact label of the first device, eg. `gpu:RTX3060`, to embed it into a
+        /// shell prompt
+        #[arg(long)]
+        short: bool,
+    },
+    /// Print a completion script for the given shell, eg. `opencl3-select completions bash`
+    ///
+    /// Besides the subcommands and options, the script completes the names and nicknames of
+    /// the devices for --device. They are taken from the devices found by the last run, so
+    /// regenerate the script after installing new devices.
+    Completions {
+        /// Shell to generate completions for (bash, elvish, fish, powershell, zsh)
+        shell: clap_complete::Shell,
+    },
+}
+
+/// Output format of the `list` subcommand
+#[derive(Clone, Debug)]
+enum ListFormat {
+    /// Name of an exporter of the [ExporterRegistry]
+    Export(String),
+    /// Indented tree of platforms and devices, see [opencl3_select::format_tree]
+    Tree,
+}
+
+impl std::str::FromStr for ListFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let registry = ExporterRegistry::default();
+        match registry.get(s) {
+            _ if s.eq_ignore_ascii_case("tree") => Ok(Self::Tree),
+            Some(exporter) => Ok(Self::Export(exporter.name().to_string())),
+            None => Err(format!(
+                "unknown format \"{s}\", expected one of {}, tree",
+                registry.names().join(", ")
+            )),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // Help and version are printed to stdout and are no errors
+        Err(error) if !error.use_stderr() => {
+            let _ = error.print();
+            return Exit::Success.into();
+        }
+        Err(error) => return report_error(Exit::Other, "usage", error.to_string()),
+    };
+
+    match run(cli) {
+        Ok(exit) => exit.into(),
+        Err(error) => {
+            let (exit, kind) = Exit::from_error(&error);
+            report_error(exit, kind, error_message(&error))
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<Exit> {
+    // Completions do not depend on the OpenCL setup
+    if let Some(Command::Completions { shell }) = cli.command {
+        let devices = device_candidates();
+        let mut command = Cli::command().mut_arg("device", |arg| match devices.is_empty() {
+            true => arg,
+            false => arg.value_parser(clap::builder::PossibleValuesParser::new(devices)),
+        });
+        let name = command.get_name().to_string();
+        clap_complete::generate(shell, &mut command, name, &mut stdout());
+        return Ok(Exit::Success);
+    }
+
+    // Snapshots are compared without enumerating the current setup
+    if let Some(Command::Diff { old, new, format }) = &cli.command {
+        let old = opencl3_select::load_fixture(old)?;
+        let diff = old.diff(&opencl3_select::load_fixture(new)?);
+        match format {
+            Some(format) => println!("{}", format.encode(&diff)?),
+            None => print!("{diff}"),
+        }
+        return Ok(Exit::Success);
+    }
+
+    // Shell prompts ask for the status often, so it is read from the saved selection only
+    if let Some(Command::Status { short }) = cli.command {
+        let selection = opencl3_select::saved_selection()?;
+        let Some(first) = selection.first() else {
+            return Ok(Exit::NotFound);
+        };
+        match short {
+            true => println!("{}", first.short_label()),
+            false => {
+                for (i, device) in selection.iter().enumerate() {
+                    println!("{}. {device}", i + 1);
+                }
+            }
+        }
+        return Ok(Exit::Success);
+    }
+
+    let mut config = Config::load(cli.config.as_deref())?;
+    let color = cli.color.unwrap_or(config.color);
+    if !color.enabled(stdout().is_terminal()) {
+        config.theme = Theme::monochrome();
+    } else {
+        config.theme = config.theme.with_color_depth(config.color_depth.resolve());
+    }
+
+    // A spec brings its own allow and deny lists
+    let spec = match &cli.command {
+        Some(Command::Apply { spec, .. }) => Some(opencl3_select::load::<SelectionSpec>(spec)?),
+        _ => None,
+    };
+    let select = spec.as_ref().map_or(&config.select, |spec| &spec.select);
+    let mut cl_state = enumerate(select, cli.smoke_test)?;
+    // The TUI keeps the hidden devices such that they can be shown again without enumerating
+    let unfiltered = cl_state.clone();
+    let hidden = load_hidden()?;
+    hidden.apply(&mut cl_state);
+
+    match cli.command {
+        Some(Command::List {
+            format,
+            columns,
+            group,
+            verbose,
+        }) => {
+            if let Some(group) = group {
+                let groups = load_groups()?;
+                cl_state.retain_devices(|device| groups.contains(&group, device));
+            }
+            let default = match verbose {
+                0 => ListFormat::Export(config.format.to_string()),
+                _ => ListFormat::Tree,
+            };
+            match format.unwrap_or(default) {
+                ListFormat::Export(name) => {
+                    let mut registry = ExporterRegistry::default();
+                    registry.register(CsvExporter {
+                        columns: match columns.is_empty() {
+                            true => config.csv_columns,
+                            false => columns,
+                        },
+                    });
+                    let exporter = registry
+                        .get(&name)
+                        .expect("formats are parsed with the default registry");
+                    exporter.write(&cl_state, &mut stdout())?;
+                }
+                ListFormat::Tree => {
+                    let color = color.enabled(stdout().is_terminal());
+                    print!("{}", opencl3_select::format_tree(&cl_state, verbose, color));
+                }
+            }
+            Ok(Exit::Success)
+        }
+        Some(Command::Summary { format }) => {
+            let summary = cl_state.summary();
+            match format {
+                Some(format) => println!("{}", format.encode(&summary)?),
+                None => print!("{summary}"),
+            }
+            Ok(Exit::Success)
+        }
+        Some(Command::Report {
+            output,
+            format,
+            selection,
+            matrix,
+        }) => {
+            let format = format
+                .or_else(|| ReportFormat::from_path(&output))
+                .unwrap_or(ReportFormat::Markdown);
+            if matrix {
+                std::fs::write(output, cl_state.capability_matrix(format))
+                    .map_err(StorageError::Report)?;
+                return Ok(Exit::Success);
+            }
+            let selection = match selection {
+                Some(path) => opencl3_select::load::<UniquePriorityList<DeviceInfo>>(path)?
+                    .view_priority_list()
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+                None => vec![],
+            };
+            cl_state.write_report(output, format, &selection)?;
+            Ok(Exit::Success)
+        }
+        Some(Command::Auto {
+            require,
+            prefer,
+            min_alloc,
+            margin,
+            image_format,
+            profile,
+            format,
+            explain,
+        }) => {
+            let priorities = opencl3_select::saved_selection()?;
+            let devices = cl_state.get_all_devices();
+            let mut auto = AutoSelect {
+                require,
+                prefer,
+                requirements: Requirements {
+                    min_alloc,
+                    margin,
+                    image_formats: image_format,
+                },
+            };
+            let weights = profile.map(|profile| profile.weights());
+            if let Some(profile) = profile {
+                auto = auto.with_profile(profile);
+            }
+            let scorers: Vec<&dyn DeviceScorer> = weights
+                .iter()
+                .map(|weights| weights as &dyn DeviceScorer)
+                .collect();
+            if explain {
+                eprint!("{}", auto.explain(&devices, &priorities, &scorers));
+            }
+            let device = auto
+                .best_with(&devices, &priorities, &scorers)
+                .ok_or(SelectionError::NoMatchingDevice)?;
+            match format {
+                Some(format) => println!("{}", format.encode(device)?),
+                None => println!("{}", device.name()),
+            }
+            Ok(Exit::Success)
+        }
+        Some(Command::Apply { explain, .. }) => {
+            let spec = spec.expect("the spec is loaded above");
+            if explain {
+                eprint!("{}", spec.explain(&cl_state)?);
+            }
+            let devices = spec.resolve(&cl_state)?;
+            let exports = spec.env_exports(&cl_state, &devices[0]);
+            let selection = UniquePriorityList::from(devices);
+            match exports.is_empty() {
+                true => println!("{}", config.format.encode(&selection)?),
+                false => {
+                    for (key, value) in exports {
+                        println!("export {key}='{}'", value.replace('\'', r"'\''"));
+                    }
+                }
+            }
+            if let Some(path) = spec.output.as_ref().or(cli.output.as_ref()) {
+                save_output(&selection, path)?;
+            }
+            Ok(Exit::Success)
+        }
+        Some(Command::Bench { kernels }) => {
+            let kernels = match kernels.is_empty() {
+                true => config.benchmark_kernels,
+                false => kernels,
+            };
+            run_bench(cl_state.get_all_devices(), &kernels)
+        }
+        Some(Command::Doctor { slow_ms }) => {
+            print_doctor(&cl_state, Duration::from_millis(slow_ms), &config.theme);
+            Ok(Exit::Success)
+        }
+        #[cfg(unix)]
+        Some(Command::Serve { socket }) => {
+            let socket = socket
+                .or_else(Config::socket_path)
+                .ok_or_else(|| StorageError::Io(io::ErrorKind::NotFound.into()))?;
+            serve::serve(cl_state, &socket)?;
+            Ok(Exit::Success)
+        }
+        #[cfg(feature = "server")]
+        Some(Command::Http { listen }) => {
+            eprintln!("serving the device inventory on http://{listen}");
+            opencl3_select::serve_http(cl_state, listen)?;
+            Ok(Exit::Success)
+        }
+        Some(Command::Completions { .. }) => unreachable!("completions are generated above"),
+        Some(Command::Diff { .. }) => unreachable!("snapshots are compared above"),
+        Some(Command::Status { .. }) => unreachable!("the status is printed above"),
+        None if cli.device.is_some()
+            || cli.device_id.is_some()
+            || cli.device_regex.is_some()
+            || cli.platform_regex.is_some() =>
+        {
+            let spec = match (cli.device, cli.device_id) {
+                (Some(text), _) => DeviceSpec::parse(&text, &load_nicknames()?),
+                (None, Some(id)) => DeviceSpec::Id(id),
+                (None, None) => DeviceSpec::Regex {
+                    device: cli.device_regex,
+                    platform: cli.platform_regex,
+                },
+            };
+            let selection = UniquePriorityList::from(vec![spec.find(&cl_state)?]);
+            println!("{}", config.format.encode(&selection)?);
+            if let Some(path) = &cli.output {
+                save_output(&selection, path)?;
+            }
+            Ok(Exit::Success)
+        }
+        None if cli.plain || !tui_supported() => {
+            select_with_prompt(&cl_state, &config, cli.output.as_deref())
+        }
+        None => {
+            // setup terminal
+            let mut terminal = match TerminalGuard::new() {
+                Ok(guard) => guard,
+                Err(error) => {
+                    eprintln!(
+                        "could not start the TUI, falling back to a prompt: {}",
+                        error_message(&error)
+                    );
+                    return select_with_prompt(&cl_state, &config, cli.output.as_deref());
+                }
+            };
+
+            // create app and run it
+            let history_path = Config::history_path();
+            let history = match &history_path {
+                Some(path) => SelectionHistory::load_or_default(path)?,
+                None => SelectionHistory::default(),
+            };
+            let mut app = App::new(&cl_state, &config, history);
+            app.output = cli.output;
+            app.assume_yes = cli.yes;
+            app.reload = Some(config.select.clone());
+            app.groups = load_groups()?;
+            app.groups_path = Config::groups_path();
+            app.nicknames = load_nicknames()?;
+            app.nicknames_path = Config::nicknames_path();
+            app.notes = load_notes()?;
+            app.notes_path = Config::notes_path();
+            app.cl_state = unfiltered;
+            app.hidden = hidden;
+            app.hidden_path = Config::hidden_path();
+            app.benchmarks_path = Config::benchmarks_path();
+            app.benchmark_kernels = config.benchmark_kernels.clone();
+            app.smoke_test = cli.smoke_test;
+            app.unicode = terminal.capabilities().unicode;
+            for error in cl_state.enumeration_errors() {
+                app.notify_error(format!("skipped {}: {}", error.platform, error.message));
+            }
+            if let Some(selection) = project_selection()? {
+                let found = app.preselect(&selection.iter().collect::<Vec<_>>());
+                app.notify(format!(
+                    "applied project selection, found {found} of {} devices",
+                    selection.len()
+                ));
+            } else if app.history.entries.is_empty() {
+                app.wizard = Some(WizardStep::Summary);
+            }
+            let ui_state_path = Config::ui_state_path();
+            if let Some(path) = &ui_state_path {
+                app.restore_ui_state(&UiState::load_or_default(path));
+            }
+            let accepted = app.run(&mut terminal)?;
+
+            terminal.restore()?;
+
+            // Like the history, the state is a convenience whose loss is no reason to fail
+            if let Some(path) = ui_state_path {
+                if let Err(error) = app.ui_state().save(&path) {
+                    eprintln!("could not save the state of the TUI: {error}");
+                }
+            }
+
+            if !accepted {
+                return Ok(Exit::Aborted);
+            }
+            let selection = UniquePriorityList::from(app.selected_devices());
+            println!("{}", config.format.encode(&selection)?);
+            if let Some(path) = &app.output {
+                save_output(&selection, path)?;
+            }
+
+            // The selection was already printed, failing to record it is no reason to fail
+            if let Some(path) = history_path {
+                let platforms = UniquePriorityList::from(app.selected_platforms());
+                let mut history = app.history;
+                history.push(selection, platforms, config.history_length);
+                if let Err(error) = save_history(&history, &path) {
+                    eprintln!("could not save the selection history: {error}");
+                }
+            }
+            Ok(Exit::Success)
+        }
+    }
+}
+
+/// Finds the platforms and devices allowed by the config
+///
+/// Devices failing the smoke test are dropped if `smoke` is set.
+fn enumerate(select: &SelectConfig, smoke: bool) -> Result<ClState> {
+    let mut cl_state = match opencl3_select::get_setup_with_config(select) {
+        Err(ClSelectError::Clinfo(ClinfoError::OpenCL {
+            source: opencl3::error_codes::ClError(opencl3::error_codes::CL_PLATFORM_NOT_FOUND_KHR),
+            ..
+        })) => return Err(ClinfoError::NoPlatforms.into()),
+        cl_state => cl_state?,
+    };
+    // Platforms which timed out are reported instead
+    if cl_state.platforms_ref().is_empty() && cl_state.enumeration_errors().is_empty() {
+        return Err(ClinfoError::NoPlatforms.into());
+    }
+    if smoke {
+        cl_state.retain_devices(smoke_test);
+    }
+    // Only shell completions read the cache, so failing to write it is no error
+    if let Some(path) = Config::enumeration_cache_path() {
+        let _ = EnumerationCache::new(path).store(&cl_state);
+    }
+    Ok(cl_state)
+}
+
+/// Values of --device offered by shell completions
+///
+/// Consists of the names of the devices in the [enumeration cache](Config::enumeration_cache_path)
+/// and of all nicknames. Empty if neither could be read.
+fn device_candidates() -> Vec<String> {
+    let names = Config::enumeration_cache_path()
+        .and_then(|path| EnumerationCache::new(path).load().ok())
+        .map(|cl_state| {
+            cl_state
+                .get_all_devices()
+                .iter()
+                .map(|device| device.name())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let nicknames = load_nicknames()
+        .map(|nicknames| nicknames.nicknames.into_keys().collect::<Vec<_>>())
+        .unwrap_or_default();
+    let mut candidates: Vec<String> = names
+        .into_iter()
+        .chain(
+            nicknames
+                .into_iter()
+                .map(|nickname| format!("name:{nickname}")),
+        )
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+/// Saves the selection to the `--output` file
+///
+/// `.rs` and `.env` files receive a [snippet](SnippetFormat) naming the first device, all other
+/// files the whole selection in the format given by their extension.
+fn save_output(selection: &UniquePriorityList<DeviceInfo>, path: &Path) -> Result<()> {
+    match SnippetFormat::from_path(path) {
+        Some(format) => {
+            let device = selection
+                .priority_first()
+                .ok_or(SelectionError::NoMatchingDevice)?;
+            format.save(device, path)
+        }
+        None => opencl3_select::save(selection, path),
+    }
+}
+
+/// Devices of the [project-local selection](Config::project_path) if there is one
+fn project_selection() -> Result<Option<Vec<DeviceInfo>>> {
+    let Some(path) = Config::project_path() else {
+        return Ok(None);
+    };
+    let selection: UniquePriorityList<DeviceInfo> = opencl3_select::load(path)?;
+    Ok(Some(
+        selection
+            .view_priority_list()
+            .into_iter()
+            .cloned()
+            .collect(),
+    ))
+}
+
+/// Device groups defined by the user, empty if none were saved yet
+fn load_groups() -> Result<DeviceGroups> {
+    match Config::groups_path() {
+        Some(path) => DeviceGroups::load_or_default(path),
+        None => Ok(DeviceGroups::default()),
+    }
+}
+
+/// Nicknames of devices given by the user, none if none were saved yet
+fn load_nicknames() -> Result<DeviceNicknames> {
+    match Config::nicknames_path() {
+        Some(path) => DeviceNicknames::load_or_default(path),
+        None => Ok(DeviceNicknames::default()),
+    }
+}
+
+/// Notes the user attached to devices, none if none were saved yet
+fn load_notes() -> Result<DeviceNotes> {
+    match Config::notes_path() {
+        Some(path) => DeviceNotes::load_or_default(path),
+        None => Ok(DeviceNotes::default()),
+    }
+}
+
+/// Devices the user hid in the TUI, none if there is no data directory
+fn load_hidden() -> Result<HiddenDevices> {
+    match Config::hidden_path() {
+        Some(path) => HiddenDevices::load_or_default(path),
+        None => Ok(HiddenDevices::default()),
+    }
+}
+
+/// Saves the history and creates its directory if needed
+fn save_history(history: &SelectionHistory, path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(StorageError::Io)?;
+    }
+    opencl3_select::save(history, path)
+}
+
+/// Reads the harness files of kernel benchmarks, reporting the ones which cannot be read
+fn load_kernels(paths: &[PathBuf], mut report: impl FnMut(String)) -> Vec<KernelBenchmark> {
+    paths
+        .iter()
+        .filter_map(|path| match KernelBenchmark::load(path) {
+            Ok(kernel) => Some(kernel),
+            Err(error) => {
+                report(format!(
+                    "skipped kernel benchmark {}: {}",
+                    path.display(),
+                    error_message(&error)
+                ));
+                None
+            }
+        })
+        .collect()
+}
+
+/// Runs the built-in benchmarks and the kernels on the devices and prints a ranking of the
+/// devices for every benchmark
+fn run_bench(devices: Vec<DeviceInfo>, kernel_paths: &[PathBuf]) -> Result<Exit> {
+    if devices.is_empty() {
+        return Err(SelectionError::NoMatchingDevice.into());
+    }
+    let kernels = load_kernels(kernel_paths, |error| eprintln!("{error}"));
+    let names: Vec<_> = Benchmark::ALL
+        .iter()
+        .map(|benchmark| benchmark.name().to_string())
+        .chain(kernels.iter().map(|kernel| kernel.name.clone()))
+        .collect();
+    let mut progress = vec![BenchmarkProgress::new(names.len()); devices.len()];
+    for event in spawn_benchmarks_with(devices.clone(), kernels) {
+        match &event {
+            BenchmarkEvent::Started { device, benchmark } => {
+                eprintln!("running {benchmark} on {}", devices[*device].name_ref());
+            }
+            BenchmarkEvent::Failed {
+                device,
+                benchmark,
+                message,
+            } => eprintln!(
+                "{benchmark} failed on {}: {message}",
+                devices[*device].name_ref()
+            ),
+            BenchmarkEvent::Finished { .. } => {}
+        }
+        progress[event.device()].apply(event);
+    }
+    let devices: Vec<_> = devices.into_iter().zip(progress).collect();
+    for name in &names {
+        let mut ranking: Vec<_> = devices
+            .iter()
+            .filter_map(|(device, progress)| {
+                let result = progress
+                    .results
+                    .iter()
+                    .find(|result| &result.name == name)?;
+                Some((device, result))
+            })
+            .collect();
+        let Some((_, first)) = ranking.first() else {
+            continue;
+        };
+        let order = match first.higher_is_better {
+            true => "higher is better",
+            false => "lower is better",
+        };
+        println!("{name} ({}, {order})", first.unit);
+        ranking.sort_by(|(_, a), (_, b)| match a.higher_is_better {
+            true => b.value.total_cmp(&a.value),
+            false => a.value.total_cmp(&b.value),
+        });
+        for (rank, (device, result)) in ranking.iter().enumerate() {
+            println!(
+                "  {}. {} ({}): {:.3} {}",
+                rank + 1,
+                device.name_ref(),
+                device.platform_ref(),
+                result.value,
+                result.unit
+            );
+        }
+    }
+    // The results were already printed, failing to record them is no reason to fail
+    if let Some(path) = Config::benchmarks_path() {
+        match record_benchmarks(&devices, &path) {
+            Ok(deltas) => {
+                for delta in deltas
+                    .iter()
+                    .filter(|delta| delta.is_regression(REGRESSION_TOLERANCE))
+                {
+                    eprintln!("regression: {delta}");
+                }
+            }
+            Err(error) => eprintln!("could not save the benchmarks: {}", error_message(&error)),
+        }
+    }
+    let failed = devices
+        .iter()
+        .any(|(_, progress)| !progress.failures.is_empty());
+    Ok(match failed {
+        true => Exit::Other,
+        false => Exit::Success,
+    })
+}
+
+/// Adds the results of the devices to the stored benchmarks and returns their changes
+fn record_benchmarks(
+    devices: &[(DeviceInfo, BenchmarkProgress)],
+    path: &Path,
+) -> Result<Vec<BenchmarkDelta>> {
+    let mut history = BenchmarkHistory::load_or_default(path)?;
+    let deltas = devices
+        .iter()
+        .filter(|(_, progress)| !progress.results.is_empty())
+        .flat_map(|(device, progress)| history.record(device, progress.results.clone()))
+        .collect();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(StorageError::Io)?;
+    }
+    opencl3_select::save(&history, path)?;
+    Ok(deltas)
+}
+
+/// Formats seconds since the unix epoch as `YYYY-MM-DD HH:MM UTC`
+fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+/// Builds [SMOKE_TEST_KERNEL] on the device and reports failures on stderr
+fn smoke_test(device: &DeviceInfo) -> bool {
+    match compile_test(device, SMOKE_TEST_KERNEL) {
+        Ok(report) if report.success => true,
+        Ok(report) => {
+            eprintln!(
+                "smoke test failed on {}:\n{}",
+                device.name(),
+                report.build_log
+            );
+            false
+        }
+        Err(error) => {
+            eprintln!("smoke test failed on {}: {error}", device.name());
+            false
+        }
+    }
+}
+
+/// Prints how long each platform and device took to enumerate and flags slow ones
+fn print_doctor(cl_state: &ClState, slow: Duration, theme: &Theme) {
+    let flag = |duration: Duration| match duration > slow {
+        true => Theme::paint("  <- slow", theme.warning),
+        false => String::new(),
+    };
+    let mut n_slow = 0;
+    println!("Enumeration timings");
+    for platform in cl_state.enumeration_timings() {
+        n_slow += usize::from(platform.duration > slow);
+        println!(
+            "  {:<40} {:>12.3?}{}",
+            platform.name,
+            platform.duration,
+            flag(platform.duration)
+        );
+        for device in platform.devices.iter() {
+            println!(
+                "    {:<38} {:>12.3?}{}",
+                device.name,
+                device.duration,
+                flag(device.duration)
+            );
+        }
+    }
+    for error in cl_state.enumeration_errors() {
+        n_slow += 1;
+        println!(
+            "  {:<40} {}",
+            error.platform,
+            Theme::paint(&error.message, theme.error)
+        );
+    }
+    match n_slow {
+        0 => println!("\nNo platform took longer than {slow:?}"),
+        n => println!(
+            "\n{}",
+            Theme::paint(
+                &format!(
+                    "{n} platform(s) took longer than {slow:?} and slow down every OpenCL application"
+                ),
+                theme.warning
+            )
+        ),
+    }
+}
+
+/// Checks if stdin and stdout are terminals which are capable of showing the TUI
+fn tui_supported() -> bool {
+    io::stdin().is_terminal()
+        && stdout().is_terminal()
+        && !matches!(std::env::var("TERM").as_deref(), Ok("" | "dumb"))
+}
+
+/// Picks devices with the numbered prompt of [prompt_select](opencl3_select::prompt_select)
+/// instead of the TUI, then prints, saves and records the selection like the TUI does
+fn select_with_prompt(cl_state: &ClState, config: &Config, output: Option<&Path>) -> Result<Exit> {
+    let Some(devices) = opencl3_select::prompt_select(cl_state).map_err(DisplayError::Terminal)?
+    else {
+        return Ok(Exit::Aborted);
+    };
+    let selection = UniquePriorityList::from(devices);
+    println!("{}", config.format.encode(&selection)?);
+    if let Some(path) = output {
+        save_output(&selection, path)?;
+    }
+    // The selection was already printed, failing to record it is no reason to fail
+    if let Some(path) = Config::history_path() {
+        let mut history = SelectionHistory::load_or_default(&path)?;
+        history.push(selection, UniquePriorityList::new(), config.history_length);
+        if let Err(error) = save_history(&history, &path) {
+            eprintln!("could not save the selection history: {error}");
+        }
+    }
+    Ok(Exit::Success)
+}
+
+impl App {
+    fn new(cl_state: &ClState, config: &Config, history: SelectionHistory) -> App {
+        let mut app = App {
+            pane: Pane::PLATFORMS,
+            items: PlatformList::from_platforms(cl_state.platforms_ref()),
+            divider_percentage: clamp_divider(config.divider_percentage),
+            default_divider: clamp_divider(config.divider_percentage),
+            priority_list: UniquePriorityList::new(),
+            priority_state: ListState::default(),
+            filter: String::new(),
+            filter_mode: false,
+            summary: cl_state.summary(),
+            summary_scroll: None,
+            help_scroll: None,
+            toasts: Vec::new(),
+            reload: None,
+            smoke_test: false,
+            details_scroll: 0,
+            compare: Vec::new(),
+            show_compare: false,
+            list_heights: [0; 3],
+            history,
+            history_state: None,
+            sort: config.sort,
+            theme: config.theme.clone(),
+            locale: config.locale,
+            keymap: KeyMap::new(&config.keys, &config.bindings),
+            output: None,
+            assume_yes: false,
+            confirm: None,
+            groups: DeviceGroups::default(),
+            groups_path: None,
+            tag_input: None,
+            nicknames: DeviceNicknames::default(),
+            nicknames_path: None,
+            nickname_input: None,
+            notes: DeviceNotes::default(),
+            notes_path: None,
+            note_input: None,
+            jump_pending: false,
+            held: false,
+            unicode: true,
+            cl_state: cl_state.clone(),
+            hidden: HiddenDevices::default(),
+            hidden_path: None,
+            hidden_state: None,
+            wizard: None,
+            benchmarks: None,
+            view: View::Devices,
+            view_scroll: 0,
+            benchmarks_path: None,
+            benchmark_kernels: Vec::new(),
+        };
+        app.update_visible();
+        app
+    }
+
+    fn go_top(&mut self) {
+        match self.pane {
+            Pane::Hierarchy(depth) => {
+                if let Some(level) = self.level_mut(depth).filter(|level| level.len() > 0) {
+                    level.state_mut().select(Some(0));
+                }
+            }
+            Pane::Priorities if self.selected_count() > 0 => self.priority_state.select(Some(0)),
+            Pane::Priorities => {}
+        }
+    }
+
+    fn go_bottom(&mut self) {
+        match self.pane {
+            Pane::Hierarchy(depth) => {
+                if let Some(level) = self.level_mut(depth).filter(|level| level.len() > 0) {
+                    let last = level.len() - 1;
+                    level.state_mut().select(Some(last));
+                }
+            }
+            Pane::Priorities if self.selected_count() > 0 => {
+                self.priority_state.select(Some(self.selected_count() - 1))
+            }
+            Pane::Priorities => {}
+        }
+    }
+
+    /// Number of items which fit into the list of the current pane
+    fn page_size(&self) -> usize {
+        let (height, item_height) = match self.pane {
+            Pane::Hierarchy(depth) => (
+                self.list_heights[depth - self.columns()[0]],
+                self.level(depth).map_or(1, |level| level.item_height()),
+            ),
+            Pane::Priorities => (self.list_heights[2], 2),
+        };
+        usize::from(height / item_height).max(1)
+    }
+
+    /// Moves the selection of the current pane by the given number of pages
+    ///
+    /// Unlike [next](App::next) and [previous](App::previous) this stops at the ends of the list.
+    fn move_pages(&mut self, pages: f32) {
+        let steps = ((self.page_size() as f32 * pages.abs()) as isize).max(1);
+        let delta = steps * pages.signum() as isize;
+        let n_selected = self.selected_count();
+        let (state, len) = match self.pane {
+            Pane::Hierarchy(depth) => {
+                let Some(level) = self.level_mut(depth) else {
+                    return;
+                };
+                let len = level.len();
+                (level.state_mut(), len)
+            }
+            Pane::Priorities => (&mut self.priority_state, n_selected),
+        };
+        if len == 0 {
+            return;
+        }
+        let i = state.selected().unwrap_or(0).saturating_add_signed(delta);
+        state.select(Some(i.min(len - 1)));
+    }
+
+    fn move_divider(&mut self, length: i16) {
+        self.divider_percentage =
+            clamp_divider(self.divider_percentage.saturating_add_signed(length));
+    }
+
+    fn currently_selected_device_index(&self) -> Option<(usize, usize)> {
+        let i = self.items.state.selected()?;
+        let devices = &self.items.items.get(i)?.devices;
+        devices.selected_index().map(|j| (i, j))
+    }
+
+    fn selected_count(&self) -> usize {
+        self.priority_list.view_priority_list().into_iter().count()
+    }
+
+    /// Devices which have been given a priority, ordered from highest to lowest
+    ///
+    /// Devices which are [missing](DeviceItem::missing) are left out.
+    fn selected_devices(&self) -> Vec<DeviceInfo> {
+        self.priority_list
+            .view_priority_list()
+            .into_iter()
+            .filter_map(|index| self.get_device(index))
+            .filter(|device| !device.missing)
+            .map(|device| device.info.clone())
+            .collect()
+    }
+
+    fn set_priority(&mut self, n: usize) {
+        if self.pane == Pane::PLATFORMS {
+            if let Some(i) = self.items.state.selected() {
+                let priorities = &mut self.items.priorities;
+                let n = n.min(priorities.view_priority_list().into_iter().count());
+                priorities.push_set_nth(i, n);
+            }
+            return;
+        }
+        if self.pane != Pane::DEVICES {
+            return;
+        }
+        // Get selected item
+        if let Some(element) = self.currently_selected_device_index() {
+            let n = n.min(self.selected_count());
+            self.priority_list.push_set_nth(element, n)
+        }
+    }
+
+    /// Platforms which have been given a priority, ordered from highest to lowest
+    fn selected_platforms(&self) -> Vec<PlatformInfo> {
+        self.items
+            .priorities
+            .view_priority_list()
+            .into_iter()
+            .filter_map(|&i| self.items.items.get(i))
+            .map(|platform| platform.info.clone())
+            .collect()
+    }
+
+    /// Device whose details are shown, the highlighted priority while the priorities are focused
+    fn details_device_index(&self) -> Option<(usize, usize)> {
+        match self.pane {
+            Pane::Priorities => self
+                .priority_state
+                .selected()
+                .and_then(|i| self.priority_list.priority_nth(i))
+                .copied(),
+            Pane::Hierarchy(_) => self.currently_selected_device_index(),
+        }
+    }
+
+    fn get_device(&self, index: &(usize, usize)) -> Option<&DeviceItem> {
+        self.items
+            .items
+            .get(index.0)
+            .and_then(|platform| platform.devices.items.get(index.1))
+    }
+
+    /// Level of the hierarchy at the given depth below the highlighted entries
+    fn level(&self, depth: usize) -> Option<&dyn Level> {
+        let mut level: &dyn Level = &self.items;
+        for _ in 0..depth {
+            level = level.child(level.state().selected()?)?;
+        }
+        Some(level)
+    }
+
+    /// See [level](App::level)
+    fn level_mut(&mut self, depth: usize) -> Option<&mut dyn Level> {
+        let mut level: &mut dyn Level = &mut self.items;
+        for _ in 0..depth {
+            let i = level.state().selected()?;
+            level = level.child_mut(i)?;
+        }
+        Some(level)
+    }
+
+    /// Depth of the innermost level below the highlighted entries
+    fn max_depth(&self) -> usize {
+        let mut depth = 0;
+        while self.level(depth + 1).is_some() {
+            depth += 1;
+        }
+        depth
+    }
+
+    /// Depths of the levels shown in the left and right column
+    ///
+    /// The focused level and its parent are shown, or the platforms and their devices while
+    /// the priorities are focused.
+    fn columns(&self) -> [usize; 2] {
+        let left = match self.pane {
+            Pane::Hierarchy(depth) => depth.saturating_sub(1),
+            Pane::Priorities => 0,
+        };
+        [left, left + 1]
+    }
+
+    /// Descends into the level below the highlighted entry
+    fn move_right(&mut self) {
+        self.pane = match self.pane {
+            Pane::Hierarchy(depth) if self.level(depth + 1).is_some() => Pane::Hierarchy(depth + 1),
+            Pane::Hierarchy(depth) => Pane::Hierarchy(depth),
+            Pane::Priorities => Pane::DEVICES,
+        };
+    }
+
+    /// Ascends to the parent level
+    fn move_left(&mut self) {
+        self.pane = match self.pane {
+            Pane::Hierarchy(depth) => Pane::Hierarchy(depth.saturating_sub(1)),
+            Pane::Priorities => Pane::PLATFORMS,
+        };
+    }
+
+    fn next_pane(&mut self) {
+        self.pane = match self.pane {
+            Pane::Hierarchy(depth) if depth < self.max_depth() => Pane::Hierarchy(depth + 1),
+            Pane::Hierarchy(_) => Pane::Priorities,
+            Pane::Priorities => Pane::PLATFORMS,
+        };
+    }
+
+    fn previous_pane(&mut self) {
+        self.pane = match self.pane {
+            Pane::Hierarchy(0) => Pane::Priorities,
+            Pane::Hierarchy(depth) => Pane::Hierarchy(depth - 1),
+            Pane::Priorities => Pane::Hierarchy(self.max_depth()),
+        };
+    }
+
+    /// Jumps to the next entry of the current pane starting with the letter, see [Level::jump]
+    fn jump(&mut self, letter: char) {
+        let Pane::Hierarchy(depth) = self.pane else {
+            return;
+        };
+        let found = self
+            .level_mut(depth)
+            .is_some_and(|level| level.jump(letter));
+        if !found {
+            self.notify_error(format!("no entry starts with \"{letter}\""));
+        }
+    }
+
+    fn next(&mut self) {
+        match self.pane {
+            Pane::Hierarchy(depth) => {
+                if let Some(level) = self.level_mut(depth) {
+                    level.next();
+                }
+            }
+            Pane::Priorities => {
+                let n_selected = self.selected_count();
+                if n_selected > 0 {
+                    let i = self
+                        .priority_state
+                        .selected()
+                        .map_or(0, |i| (i + 1) % n_selected);
+                    self.priority_state.select(Some(i));
+                }
+            }
+        }
+    }
+
+    /// Moves the grabbed priority by the given number of entries, stopping at the ends
+    fn move_held(&mut self, delta: isize) {
+        let Some(i) = self.priority_state.selected() else {
+            return;
+        };
+        let Some(to) = i
+            .checked_add_signed(delta)
+            .filter(|to| *to < self.selected_count())
+        else {
+            return;
+        };
+        if let Some(&element) = self.priority_list.priority_nth(i) {
+            self.priority_list.push_set_nth(element, to);
+            self.priority_state.select(Some(to));
+        }
+    }
+
+    fn previous(&mut self) {
+        match self.pane {
+            Pane::Hierarchy(depth) => {
+                if let Some(level) = self.level_mut(depth) {
+                    level.previous();
+                }
+            }
+            Pane::Priorities => {
+                let n_selected = self.selected_count();
+                if n_selected > 0 {
+                    let i = match self.priority_state.selected() {
+                        Some(0) | None => n_selected - 1,
+                        Some(i) => i - 1,
+                    };
+                    self.priority_state.select(Some(i));
+                }
+            }
+        }
+    }
+
+    fn update_visible(&mut self) {
+        for platform in self.items.items.iter_mut() {
+            platform.devices.update_visible(&self.filter, self.sort);
+        }
+    }
+
+    fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.filter_mode = false;
+        self.update_visible();
+    }
+
+    fn cycle_sort(&mut self) {
+        self.sort = self.sort.next();
+        self.update_visible();
+    }
+
+    /// Copies the highlighted device to the system clipboard
+    ///
+    /// Copies all properties if `full` is set and only the name of the device otherwise.
+    #[cfg(feature = "clipboard")]
+    fn yank(&mut self, full: bool) {
+        let Some(device) = self
+            .currently_selected_device_index()
+            .and_then(|index| self.get_device(&index))
+        else {
+            return;
+        };
+        let text = if full {
+            device.to_text()
+        } else {
+            device.info.name()
+        };
+        let result = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text));
+        let name = device.info.name();
+        match result {
+            Ok(()) if full => self.notify(format!("copied info of {name}")),
+            Ok(()) => self.notify(format!("copied name of {name}")),
+            Err(error) => self.notify_error(format!("clipboard: {error}")),
+        }
+    }
+
+    /// Shows the history of previous selections
+    fn open_history(&mut self) {
+        let mut state = ListState::default();
+        if !self.history.entries.is_empty() {
+            state.select(Some(0));
+        }
+        self.history_state = Some(state);
+    }
+
+    /// Moves the highlighted entry of the history by the given offset, wrapping around
+    fn move_history(&mut self, offset: isize) {
+        let len = self.history.entries.len();
+        if let Some(state) = self.history_state.as_mut().filter(|_| len > 0) {
+            let i = state.selected().unwrap_or(0) as isize + offset;
+            state.select(Some(i.rem_euclid(len as isize) as usize));
+        }
+    }
+
+    /// Replaces the device and platform priorities by the highlighted entry of the history
+    ///
+    /// Devices and platforms which are no longer present are skipped.
+    fn apply_history(&mut self) {
+        let Some(entry) = self
+            .history_state
+            .as_ref()
+            .and_then(|state| state.selected())
+            .and_then(|i| self.history.entries.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let selection: Vec<_> = entry.selection.view_priority_list().into_iter().collect();
+        let found = self.preselect(&selection);
+        self.preselect_platforms(
+            &entry
+                .platforms
+                .view_priority_list()
+                .into_iter()
+                .collect::<Vec<_>>(),
+        );
+        self.notify(format!(
+            "applied selection of {}, found {found} of {} devices",
+            format_timestamp(entry.timestamp),
+            selection.len()
+        ));
+        self.history_state = None;
+    }
+
+    /// Replaces the platform priorities by the given platforms
+    ///
+    /// Platforms are matched by name since their devices may have changed.
+    fn preselect_platforms(&mut self, platforms: &[&PlatformInfo]) {
+        let indices: Vec<_> = platforms
+            .iter()
+            .filter_map(|platform| {
+                self.items
+                    .items
+                    .iter()
+                    .position(|item| item.info.name_ref() == platform.name_ref())
+            })
+            .collect();
+        self.items.priorities = UniquePriorityList::from(indices);
+    }
+
+    /// Shows a notification for [TOAST_DURATION]
+    fn notify(&mut self, text: impl Into<String>) {
+        self.toasts.push(Toast {
+            text: text.into(),
+            error: false,
+            until: Instant::now() + TOAST_DURATION,
+        });
+    }
+
+    /// Shows a notification about a failure for [ERROR_TOAST_DURATION]
+    fn notify_error(&mut self, text: impl Into<String>) {
+        self.toasts.push(Toast {
+            text: text.into(),
+            error: true,
+            until: Instant::now() + ERROR_TOAST_DURATION,
+        });
+    }
+
+    /// Dismisses the toasts which expired before the given point in time
+    fn expire_toasts(&mut self, now: Instant) {
+        self.toasts.retain(|toast| toast.until > now);
+    }
+
+    /// Enumerates the platforms and devices again and keeps the priorities of those still present
+    ///
+    /// Failures are reported as toasts such that the App keeps running.
+    fn refresh(&mut self) {
+        let Some(reload) = &self.reload else {
+            return;
+        };
+        let cl_state = match enumerate(reload, self.smoke_test) {
+            Ok(cl_state) => cl_state,
+            Err(error) => {
+                self.notify_error(format!("refresh failed: {}", error_message(&error)));
+                return;
+            }
+        };
+        let devices = cl_state.get_all_devices().len();
+        let errors = cl_state.enumeration_errors().to_vec();
+        match self.set_state(cl_state, true) {
+            0 => self.notify(format!("refreshed, found {devices} devices")),
+            1 => self.notify_error("1 device disappeared, P removes it"),
+            missing => self.notify_error(format!("{missing} devices disappeared, P removes them")),
+        }
+        for error in errors {
+            self.notify_error(format!("skipped {}: {}", error.platform, error.message));
+        }
+    }
+
+    /// Lists the platforms and devices of the state except the hidden devices
+    ///
+    /// Priorities of platforms and devices which are still listed are kept, as are the
+    /// highlighted entries where possible. With `keep_missing`, devices which were highlighted
+    /// or had a priority but are no longer present stay listed as
+    /// [missing](DeviceItem::missing), see [App::prune_missing]. Returns the number of missing
+    /// devices.
+    fn set_state(&mut self, cl_state: ClState, keep_missing: bool) -> usize {
+        let highlighted = self
+            .currently_selected_device_index()
+            .and_then(|index| self.get_device(&index))
+            .map(|device| device.info.clone());
+        let selection: Vec<_> = self
+            .priority_list
+            .view_priority_list()
+            .into_iter()
+            .filter_map(|index| self.get_device(index))
+            .map(|device| device.info.clone())
+            .collect();
+        let platforms = self.selected_platforms();
+        // Properties such as the free memory may change between enumerations, devices are
+        // thus matched by their id. Hidden devices are still present and thus dropped rather
+        // than kept as missing.
+        let present: Vec<_> = cl_state
+            .get_all_devices()
+            .iter()
+            .map(DeviceInfo::id)
+            .collect();
+        let mut missing: Vec<DeviceInfo> = Vec::new();
+        for device in highlighted.iter().chain(&selection) {
+            let id = device.id();
+            if keep_missing
+                && !present.contains(&id)
+                && !missing.iter().any(|other| other.id() == id)
+            {
+                missing.push(device.clone());
+            }
+        }
 
-const HEADER_BG: Color = tailwind::ZINC.c950;
-const NORMAL_ROW_COLOR: Color = tailwind::ZINC.c950;
-const ALT_ROW_COLOR: Color = tailwind::ZINC.c900;
-const SELECTED_STYLE_FG: Color = tailwind::ZINC.c300;
-const SELECTED_STYLE_FG_LIGHT: Color = tailwind::ZINC.c500;
-const TEXT_COLOR: Color = tailwind::ZINC.c200;
+        let mut listed = cl_state.clone();
+        self.hidden.apply(&mut listed);
+        self.cl_state = cl_state;
+        let platform = self.items.state.selected();
+        self.items = PlatformList::from_platforms(listed.platforms_ref());
+        for device in missing.iter().cloned() {
+            self.items.push_missing(device);
+        }
+        self.summary = listed.summary();
+        self.compare.clear();
+        self.update_visible();
+        self.preselect(&selection.iter().collect::<Vec<_>>());
+        self.preselect_platforms(&platforms.iter().collect::<Vec<_>>());
+        self.restore_highlight(platform, highlighted.as_ref());
+        missing.len()
+    }
 
-#[derive(Clone, Debug)]
-struct PlatformItem {
-    info: clinfo::PlatformInfo,
-    devices: DeviceList,
-}
+    /// Highlights the device again after the lists were rebuilt
+    ///
+    /// Falls back to the entry at the previous position if the device is no longer listed,
+    /// such that the highlighted indices stay within the lists.
+    fn restore_highlight(&mut self, platform: Option<usize>, device: Option<&DeviceInfo>) {
+        let len = self.items.items.len();
+        let platform = self
+            .items
+            .items
+            .iter()
+            .position(|platform| {
+                platform
+                    .devices
+                    .items
+                    .iter()
+                    .any(|item| Some(item.info.id()) == device.map(DeviceInfo::id))
+            })
+            .or(platform.filter(|_| len > 0).map(|i| i.min(len - 1)));
+        self.items.state.select(platform);
+        if let Some(devices) = platform.map(|i| &mut self.items.items[i].devices) {
+            let position = devices
+                .visible
+                .iter()
+                .position(|&j| Some(devices.items[j].info.id()) == device.map(DeviceInfo::id));
+            if position.is_some() {
+                devices.state.select(position);
+            }
+        }
+        if let Pane::Hierarchy(depth) = self.pane {
+            if self.level(depth).is_none() {
+                self.pane = Pane::PLATFORMS;
+            }
+        }
+        let count = self.selected_count();
+        if let Some(i) = self.priority_state.selected() {
+            self.priority_state
+                .select((count > 0).then(|| i.min(count - 1)));
+        }
+    }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-struct DeviceItem {
-    info: clinfo::DeviceInfo,
-}
+    /// Removes the devices which are [missing](DeviceItem::missing) from the lists and the
+    /// priorities
+    fn prune_missing(&mut self) {
+        let missing = self
+            .items
+            .items
+            .iter()
+            .flat_map(|platform| &platform.devices.items)
+            .filter(|device| device.missing)
+            .count();
+        if missing == 0 {
+            self.notify("no device is missing");
+            return;
+        }
+        self.set_state(self.cl_state.clone(), false);
+        self.notify(match missing {
+            1 => "removed 1 missing device".to_string(),
+            missing => format!("removed {missing} missing devices"),
+        });
+    }
 
-#[derive(Clone, Debug)]
-struct PlatformList {
-    state: ListState,
-    items: Vec<PlatformItem>,
-}
+    /// Hides the highlighted device from now on, see [App::open_hidden] to show it again
+    fn hide_device(&mut self) {
+        let Some(device) = self
+            .currently_selected_device_index()
+            .and_then(|index| self.get_device(&index))
+            .map(|device| device.info.clone())
+        else {
+            return;
+        };
+        self.hidden.toggle(&device);
+        self.save_hidden();
+        let platform = self.items.state.selected();
+        self.set_state(self.cl_state.clone(), true);
+        self.items.state.select(platform);
+        self.pane = Pane::DEVICES;
+        self.notify(format!("hid {}, D lists the hidden devices", device.name()));
+    }
 
-#[derive(Clone, Debug)]
-struct DeviceList {
-    state: ListState,
-    items: Vec<DeviceItem>,
-}
+    /// Shows the highlighted hidden device in the lists again
+    fn unhide_device(&mut self) {
+        let Some(i) = self
+            .hidden_state
+            .as_ref()
+            .and_then(|state| state.selected())
+            .filter(|&i| i < self.hidden.devices.len())
+        else {
+            return;
+        };
+        let device = self.hidden.devices.remove(i);
+        self.save_hidden();
+        self.set_state(self.cl_state.clone(), true);
+        if let Some(state) = self.hidden_state.as_mut() {
+            let len = self.hidden.devices.len();
+            state.select((len > 0).then(|| i.min(len - 1)));
+        }
+        self.notify(format!("{} is listed again", device.name));
+    }
 
-#[derive(Clone, Debug)]
-struct App {
-    currently_left: bool,
-    items: PlatformList,
-    divider_percentage: u16,
-    priority_list: UniquePriorityList<(usize, usize)>,
-}
+    /// Saves the hidden devices and reports failures
+    fn save_hidden(&mut self) {
+        let saved = match &self.hidden_path {
+            Some(path) => path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .map_err(|error| StorageError::Io(error).into())
+                .and_then(|_| opencl3_select::save(&self.hidden, path)),
+            None => Ok(()),
+        };
+        if let Err(error) = saved {
+            self.notify_error(format!(
+                "could not save the hidden devices: {}",
+                error_message(&error)
+            ));
+        }
+    }
 
-fn main() -> Result<()> {
-    // setup terminal
-    let terminal = init_terminal()?;
+    /// Shows the hidden devices such that they can be listed again
+    fn open_hidden(&mut self) {
+        let mut state = ListState::default();
+        if !self.hidden.devices.is_empty() {
+            state.select(Some(0));
+        }
+        self.hidden_state = Some(state);
+    }
 
-    let cl_state = clinfo::get_setup()?;
+    /// Moves the highlighted hidden device by the given offset, wrapping around
+    fn move_hidden(&mut self, offset: isize) {
+        let len = self.hidden.devices.len();
+        if let Some(state) = self.hidden_state.as_mut().filter(|_| len > 0) {
+            let i = state.selected().unwrap_or(0) as isize + offset;
+            state.select(Some(i.rem_euclid(len as isize) as usize));
+        }
+    }
 
-    // create app and run it
-    App::new(&cl_state.get_platforms()).run(terminal)?;
+    /// Layout and position to restore when the App is opened again
+    fn ui_state(&self) -> UiState {
+        let platform = self
+            .items
+            .state
+            .selected()
+            .and_then(|i| self.items.items.get(i));
+        let device = platform.and_then(|platform| {
+            let j = platform.devices.selected_index()?;
+            Some(platform.devices.items[j].info.fingerprint())
+        });
+        UiState {
+            divider_percentage: Some(self.divider_percentage),
+            platform: platform.map(|platform| platform.info.name()),
+            device,
+            sort: Some(self.sort),
+            filter: self.filter.clone(),
+        }
+    }
 
-    restore_terminal()?;
+    /// Restores the layout and position of a previous run as far as they still apply
+    fn restore_ui_state(&mut self, state: &UiState) {
+        if let Some(divider_percentage) = state.divider_percentage {
+            self.divider_percentage = clamp_divider(divider_percentage);
+        }
+        if let Some(sort) = state.sort {
+            self.sort = sort;
+        }
+        self.filter.clone_from(&state.filter);
+        self.update_visible();
+        let Some(i) = self
+            .items
+            .items
+            .iter()
+            .position(|platform| Some(platform.info.name_ref()) == state.platform.as_deref())
+        else {
+            return;
+        };
+        self.items.state.select(Some(i));
+        let devices = &mut self.items.items[i].devices;
+        let found = state.device.as_ref().and_then(|fingerprint| {
+            devices
+                .visible
+                .iter()
+                .position(|&j| fingerprint.matches(&devices.items[j].info))
+        });
+        if let Some(position) = found {
+            devices.state.select(Some(position));
+        }
+    }
 
-    Ok(())
-}
+    /// Adds the highlighted device to the typed group or removes it and saves the groups
+    fn apply_tag(&mut self) {
+        let Some(group) = self
+            .tag_input
+            .take()
+            .filter(|group| !group.trim().is_empty())
+        else {
+            return;
+        };
+        let group = group.trim();
+        let Some(device) = self
+            .currently_selected_device_index()
+            .and_then(|index| self.get_device(&index))
+            .map(|device| device.info.clone())
+        else {
+            return;
+        };
+        let tagged = self.groups.toggle(group, &device);
+        let saved = match &self.groups_path {
+            Some(path) => path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .map_err(|error| StorageError::Io(error).into())
+                .and_then(|_| opencl3_select::save(&self.groups, path)),
+            None => Ok(()),
+        };
+        match saved {
+            Err(error) => self.notify_error(format!(
+                "could not save the groups: {}",
+                error_message(&error)
+            )),
+            Ok(()) if tagged => self.notify(format!("added {} to {group}", device.name())),
+            Ok(()) => self.notify(format!("removed {} from {group}", device.name())),
+        }
+    }
 
-fn init_terminal() -> Result<Terminal<impl Backend>> {
-    enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout());
-    let terminal = Terminal::new(backend)?;
-    Ok(terminal)
-}
+    /// Gives the highlighted device the typed nickname or removes it if none was typed and saves
+    /// the nicknames
+    fn apply_nickname(&mut self) {
+        let Some(nickname) = self.nickname_input.take() else {
+            return;
+        };
+        let nickname = nickname.trim();
+        let Some(device) = self
+            .currently_selected_device_index()
+            .and_then(|index| self.get_device(&index))
+            .map(|device| device.info.clone())
+        else {
+            return;
+        };
+        let removed = match nickname.is_empty() {
+            true => self.nicknames.remove(&device),
+            false => {
+                self.nicknames.set(nickname, &device);
+                None
+            }
+        };
+        let saved = match &self.nicknames_path {
+            Some(path) => path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .map_err(|error| StorageError::Io(error).into())
+                .and_then(|_| opencl3_select::save(&self.nicknames, path)),
+            None => Ok(()),
+        };
+        match (saved, removed) {
+            (Err(error), _) => self.notify_error(format!(
+                "could not save the nicknames: {}",
+                error_message(&error)
+            )),
+            (Ok(()), Some(removed)) => {
+                self.notify(format!("removed nickname {removed} of {}", device.name()))
+            }
+            (Ok(()), None) if nickname.is_empty() => {}
+            (Ok(()), None) => self.notify(format!("named {} {nickname}", device.name())),
+        }
+    }
 
-fn restore_terminal() -> Result<()> {
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
-    Ok(())
-}
+    /// Attaches the typed note to the highlighted device or removes its note if none was typed
+    /// and saves the notes
+    fn apply_note(&mut self) {
+        let Some(note) = self.note_input.take() else {
+            return;
+        };
+        let note = note.trim();
+        let Some(device) = self
+            .currently_selected_device_index()
+            .and_then(|index| self.get_device(&index))
+            .map(|device| device.info.clone())
+        else {
+            return;
+        };
+        let removed = match note.is_empty() {
+            true => self.notes.remove(&device).is_some(),
+            false => {
+                self.notes.set(&device, note);
+                false
+            }
+        };
+        let saved = match &self.notes_path {
+            Some(path) => path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .map_err(|error| StorageError::Io(error).into())
+                .and_then(|_| opencl3_select::save(&self.notes, path)),
+            None => Ok(()),
+        };
+        match saved {
+            Err(error) => self.notify_error(format!(
+                "could not save the notes: {}",
+                error_message(&error)
+            )),
+            Ok(()) if removed => self.notify(format!("removed the note of {}", device.name())),
+            Ok(()) if note.is_empty() => {}
+            Ok(()) => self.notify(format!("saved the note of {}", device.name())),
+        }
+    }
 
-impl App {
-    fn new<'a>(platforms: &Vec<clinfo::PlatformInfo>) -> App {
-        App {
-            currently_left: true,
-            items: PlatformList::from_platforms(platforms),
-            divider_percentage: 40,
-            priority_list: UniquePriorityList::new(),
+    /// Saves the current selection to the `--output` file without leaving the App
+    fn save_selection(&mut self) {
+        let Some(path) = self.output.clone() else {
+            self.notify_error("no --output file to save the selection to");
+            return;
+        };
+        let selection = UniquePriorityList::from(self.selected_devices());
+        match save_output(&selection, &path) {
+            Ok(()) => self.notify(format!("selection saved to {}", path.display())),
+            Err(error) => self.notify_error(format!("saving failed: {}", error_message(&error))),
         }
     }
 
-    /// Changes the status of the selected list item
-    fn push_priority(&mut self) {
-        // Get currently selected device
+    /// Replaces the priority list by the given devices and returns how many of them were found
+    ///
+    /// Devices are matched by their [id](DeviceInfo::id), those which are no longer present are
+    /// skipped.
+    fn preselect(&mut self, selection: &[&DeviceInfo]) -> usize {
+        let indices: Vec<_> = selection
+            .iter()
+            .filter_map(|&device| {
+                let id = device.id();
+                self.items
+                    .items
+                    .iter()
+                    .enumerate()
+                    .find_map(|(i, platform)| {
+                        let j = platform
+                            .devices
+                            .items
+                            .iter()
+                            .position(|item| item.info.id() == id)?;
+                        Some((i, j))
+                    })
+            })
+            .collect();
+        let found = indices.len();
+        self.priority_list = UniquePriorityList::from(indices);
+        found
     }
 
-    fn go_top(&mut self) {
-        if self.currently_left {
-            self.items.state.select(Some(0));
-        } else if let Some(i) = self.items.state.selected() {
-            self.items
-                .items
-                .get_mut(i)
-                .unwrap()
-                .devices
-                .state
-                .select(Some(0));
+    /// Performs the action or asks for confirmation if it would discard data
+    ///
+    /// Returns `true` if the selection was accepted.
+    fn request(&mut self, action: Confirm) -> bool {
+        let destructive = match action {
+            Confirm::ClearPriorities | Confirm::ApplyHistory => self.selected_count() > 0,
+            Confirm::Overwrite => self.output.as_ref().is_some_and(|path| path.exists()),
+        };
+        if destructive && !self.assume_yes {
+            self.confirm = Some(action);
+            false
+        } else {
+            self.perform(action)
         }
     }
 
-    fn go_bottom(&mut self) {
-        if self.currently_left {
-            self.items.state.select(Some(self.items.items.len() - 1))
-        } else if let Some(i) = self.items.state.selected() {
-            let device_list = &mut self.items.items.get_mut(i).unwrap().devices;
-            device_list.state.select(Some(device_list.items.len() - 1));
+    /// Performs the action without asking, returns `true` if the selection was accepted
+    fn perform(&mut self, action: Confirm) -> bool {
+        self.confirm = None;
+        match action {
+            Confirm::ClearPriorities => {
+                self.priority_list = UniquePriorityList::new();
+                self.priority_state = ListState::default();
+                self.notify("cleared the priority list");
+                false
+            }
+            Confirm::ApplyHistory => {
+                self.apply_history();
+                false
+            }
+            Confirm::Overwrite => true,
         }
     }
 
-    fn move_divider(&mut self, length: i16) {
-        self.divider_percentage = self
-            .divider_percentage
-            .saturating_add_signed(length)
-            .min(100);
+    /// Marks or unmarks the highlighted device for comparison
+    ///
+    /// Marking a third device unmarks the one which was marked first.
+    fn toggle_compare(&mut self) {
+        if self.pane != Pane::DEVICES {
+            return;
+        }
+        let Some(element) = self.currently_selected_device_index() else {
+            return;
+        };
+        match self.compare.iter().position(|&index| index == element) {
+            Some(i) => {
+                self.compare.remove(i);
+            }
+            None => {
+                self.compare.push(element);
+                if self.compare.len() > 2 {
+                    self.compare.remove(0);
+                }
+            }
+        }
+        self.notify(format!("{}/2 marked for comparison", self.compare.len()));
     }
 
-    fn currently_selected_device(&self) -> Option<&DeviceItem> {
-        if let Some(i) = self.items.state.selected() {
-            let devices = &self.items.items[i].devices;
-            return devices.state.selected().and_then(|j| devices.items.get(j));
+    /// Opens the comparison view if two devices are marked
+    fn open_compare(&mut self) {
+        if self.compare.len() == 2 {
+            self.show_compare = true;
+        } else {
+            self.notify("mark two devices with c to compare them");
         }
-        None
     }
 
-    fn currently_selected_device_index(&self) -> Option<(usize, usize)> {
-        if let Some(i) = self.items.state.selected() {
-            let devices = &self.items.items[i].devices;
-            return devices.state.selected().and_then(|j| Some((i, j)));
+    /// Benchmarks the devices with a priority, or the highlighted device if there are none, in
+    /// the background and shows their progress
+    ///
+    /// While benchmarks are running, only their progress is shown again.
+    fn start_benchmarks(&mut self) {
+        if self
+            .benchmarks
+            .as_ref()
+            .is_some_and(|run| run.events.is_some())
+        {
+            self.show_view(View::Benchmarks);
+            return;
         }
-        None
+        let mut devices = self.selected_devices();
+        if devices.is_empty() {
+            devices.extend(
+                self.currently_selected_device_index()
+                    .and_then(|index| self.get_device(&index))
+                    .filter(|device| !device.missing)
+                    .map(|device| device.info.clone()),
+            );
+        }
+        if devices.is_empty() {
+            self.notify("highlight a device or give devices a priority to benchmark them");
+            return;
+        }
+        self.run_benchmarks(devices);
+        self.show_view(View::Benchmarks);
     }
 
-    fn set_priority(&mut self, n: usize) {
-        // Get selected item
-        if let Some(element) = self.currently_selected_device_index() {
-            self.priority_list.push_set_nth(element, n)
+    /// Switches to the view, the priorities are the only pane of their view
+    fn show_view(&mut self, view: View) {
+        match (self.view, view) {
+            (_, View::Priorities) => {
+                self.pane = Pane::Priorities;
+                if self.priority_state.selected().is_none() && self.selected_count() > 0 {
+                    self.priority_state.select(Some(0));
+                }
+            }
+            (View::Priorities, _) => self.pane = Pane::PLATFORMS,
+            _ => {}
         }
+        self.view = view;
+        self.view_scroll = 0;
     }
 
-    fn get_device(&self, index: &(usize, usize)) -> Option<&DeviceItem> {
+    /// Benchmarks the devices in the background, see [App::poll_benchmarks]
+    fn run_benchmarks(&mut self, devices: Vec<DeviceInfo>) {
+        let paths = self.benchmark_kernels.clone();
+        let kernels = load_kernels(&paths, |error| self.notify_error(error));
+        let progress = BenchmarkProgress::new(Benchmark::ALL.len() + kernels.len());
+        let events = spawn_benchmarks_with(devices.clone(), kernels);
+        self.benchmarks = Some(BenchmarkRun {
+            devices: devices
+                .into_iter()
+                .map(|device| (device, progress.clone()))
+                .collect(),
+            events: Some(events),
+        });
+    }
+
+    /// All listed devices in the order of their platforms
+    fn listed_devices(&self) -> Vec<DeviceInfo> {
         self.items
             .items
-            .get(index.0)
-            .and_then(|platform| platform.devices.items.get(index.1))
+            .iter()
+            .flat_map(|platform| platform.devices.items.iter())
+            .map(|device| device.info.clone())
+            .collect()
     }
 
-    fn move_right(&mut self) {
-        self.currently_left = false;
+    /// Best listed device together with the reason it was chosen
+    ///
+    /// Devices are ranked like by `auto` and by the results of the benchmarks if any were run.
+    fn recommendation(&self) -> Option<(DeviceInfo, String)> {
+        let devices = self.listed_devices();
+        let saxpy = self.benchmarks.as_ref().map(SaxpyScorer);
+        let scorers: Vec<&dyn DeviceScorer> = saxpy
+            .iter()
+            .map(|scorer| scorer as &dyn DeviceScorer)
+            .collect();
+        let device = AutoSelect::default()
+            .best_with(&devices, &[], &scorers)?
+            .clone();
+        let millis = self
+            .benchmarks
+            .as_ref()
+            .and_then(|run| run.saxpy_millis(&device));
+        let reason = match millis {
+            Some(millis) => self.locale.format(
+                "it was the fastest in the saxpy benchmark with {millis} ms",
+                &[("millis", &format!("{millis:.2}"))],
+            ),
+            None => {
+                let memory = Bytes(device.global_mem_size().unwrap_or(0));
+                let device_type = device.type_text();
+                let device_type = device_type.trim_start_matches("CL_DEVICE_TYPE_");
+                self.locale.format(
+                    "it is a {device_type} with {memory} of memory",
+                    &[
+                        ("device_type", device_type),
+                        ("memory", &memory.to_string()),
+                    ],
+                )
+            }
+        };
+        Some((device, reason))
     }
 
-    fn move_left(&mut self) {
-        self.currently_left = true;
+    /// Leaves the first-run guide for the lists
+    fn close_wizard(&mut self) {
+        self.wizard = None;
+        self.notify(
+            self.locale
+                .tr("S shows the summary, b benchmarks and Enter accepts the selection"),
+        );
     }
 
-    fn next(&mut self) {
-        if self.currently_left {
-            self.items.next();
-            // If nothing is selected on the right hand side, we select
-            // the first item
-        } else if let Some(i) = self.items.state.selected() {
-            let device_list = &mut self.items.items.get_mut(i).unwrap().devices;
-            device_list.next();
+    /// Gives the recommended device the highest priority and accepts the selection
+    fn accept_recommendation(&mut self) -> Option<bool> {
+        let (device, _) = self.recommendation()?;
+        self.wizard = None;
+        self.preselect(&[&device]);
+        self.trigger(Action::Accept)
+    }
+
+    /// Applies the events of the running benchmarks and records the results once all are done
+    fn poll_benchmarks(&mut self) {
+        let Some(run) = self.benchmarks.as_mut() else {
+            return;
+        };
+        let Some(events) = &run.events else {
+            return;
+        };
+        loop {
+            match events.try_recv() {
+                Ok(event) => {
+                    if let Some((_, progress)) = run.devices.get_mut(event.device()) {
+                        progress.apply(event);
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => break,
+            }
         }
+        run.events = None;
+        self.finish_benchmarks();
     }
 
-    fn previous(&mut self) {
-        if self.currently_left {
-            self.items.previous();
-        } else if let Some(i) = self.items.state.selected() {
-            let device_items = &mut self.items.items.get_mut(i).unwrap().devices;
-            device_items.previous();
+    /// Stores the results of the completed run and reports failures and regressions
+    fn finish_benchmarks(&mut self) {
+        let Some(run) = &self.benchmarks else {
+            return;
+        };
+        let failures: usize = run
+            .devices
+            .iter()
+            .map(|(_, progress)| progress.failures.len())
+            .sum();
+        let recorded = match &self.benchmarks_path {
+            Some(path) => record_benchmarks(&run.devices, path),
+            None => Ok(Vec::new()),
+        };
+        let regressions = match recorded {
+            Ok(deltas) => deltas
+                .iter()
+                .filter(|delta| delta.is_regression(REGRESSION_TOLERANCE))
+                .count(),
+            Err(error) => {
+                self.notify_error(format!(
+                    "could not save the benchmarks: {}",
+                    error_message(&error)
+                ));
+                0
+            }
+        };
+        match (failures, regressions) {
+            (0, 0) => self.notify("benchmarks done"),
+            (0, regressions) => self.notify_error(format!(
+                "benchmarks done, {regressions} regressed by more than {:.0}%",
+                100.0 * REGRESSION_TOLERANCE
+            )),
+            (failures, _) => self.notify_error(format!("benchmarks done, {failures} failed")),
+        }
+    }
+
+    /// Keybindings which are valid in the current context
+    fn key_hints(&self) -> Vec<(&'static str, &'static str)> {
+        if self.confirm.is_some() {
+            return vec![("y", "confirm"), ("n/Esc", "cancel"), ("q", "abort")];
+        }
+        match self.wizard {
+            Some(WizardStep::Summary) => {
+                return vec![("Enter", "next"), ("Esc", "skip guide"), ("q", "abort")]
+            }
+            Some(WizardStep::Benchmarks) => {
+                let mut hints = vec![("Enter", "next"), ("Esc", "skip guide"), ("q", "abort")];
+                if self.benchmarks.is_none() {
+                    hints.insert(0, ("b", "run benchmarks"));
+                }
+                return hints;
+            }
+            Some(WizardStep::Recommend) => {
+                return vec![("y", "save"), ("n/Esc", "choose manually"), ("q", "abort")]
+            }
+            None => {}
+        }
+        if self.help_scroll.is_some() {
+            return vec![("↓↑", "scroll"), ("?/Esc", "close"), ("q", "abort")];
+        }
+        if self.summary_scroll.is_some() {
+            return vec![("↓↑", "scroll"), ("S/Esc", "close"), ("q", "abort")];
+        }
+        if self.show_compare {
+            return vec![("C/Esc", "close"), ("q", "abort")];
+        }
+        if self.history_state.is_some() {
+            return vec![
+                ("↓↑", "move"),
+                ("Enter", "apply"),
+                ("r/Esc", "close"),
+                ("q", "abort"),
+            ];
+        }
+        if self.hidden_state.is_some() {
+            return vec![
+                ("↓↑", "move"),
+                ("Enter", "unhide"),
+                ("D/Esc", "close"),
+                ("q", "abort"),
+            ];
+        }
+        if self.nickname_input.is_some() {
+            return vec![
+                ("type", "nickname"),
+                ("Enter", "save, empty removes"),
+                ("Esc", "cancel"),
+            ];
+        }
+        if self.note_input.is_some() {
+            return vec![
+                ("type", "note"),
+                ("Enter", "save, empty removes"),
+                ("Esc", "cancel"),
+            ];
+        }
+        if self.tag_input.is_some() {
+            return vec![
+                ("type", "group name"),
+                ("Enter", "tag/untag"),
+                ("Esc", "cancel"),
+            ];
+        }
+        if self.jump_pending {
+            return vec![("letter", "jump to entry"), ("Esc", "cancel")];
+        }
+        if self.held {
+            return vec![("↓↑", "move entry"), ("Space/Esc", "drop")];
+        }
+        if self.filter_mode {
+            return vec![
+                ("type", "filter devices"),
+                ("Enter", "apply"),
+                ("Esc", "clear"),
+            ];
+        }
+        match self.view {
+            View::Devices => {}
+            View::Priorities => {
+                return vec![
+                    ("↓↑", "move"),
+                    ("Space", "grab"),
+                    ("J/K", "scroll details"),
+                    ("b", "benchmark"),
+                    ("x", "clear priorities"),
+                    ("F1-F4/v", "switch view"),
+                    ("Esc", "devices"),
+                    ("Enter", "accept"),
+                    ("q", "abort"),
+                ]
+            }
+            View::Benchmarks => {
+                return vec![
+                    ("b", "benchmark"),
+                    ("↓↑", "scroll"),
+                    ("F1-F4/v", "switch view"),
+                    ("Esc", "devices"),
+                    ("q", "abort"),
+                ]
+            }
+            View::Diagnostics => {
+                return vec![
+                    ("↓↑", "scroll"),
+                    ("F1-F4/v", "switch view"),
+                    ("Esc", "devices"),
+                    ("q", "abort"),
+                ]
+            }
+        }
+        let mut hints = vec![
+            ("↓↑", "move"),
+            ("^d/^u", "half page"),
+            ("PgDn/PgUp", "page"),
+            ("g/G", "top/bottom"),
+        ];
+        match self.pane {
+            Pane::PLATFORMS => hints.extend([
+                ("→", "devices"),
+                ("0-9", "set platform priority"),
+                ("H/L/=", "resize/reset"),
+            ]),
+            Pane::DEVICES => {
+                hints.extend([
+                    ("←", "platforms"),
+                    ("0-9", "set priority"),
+                    ("s", "sort"),
+                    ("c/C", "mark/compare"),
+                    ("b", "benchmark"),
+                    ("t", "tag"),
+                    ("a", "nickname"),
+                    ("n", "note"),
+                    ("d/D", "hide/hidden"),
+                ]);
+                #[cfg(feature = "clipboard")]
+                hints.push(("y/Y", "copy info/name"));
+            }
+            Pane::Hierarchy(_) => hints.extend([("←/→", "up/down"), ("H/L/=", "resize/reset")]),
+            Pane::Priorities => hints.push(("Space", "grab")),
+        }
+        let missing = self
+            .items
+            .items
+            .iter()
+            .any(|platform| platform.devices.items.iter().any(|device| device.missing));
+        if missing {
+            hints.push(("P", "prune missing"));
         }
+        hints.extend([
+            ("J/K", "scroll details"),
+            ("Tab", "next pane"),
+            ("F1-F4/v", "switch view"),
+            ("/", "filter"),
+            ("f", "jump to letter"),
+            ("S", "summary"),
+            ("r", "history"),
+            ("?", "help"),
+            ("x", "clear priorities"),
+            ("R", "refresh"),
+            ("Enter", "accept"),
+            ("q", "abort"),
+        ]);
+        hints
     }
 }
 
 impl App {
-    fn run(&mut self, mut terminal: Terminal<impl Backend>) -> io::Result<()> {
+    /// Runs the App until the user accepts (`true`) or aborts (`false`) the selection
+    fn run(&mut self, terminal: &mut Terminal<impl Backend>) -> Result<bool> {
         loop {
-            self.draw(&mut terminal)?;
-
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    use KeyCode::*;
-                    match key.code {
-                        Char('q') | Esc => return Ok(()),
-                        Char('h') | Left => self.move_left(),
-                        Char('j') | Down => self.next(),
-                        Char('k') | Up => self.previous(),
-                        Char('l') | Right => self.move_right(),
-                        Char('g') => self.go_top(),
-                        Char('G') => self.go_bottom(),
-                        Char('H') => self.move_divider(-5),
-                        Char('L') => self.move_divider(5),
-                        Char('0') => self.set_priority(0),
-                        Char('1') => self.set_priority(1),
-                        Char('2') => self.set_priority(2),
-                        Char('3') => self.set_priority(3),
-                        Char('4') => self.set_priority(4),
-                        Char('5') => self.set_priority(5),
-                        Char('6') => self.set_priority(6),
-                        Char('7') => self.set_priority(7),
-                        Char('8') => self.set_priority(8),
-                        Char('9') => self.set_priority(9),
-                        _ => {}
-                    }
+            self.expire_toasts(Instant::now());
+            self.poll_benchmarks();
+            self.draw(terminal)?;
+
+            // Without input the App still wakes up regularly to dismiss expired toasts and
+            // update the progress of benchmarks
+            if !event::poll(TICK).map_err(DisplayError::Terminal)? {
+                continue;
+            }
+            if let Event::Key(key) = event::read().map_err(DisplayError::Terminal)? {
+                if let Some(accepted) = self.handle_key(key) {
+                    return Ok(accepted);
+                }
+            }
+        }
+    }
+
+    /// Reacts to a key event, returns whether the selection was accepted once the App is done
+    fn handle_key(&mut self, key: KeyEvent) -> Option<bool> {
+        let key = normalize_key_event(key)?;
+        use KeyCode::*;
+        if let Some(action) = self.confirm {
+            match key.code {
+                Char('q') => return Some(false),
+                Char('y') | Char('Y') if self.perform(action) => return Some(true),
+                Char('n') | Char('N') | Esc => self.confirm = None,
+                _ => {}
+            }
+        } else if let Some(step) = self.wizard {
+            match (step, key.code) {
+                (_, Char('q')) => return Some(false),
+                (_, Esc) => self.close_wizard(),
+                (WizardStep::Summary, Enter) => self.wizard = Some(WizardStep::Benchmarks),
+                (WizardStep::Benchmarks, Char('b')) if self.benchmarks.is_none() => {
+                    self.run_benchmarks(self.listed_devices())
+                }
+                (WizardStep::Benchmarks, Enter) => self.wizard = Some(WizardStep::Recommend),
+                (WizardStep::Recommend, Char('y')) => return self.accept_recommendation(),
+                (WizardStep::Recommend, Char('n')) => self.close_wizard(),
+                _ => {}
+            }
+        } else if let Some(nickname) = self.nickname_input.as_mut() {
+            match key.code {
+                Esc => self.nickname_input = None,
+                Enter => self.apply_nickname(),
+                Backspace => {
+                    nickname.pop();
+                }
+                Char(c) => nickname.push(c),
+                _ => {}
+            }
+        } else if let Some(note) = self.note_input.as_mut() {
+            match key.code {
+                Esc => self.note_input = None,
+                Enter => self.apply_note(),
+                Backspace => {
+                    note.pop();
+                }
+                Char(c) => note.push(c),
+                _ => {}
+            }
+        } else if let Some(group) = self.tag_input.as_mut() {
+            match key.code {
+                Esc => self.tag_input = None,
+                Enter => self.apply_tag(),
+                Backspace => {
+                    group.pop();
+                }
+                Char(c) => group.push(c),
+                _ => {}
+            }
+        } else if self.filter_mode {
+            match key.code {
+                Esc => self.clear_filter(),
+                Enter => self.filter_mode = false,
+                Backspace => {
+                    self.filter.pop();
+                    self.update_visible();
+                }
+                Char(c) => {
+                    self.filter.push(c);
+                    self.update_visible();
+                }
+                _ => {}
+            }
+        } else if let Some(scroll) = self.help_scroll {
+            match key.code {
+                Char('q') => return Some(false),
+                Char('?') | Esc => self.help_scroll = None,
+                Char('j') | Down => self.help_scroll = Some(scroll.saturating_add(1)),
+                Char('k') | Up => self.help_scroll = Some(scroll.saturating_sub(1)),
+                Char('g') => self.help_scroll = Some(0),
+                _ => {}
+            }
+        } else if self.summary_scroll.is_some() {
+            let scroll = self.summary_scroll.unwrap_or_default();
+            match key.code {
+                Char('q') => return Some(false),
+                Char('S') | Esc => self.summary_scroll = None,
+                Char('j') | Down => self.summary_scroll = Some(scroll.saturating_add(1)),
+                Char('k') | Up => self.summary_scroll = Some(scroll.saturating_sub(1)),
+                Char('g') => self.summary_scroll = Some(0),
+                _ => {}
+            }
+        } else if self.history_state.is_some() {
+            match key.code {
+                Char('q') => return Some(false),
+                Char('r') | Esc => self.history_state = None,
+                Char('j') | Down => self.move_history(1),
+                Char('k') | Up => self.move_history(-1),
+                Enter => {
+                    self.request(Confirm::ApplyHistory);
+                }
+                _ => {}
+            }
+        } else if self.show_compare {
+            match key.code {
+                Char('q') => return Some(false),
+                Char('C') | Esc => self.show_compare = false,
+                _ => {}
+            }
+        } else if self.hidden_state.is_some() {
+            match key.code {
+                Char('q') => return Some(false),
+                Char('D') | Esc => self.hidden_state = None,
+                Char('j') | Down => self.move_hidden(1),
+                Char('k') | Up => self.move_hidden(-1),
+                Enter => self.unhide_device(),
+                _ => {}
+            }
+        } else if self.view == View::Priorities {
+            match self.keymap.action(key) {
+                _ if key.code == Esc && self.held => self.held = false,
+                _ if key.code == Esc => self.show_view(View::Devices),
+                // The priorities are the only pane of the view
+                Some(Action::Left | Action::Right | Action::NextPane | Action::PreviousPane)
+                | None => {}
+                Some(action) => return self.trigger(action),
+            }
+        } else if self.view != View::Devices {
+            match (key.code, self.keymap.action(key)) {
+                (Char('q'), _) => return Some(false),
+                (Esc, _) => self.show_view(View::Devices),
+                (Char('b'), _) if self.view == View::Benchmarks => self.start_benchmarks(),
+                (Char('j') | Down, _) => self.view_scroll = self.view_scroll.saturating_add(1),
+                (Char('k') | Up, _) => self.view_scroll = self.view_scroll.saturating_sub(1),
+                (Char('g'), _) => self.view_scroll = 0,
+                (_, Some(action @ (Action::View(_) | Action::NextView | Action::Help))) => {
+                    self.trigger(action);
+                }
+                _ => {}
+            }
+        } else if self.jump_pending {
+            self.jump_pending = false;
+            if let Char(letter) = key.code {
+                self.jump(letter);
+            }
+        } else if self.held && key.code == Esc {
+            self.held = false;
+        } else if let Some(action) = self.keymap.action(key) {
+            return self.trigger(action);
+        }
+        None
+    }
+
+    /// Performs the action in the main view, returns whether the selection was accepted once
+    /// the App is done
+    fn trigger(&mut self, action: Action) -> Option<bool> {
+        // Any other action drops the grabbed priority where it is
+        if !matches!(action, Action::Down | Action::Up | Action::Grab) {
+            self.held = false;
+        }
+        match action {
+            Action::Abort => return Some(false),
+            Action::Accept if self.request(Confirm::Overwrite) => return Some(true),
+            Action::Accept => {}
+            Action::Down if self.held => self.move_held(1),
+            Action::Up if self.held => self.move_held(-1),
+            Action::Down => self.next(),
+            Action::Up => self.previous(),
+            Action::Left => self.move_left(),
+            Action::Right => self.move_right(),
+            Action::Top => self.go_top(),
+            Action::Bottom => self.go_bottom(),
+            Action::HalfPageDown => self.move_pages(0.5),
+            Action::HalfPageUp => self.move_pages(-0.5),
+            Action::PageDown => self.move_pages(1.0),
+            Action::PageUp => self.move_pages(-1.0),
+            Action::NextPane => self.next_pane(),
+            Action::PreviousPane => self.previous_pane(),
+            Action::Filter => self.filter_mode = true,
+            Action::Summary => self.summary_scroll = Some(0),
+            Action::Help => self.help_scroll = Some(0),
+            Action::History => self.open_history(),
+            Action::Sort if self.pane == Pane::DEVICES => self.cycle_sort(),
+            Action::ToggleCompare => self.toggle_compare(),
+            Action::OpenCompare => self.open_compare(),
+            #[cfg(feature = "clipboard")]
+            Action::CopyInfo if self.pane == Pane::DEVICES => self.yank(true),
+            #[cfg(feature = "clipboard")]
+            Action::CopyName if self.pane == Pane::DEVICES => self.yank(false),
+            Action::ScrollDetailsDown => {
+                self.details_scroll = self.details_scroll.saturating_add(1)
+            }
+            Action::ScrollDetailsUp => self.details_scroll = self.details_scroll.saturating_sub(1),
+            Action::ShrinkLeft => self.move_divider(-5),
+            Action::GrowLeft => self.move_divider(5),
+            Action::ResetDivider => self.divider_percentage = self.default_divider,
+            Action::ClearPriorities => {
+                self.request(Confirm::ClearPriorities);
+            }
+            Action::Grab if self.pane == Pane::Priorities => {
+                self.held = !self.held && self.priority_state.selected().is_some();
+            }
+            Action::Priority(n) => self.set_priority(usize::from(n)),
+            Action::Refresh => self.refresh(),
+            Action::PruneMissing => self.prune_missing(),
+            Action::Save => self.save_selection(),
+            Action::Tag if self.pane == Pane::DEVICES => self.tag_input = Some(String::new()),
+            Action::Nickname if self.pane == Pane::DEVICES => {
+                let nickname = self
+                    .currently_selected_device_index()
+                    .and_then(|index| self.get_device(&index))
+                    .and_then(|device| self.nicknames.nickname_of(&device.info));
+                self.nickname_input = Some(nickname.unwrap_or_default().to_string());
+            }
+            Action::Note if self.pane == Pane::DEVICES => {
+                let note = self
+                    .currently_selected_device_index()
+                    .and_then(|index| self.get_device(&index))
+                    .and_then(|device| self.notes.note_of(&device.info));
+                self.note_input = Some(note.unwrap_or_default().to_string());
+            }
+            Action::Jump if self.pane != Pane::Priorities => self.jump_pending = true,
+            Action::Benchmark => self.start_benchmarks(),
+            Action::Hide if self.pane == Pane::DEVICES => self.hide_device(),
+            Action::Hidden => self.open_hidden(),
+            Action::View(n) => {
+                if let Some(view) = usize::from(n).checked_sub(1).and_then(|i| View::ALL.get(i)) {
+                    self.show_view(*view);
                 }
             }
+            Action::NextView => {
+                let i = View::ALL.iter().position(|view| *view == self.view);
+                self.show_view(View::ALL[i.map_or(0, |i| (i + 1) % View::ALL.len())]);
+            }
+            Action::Sort
+            | Action::Grab
+            | Action::CopyInfo
+            | Action::CopyName
+            | Action::Tag
+            | Action::Nickname
+            | Action::Note
+            | Action::Jump
+            | Action::Hide => {}
         }
+        None
     }
 
-    fn draw(&mut self, terminal: &mut Terminal<impl Backend>) -> io::Result<()> {
-        terminal.draw(|f| f.render_widget(self, f.size()))?;
+    fn draw(&mut self, terminal: &mut Terminal<impl Backend>) -> Result<()> {
+        let unicode = self.unicode;
+        terminal
+            .draw(|f| {
+                f.render_widget(self, f.size());
+                if !unicode {
+                    ascii_fallback(f.buffer_mut());
+                }
+            })
+            .map_err(DisplayError::Terminal)?;
         Ok(())
     }
 }
@@ -245,307 +3057,1527 @@ impl Widget for &mut App {
         ]);
         let [left_platform_list, right_device_list] = vertical.areas(rest_area);
 
-        self.render_title(header_area, buf);
-        self.render_platforms(left_platform_list, buf);
-        self.render_devices(right_device_list, buf);
+        let [title_area, breadcrumb_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(header_area);
+        self.render_title(title_area, buf);
+        if let Some(step) = self.wizard {
+            let wizard_area = rest_area.union(priority_area);
+            self.render_wizard(step, wizard_area, buf);
+            self.render_footer(footer_area, buf);
+            self.render_overlays(area, buf);
+            return;
+        }
+        if let Some(scroll) = self.summary_scroll {
+            let summary_area = rest_area.union(priority_area);
+            self.render_summary(summary_area, scroll, buf);
+            self.render_footer(footer_area, buf);
+            self.render_overlays(area, buf);
+            return;
+        }
+        if self.history_state.is_some() {
+            let history_area = rest_area.union(priority_area);
+            self.render_history(history_area, buf);
+            self.render_footer(footer_area, buf);
+            self.render_overlays(area, buf);
+            return;
+        }
+        if self.show_compare {
+            let compare_area = rest_area.union(priority_area);
+            self.render_compare(compare_area, buf);
+            self.render_footer(footer_area, buf);
+            self.render_overlays(area, buf);
+            return;
+        }
+        if self.hidden_state.is_some() {
+            let hidden_area = rest_area.union(priority_area);
+            self.render_hidden(hidden_area, buf);
+            self.render_footer(footer_area, buf);
+            self.render_overlays(area, buf);
+            return;
+        }
+        if self.view != View::Devices {
+            let view_area = rest_area.union(priority_area);
+            match self.view {
+                View::Devices => {}
+                View::Priorities => self.render_priorities(view_area, buf),
+                View::Benchmarks => self.render_benchmarks(view_area, buf),
+                View::Diagnostics => self.render_diagnostics(view_area, buf),
+            }
+            self.render_footer(footer_area, buf);
+            self.render_overlays(area, buf);
+            return;
+        }
+        // Without platforms there is nothing to navigate, so the explanation gets all the space
+        if self.items.len() == 0 {
+            let body_area = rest_area.union(priority_area);
+            self.render_level(0, 0, body_area, buf);
+            self.render_footer(footer_area, buf);
+            self.render_overlays(area, buf);
+            return;
+        }
+        let [left, right] = self.columns();
+        self.render_breadcrumb(breadcrumb_area, buf);
+        self.render_level(left, 0, left_platform_list, buf);
+        self.render_level(right, 1, right_device_list, buf);
+        let horizontal = Layout::horizontal([
+            Constraint::Percentage(self.divider_percentage),
+            Constraint::Percentage(100 - self.divider_percentage),
+        ]);
+        let [priority_area, details_area] = horizontal.areas(priority_area);
+
         self.render_priority_list(priority_area, buf);
+        self.render_details(details_area, buf);
         self.render_footer(footer_area, buf);
+        self.render_overlays(area, buf);
     }
 }
 
 impl App {
+    /// Name of the App followed by the tab bar of the views
     fn render_title(&self, area: Rect, buf: &mut Buffer) {
-        Paragraph::new("opencl3_select")
+        let title = "opencl3_select";
+        let [title_area, tabs_area] = Layout::horizontal([
+            Constraint::Length(title.len() as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+        Paragraph::new(title)
             .bold()
             .centered()
-            .render(area, buf);
+            .render(title_area, buf);
+        let titles = View::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, view)| format!("F{} {}", i + 1, self.locale.tr(view.title())));
+        let selected = View::ALL.iter().position(|view| *view == self.view);
+        Tabs::new(titles)
+            .select(selected.unwrap_or(0))
+            .fg(self.theme.text)
+            .highlight_style(Style::default().fg(self.theme.selected_fg).bold())
+            .render(tabs_area, buf);
     }
 
-    fn get_fg_style(&self, is_left: bool) -> Style {
-        if self.currently_left == is_left {
+    fn get_fg_style(&self, pane: Pane) -> Style {
+        if self.pane == pane {
             Style::default()
                 .add_modifier(Modifier::BOLD)
                 .add_modifier(Modifier::REVERSED)
-                .fg(SELECTED_STYLE_FG)
+                .fg(self.theme.selected_fg)
         } else {
             Style::default()
                 .add_modifier(Modifier::BOLD)
                 .add_modifier(Modifier::REVERSED)
-                .fg(SELECTED_STYLE_FG_LIGHT)
+                .fg(self.theme.selected_fg_light)
         }
     }
 
-    fn render_platforms(&mut self, area: Rect, buf: &mut Buffer) {
+    /// Renders the level of the hierarchy at the given depth into the given column
+    fn render_level(&mut self, depth: usize, column: usize, area: Rect, buf: &mut Buffer) {
+        let marks = Marks {
+            compare: &self.compare,
+            priorities: &self.priority_list,
+            nicknames: &self.nicknames,
+        };
+        let title = self
+            .level(depth)
+            .map_or(String::new(), |level| level.heading(&marks, self.locale));
         // We create two blocks, one is for the header (outer) and the other is for list (inner).
         let outer_block = Block::default()
             .borders(Borders::NONE)
-            .fg(TEXT_COLOR)
-            .bg(HEADER_BG)
-            .title("Platforms")
+            .fg(self.theme.text)
+            .bg(self.theme.header_bg)
+            .title(title)
             .title_alignment(Alignment::Center);
         let inner_block = Block::default()
             .borders(Borders::NONE)
-            .fg(TEXT_COLOR)
-            .bg(NORMAL_ROW_COLOR);
+            .fg(self.theme.text)
+            .bg(self.theme.normal_row);
 
         // We get the inner area from outer_block. We'll use this area later to render the table.
         let outer_area = area;
         let inner_area = outer_block.inner(outer_area);
+        self.list_heights[column] = inner_area.height;
 
         // We can render the header in outer_area.
         outer_block.render(outer_area, buf);
 
-        // Iterate through all elements in the `items` and stylize them.
-        let items: Vec<ListItem> = self
-            .items
-            .items
-            .iter()
-            .enumerate()
-            .map(|(i, platform_info)| platform_info.to_list_item(i))
-            .collect();
+        let Some(level) = self.level(depth) else {
+            inner_block.render(inner_area, buf);
+            return;
+        };
+        if level.len() == 0 {
+            Paragraph::new(level.empty_message(self.locale))
+                .wrap(Wrap { trim: false })
+                .block(inner_block)
+                .render(inner_area, buf);
+            return;
+        }
+        let item_height = usize::from(level.item_height());
+        let length = level.len() * item_height;
 
         // Create a List from all list items and highlight the currently selected one
-        let style = self.get_fg_style(true);
-        let items = List::new(items)
+        let marks = Marks {
+            compare: &self.compare,
+            priorities: &self.priority_list,
+            nicknames: &self.nicknames,
+        };
+        let items = List::new(level.list_items(&self.theme, &marks))
             .block(inner_block)
-            .highlight_style(style)
+            .highlight_style(self.get_fg_style(Pane::Hierarchy(depth)))
             .highlight_symbol(">")
             .highlight_spacing(HighlightSpacing::Always);
 
         // We can now render the item list
         // (look careful we are using StatefulWidget's render.)
         // ratatui::widgets::StatefulWidget::render as stateful_render
-        StatefulWidget::render(items, inner_area, buf, &mut self.items.state);
+        // The items borrow from the level, so its state is updated after rendering.
+        let mut state = level.state().clone();
+        StatefulWidget::render(items, inner_area, buf, &mut state);
+        let position = state.selected().unwrap_or(0) * item_height;
+        if let Some(level) = self.level_mut(depth) {
+            *level.state_mut() = state;
+        }
+        self.render_scrollbar(inner_area, length, position, buf);
+    }
+
+    /// Renders the path from the outermost level to the highlighted entry of the focused level
+    fn render_breadcrumb(&self, area: Rect, buf: &mut Buffer) {
+        let depth = match self.pane {
+            Pane::Hierarchy(depth) => depth,
+            Pane::Priorities => self.columns()[1],
+        };
+        let mut path = vec![Span::raw(self.locale.tr(self.items.title()))];
+        for depth in 0..=depth {
+            let Some(name) = self
+                .level(depth)
+                .and_then(|level| level.name(level.state().selected()?))
+            else {
+                break;
+            };
+            path.extend([Span::raw(" › "), Span::raw(name)]);
+        }
+        if let Some(last) = path.last_mut() {
+            *last = last.clone().bold();
+        }
+        Paragraph::new(Line::from(path))
+            .centered()
+            .fg(self.theme.text)
+            .render(area, buf);
     }
 
-    fn render_devices(&mut self, area: Rect, buf: &mut Buffer) {
+    fn render_priority_list(&mut self, area: Rect, buf: &mut Buffer) {
         let outer_block = Block::default()
             .borders(Borders::NONE)
-            .fg(TEXT_COLOR)
-            .bg(HEADER_BG)
-            .title("Devices")
+            .fg(self.theme.text)
+            .bg(self.theme.header_bg)
+            .title(self.locale.tr("Priorities"))
             .title_alignment(Alignment::Center);
         let inner_block = Block::default()
             .borders(Borders::NONE)
-            .fg(TEXT_COLOR)
-            .bg(NORMAL_ROW_COLOR);
+            .fg(self.theme.text)
+            .bg(self.theme.normal_row);
 
         let outer_area = area;
         let inner_area = outer_block.inner(outer_area);
+        self.list_heights[2] = inner_area.height;
         outer_block.render(outer_area, buf);
 
         // Find index of platform
-        if let Some(si) = self.items.state.selected() {
+        if self.items.state.selected().is_some() {
             // Obtain all devices under platform
-            let style = self.get_fg_style(false);
-            let current_devices = &mut self.items.items.get_mut(si).unwrap();
-            let items: Vec<ListItem> = current_devices
-                .devices
-                .items
-                .iter()
+            let (style, symbol) = match self.held {
+                true => (
+                    Style::default()
+                        .add_modifier(Modifier::BOLD)
+                        .add_modifier(Modifier::REVERSED)
+                        .fg(self.theme.warning),
+                    "↕",
+                ),
+                false => (self.get_fg_style(Pane::Priorities), ">"),
+            };
+            let priority_list = self.priority_list.view_priority_list();
+            let items: Vec<ListItem> = priority_list
+                .into_iter()
                 .enumerate()
-                .map(|(i, device)| device.info.to_list_item(i))
+                .filter_map(|(i, device_index)| {
+                    let marked = self.compare.contains(device_index);
+                    self.get_device(device_index).map(|device| {
+                        let nickname = self.nicknames.nickname_of(&device.info);
+                        device.to_list_item(i, marked, Some(i + 1), nickname, &self.theme)
+                    })
+                })
                 .collect();
             let items = List::new(items)
                 .block(inner_block)
                 .highlight_style(style)
-                .highlight_symbol(">")
+                .highlight_symbol(symbol)
                 .highlight_spacing(HighlightSpacing::Always);
 
-            StatefulWidget::render(items, inner_area, buf, &mut current_devices.devices.state);
+            // The items borrow from the app, so its state is updated after rendering
+            let mut state = self.priority_state.clone();
+            StatefulWidget::render(items, inner_area, buf, &mut state);
+            self.priority_state = state;
         }
     }
 
-    fn render_priority_list(&mut self, area: Rect, buf: &mut Buffer) {
+    fn render_details(&mut self, area: Rect, buf: &mut Buffer) {
         let outer_block = Block::default()
             .borders(Borders::NONE)
-            .fg(TEXT_COLOR)
-            .bg(HEADER_BG)
-            .title("Devices")
+            .fg(self.theme.text)
+            .bg(self.theme.header_bg)
+            .title(self.locale.tr("Details"))
             .title_alignment(Alignment::Center);
         let inner_block = Block::default()
             .borders(Borders::NONE)
-            .fg(TEXT_COLOR)
-            .bg(NORMAL_ROW_COLOR);
+            .fg(self.theme.text)
+            .bg(self.theme.normal_row);
 
         let outer_area = area;
         let inner_area = outer_block.inner(outer_area);
         outer_block.render(outer_area, buf);
 
-        // Find index of platform
-        if let Some(si) = self.items.state.selected() {
-            // Obtain all devices under platform
-            let style = self.get_fg_style(false);
-            let priority_list = self.priority_list.view_priority_list();
-            let items: Vec<ListItem> = priority_list
-                .into_iter()
-                .enumerate()
-                .filter_map(|(i, device_index)| {
-                    self.get_device(device_index)
-                        .and_then(|device| Some(device.info.to_list_item(i)))
-                })
-                .collect();
-            let items = List::new(items)
-                .block(inner_block)
-                .highlight_style(style)
-                .highlight_symbol(">")
-                .highlight_spacing(HighlightSpacing::Always);
+        let Some(device) = self
+            .details_device_index()
+            .and_then(|index| self.get_device(&index))
+        else {
+            return;
+        };
+
+        // Every section starts with a bold title row followed by its properties
+        let mut rows = vec![];
+        let mut sections = device.sections();
+        if let Some((_, properties)) = sections.first_mut() {
+            properties.push(("Groups", self.groups.groups_of(&device.info).join(" ")));
+            if let Some(note) = self.notes.note_of(&device.info) {
+                properties.insert(0, ("Note", note.to_string()));
+            }
+            if let Some(nickname) = self.nicknames.nickname_of(&device.info) {
+                properties.insert(0, ("Nickname", nickname.to_string()));
+            }
+        }
+        for (section, properties) in sections {
+            rows.push(Row::new([Cell::from(section).bold()]).bg(self.theme.header_bg));
+            rows.extend(
+                properties
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (name, value))| {
+                        let bg_color = match i % 2 {
+                            0 => self.theme.normal_row,
+                            _ => self.theme.alt_row,
+                        };
+                        Row::new([Cell::from(format!("  {name}")), Cell::from(value)]).bg(bg_color)
+                    }),
+            );
+        }
+        // Keep the last property at the bottom of the pane when scrolling down
+        let length = rows.len();
+        let offset = self
+            .details_scroll
+            .min(length.saturating_sub(inner_area.height as usize));
+        self.details_scroll = offset;
+        let rows = rows.into_iter().skip(offset);
+        let table =
+            Table::new(rows, [Constraint::Length(26), Constraint::Min(0)]).block(inner_block);
+        Widget::render(table, inner_area, buf);
+        self.render_scrollbar(inner_area, length, offset, buf);
+    }
 
-            ratatui::widgets::Widget::render(items, inner_area, buf);
+    /// Draws a vertical scrollbar at the right edge of the area if the content does not fit
+    ///
+    /// Length and position are given in lines.
+    fn render_scrollbar(&self, area: Rect, length: usize, position: usize, buf: &mut Buffer) {
+        let viewport = area.height as usize;
+        if length <= viewport {
+            return;
         }
+        let mut state = ScrollbarState::new(length.saturating_sub(viewport))
+            .viewport_content_length(viewport)
+            .position(position.min(length.saturating_sub(viewport)));
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .track_style(Style::default().fg(self.theme.alt_row))
+            .thumb_style(Style::default().fg(self.theme.selected_fg_light))
+            .render(area, buf, &mut state);
     }
 
-    fn render_footer(&self, area: Rect, buf: &mut Buffer) {
-        Paragraph::new(
-            "\nUse ↓↑ to move, ← to unselect, → to change status, g/G to go top/bottom.",
+    /// Shows the properties of both marked devices in aligned columns
+    ///
+    /// Properties which differ between the devices are highlighted.
+    fn render_compare(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::NONE)
+            .fg(self.theme.text)
+            .bg(self.theme.normal_row)
+            .title(self.locale.tr("Compare"))
+            .title_alignment(Alignment::Center);
+        let [Some(left), Some(right)] =
+            [0, 1].map(|i| self.compare.get(i).and_then(|index| self.get_device(index)))
+        else {
+            block.render(area, buf);
+            return;
+        };
+
+        let mut rows = vec![Row::new([
+            Cell::from(""),
+            Cell::from(left.info.name_ref()).bold(),
+            Cell::from(right.info.name_ref()).bold(),
+        ])
+        .bg(self.theme.header_bg)];
+        for ((section, left), (_, right)) in left.sections().into_iter().zip(right.sections()) {
+            rows.push(Row::new([Cell::from(section).bold()]).bg(self.theme.header_bg));
+            for (i, ((name, left), (_, right))) in left.into_iter().zip(right).enumerate() {
+                let bg_color = match i % 2 {
+                    0 => self.theme.normal_row,
+                    _ => self.theme.alt_row,
+                };
+                let row = Row::new([
+                    Cell::from(format!("  {name}")),
+                    Cell::from(left.clone()),
+                    Cell::from(right.clone()),
+                ])
+                .bg(bg_color);
+                rows.push(match left == right {
+                    true => row,
+                    false => row.fg(self.theme.selected_fg).bold(),
+                });
+            }
+        }
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(26),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+            ],
         )
-        .centered()
-        .render(area, buf);
+        .block(block);
+        Widget::render(table, area, buf);
+    }
+
+    /// Shows a progress bar per benchmarked device together with the running benchmark, or the
+    /// results and failures once the device is done
+    fn render_benchmarks(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::NONE)
+            .fg(self.theme.text)
+            .bg(self.theme.normal_row)
+            .title(self.locale.tr("Benchmarks"))
+            .title_alignment(Alignment::Center);
+        let Some(run) = &self.benchmarks else {
+            Paragraph::new(self.locale.tr(
+                "No benchmarks were run yet, press b to benchmark the devices with a priority",
+            ))
+            .block(block)
+            .render(area, buf);
+            return;
+        };
+        Paragraph::new(self.benchmark_lines(run))
+            .block(block)
+            .scroll((self.view_scroll, 0))
+            .render(area, buf);
+    }
+
+    /// Shows the devices with a priority next to the details of the highlighted one
+    fn render_priorities(&mut self, area: Rect, buf: &mut Buffer) {
+        let [list_area, details_area] = Layout::horizontal([
+            Constraint::Percentage(self.divider_percentage),
+            Constraint::Percentage(100 - self.divider_percentage),
+        ])
+        .areas(area);
+        self.render_priority_list(list_area, buf);
+        self.render_details(details_area, buf);
+    }
+
+    /// Shows how long every platform and device took to enumerate and which platforms failed
+    fn render_diagnostics(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::NONE)
+            .fg(self.theme.text)
+            .bg(self.theme.normal_row)
+            .title(self.locale.tr("Diagnostics"))
+            .title_alignment(Alignment::Center);
+        Paragraph::new(self.diagnostics_lines())
+            .block(block)
+            .scroll((self.view_scroll, 0))
+            .render(area, buf);
+    }
+
+    /// Enumeration timings with slow platforms and devices flagged, followed by the errors
+    fn diagnostics_lines(&self) -> Vec<Line<'static>> {
+        let slow = Duration::from_millis(SLOW_ENUMERATION_MS);
+        let flag = |duration: Duration| match duration > slow {
+            true => Span::raw("  slow").fg(self.theme.warning),
+            false => Span::raw(""),
+        };
+        let timings = self.cl_state.enumeration_timings();
+        let errors = self.cl_state.enumeration_errors();
+        let mut lines = vec![Line::raw(self.locale.tr("Enumeration timings")).bold()];
+        for platform in timings {
+            lines.push(Line::from(vec![
+                Span::raw(format!(
+                    "  {:<40} {:>12.3?}",
+                    platform.name, platform.duration
+                )),
+                flag(platform.duration),
+            ]));
+            for device in platform.devices.iter() {
+                lines.push(Line::from(vec![
+                    Span::raw(format!(
+                        "    {:<38} {:>12.3?}",
+                        device.name, device.duration
+                    )),
+                    flag(device.duration),
+                ]));
+            }
+        }
+        if timings.is_empty() {
+            lines.push(Line::raw(format!(
+                "  {}",
+                self.locale
+                    .tr("none recorded, the setup was not enumerated by this run")
+            )));
+        }
+        for error in errors {
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {:<40} ", error.platform)),
+                Span::raw(error.message.clone()).fg(self.theme.error),
+            ]));
+        }
+        let n_slow = timings
+            .iter()
+            .filter(|platform| platform.duration > slow)
+            .count()
+            + errors.len();
+        lines.push(Line::raw(""));
+        lines.push(match n_slow {
+            0 => Line::raw(self.locale.format(
+                "No platform took longer than {slow}",
+                &[("slow", &format!("{slow:?}"))],
+            )),
+            n => Line::raw(self.locale.format(
+                "{count} platform(s) took longer than {slow} and slow down every OpenCL application",
+                &[("count", &n.to_string()), ("slow", &format!("{slow:?}"))],
+            ))
+            .fg(self.theme.warning),
+        });
+        lines
+    }
+
+    /// Progress bar, running benchmark, results and failures of every benchmarked device
+    fn benchmark_lines(&self, run: &BenchmarkRun) -> Vec<Line<'static>> {
+        let width = run
+            .devices
+            .iter()
+            .map(|(device, _)| device.name_ref().chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut lines = vec![];
+        for (device, progress) in &run.devices {
+            let status = match &progress.current {
+                Some(benchmark) => format!("running {benchmark}"),
+                None if progress.is_done() => "done".to_string(),
+                None => "waiting".to_string(),
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!("{:width$} ", device.name_ref())).bold(),
+                Span::raw(progress.gauge(20)).fg(self.theme.selected_fg),
+                Span::raw(format!(" {status}")),
+            ]));
+            for result in &progress.results {
+                lines.push(Line::raw(format!(
+                    "  {}: {:.2} {}",
+                    result.name, result.value, result.unit
+                )));
+            }
+            for (benchmark, message) in &progress.failures {
+                lines.push(
+                    Line::raw(format!("  {benchmark} failed: {message}")).fg(self.theme.error),
+                );
+            }
+        }
+        lines
+    }
+
+    /// Shows the current step of the first-run guide
+    fn render_wizard(&self, step: WizardStep, area: Rect, buf: &mut Buffer) {
+        let tr = |message| self.locale.tr(message);
+        let title = match step {
+            WizardStep::Summary => tr("Welcome (1/3)"),
+            WizardStep::Benchmarks => tr("Benchmarks (2/3)"),
+            WizardStep::Recommend => tr("Recommendation (3/3)"),
+        };
+        let block = Block::default()
+            .borders(Borders::NONE)
+            .fg(self.theme.text)
+            .bg(self.theme.normal_row)
+            .title(title)
+            .title_alignment(Alignment::Center);
+        let mut lines = vec![];
+        match step {
+            WizardStep::Summary => {
+                lines.push(Line::raw(tr(
+                    "No device was selected on this machine yet. This guide recommends one for \
+                     the setup below.",
+                )));
+                lines.push(Line::default());
+                let summary = self.summary.to_string();
+                lines.extend(summary.lines().map(|line| Line::raw(line.to_string())));
+            }
+            WizardStep::Benchmarks => {
+                lines.push(Line::raw(tr(
+                    "Quick benchmarks take a few seconds per device and let the recommendation \
+                     prefer the fastest one.",
+                )));
+                lines.push(Line::default());
+                match &self.benchmarks {
+                    Some(run) => lines.extend(self.benchmark_lines(run)),
+                    None => lines.push(Line::raw(tr("Press b to run them or Enter to skip them."))),
+                }
+            }
+            WizardStep::Recommend => match self.recommendation() {
+                Some((device, reason)) => {
+                    lines.push(Line::from(vec![
+                        Span::raw(tr("Recommended: ")),
+                        Span::raw(device.name()).bold(),
+                        Span::raw(
+                            self.locale
+                                .format(" on {platform}", &[("platform", device.platform_ref())]),
+                        ),
+                    ]));
+                    lines.push(Line::raw(
+                        self.locale
+                            .format("Chosen because {reason}.", &[("reason", &reason)]),
+                    ));
+                    lines.push(Line::default());
+                    lines.push(Line::raw(tr(
+                        "Save it as your selection? It is used by auto and preselected from now \
+                         on.",
+                    )));
+                }
+                None => lines.push(Line::raw(tr(
+                    "No device was found, press n to look at the platforms.",
+                ))),
+            },
+        }
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(area, buf);
+    }
+
+    /// Lists the hidden devices, including those which are currently not present
+    fn render_hidden(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::NONE)
+            .fg(self.theme.text)
+            .bg(self.theme.normal_row)
+            .title(self.locale.tr("Hidden"))
+            .title_alignment(Alignment::Center);
+        let items: Vec<ListItem> = self
+            .hidden
+            .devices
+            .iter()
+            .enumerate()
+            .map(|(i, device)| {
+                let bg_color = match i % 2 {
+                    0 => self.theme.normal_row,
+                    _ => self.theme.alt_row,
+                };
+                let mut text = Text::default();
+                text.extend([
+                    Span::raw(device.name.clone()).bold(),
+                    Span::raw(format!("{}, {}", device.platform, device.version)),
+                ]);
+                ListItem::new(text).bg(bg_color)
+            })
+            .collect();
+        let style = self.get_fg_style(self.pane);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(style)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        if let Some(state) = self.hidden_state.as_mut() {
+            StatefulWidget::render(list, area, buf, state);
+        }
+    }
+
+    fn render_history(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::NONE)
+            .fg(self.theme.text)
+            .bg(self.theme.normal_row)
+            .title(self.locale.tr("History"))
+            .title_alignment(Alignment::Center);
+        let items: Vec<ListItem> = self
+            .history
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let bg_color = match i % 2 {
+                    0 => self.theme.normal_row,
+                    _ => self.theme.alt_row,
+                };
+                let devices = entry
+                    .selection
+                    .view_priority_list()
+                    .into_iter()
+                    .map(|device| device.name())
+                    .collect::<Vec<_>>()
+                    .join(" > ");
+                let mut text = Text::default();
+                text.extend([
+                    Span::raw(format_timestamp(entry.timestamp)).bold(),
+                    Span::raw(devices),
+                ]);
+                ListItem::new(text).bg(bg_color)
+            })
+            .collect();
+        let style = self.get_fg_style(self.pane);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(style)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        if let Some(state) = self.history_state.as_mut() {
+            StatefulWidget::render(list, area, buf, state);
+        }
+    }
+
+    fn render_summary(&self, area: Rect, scroll: u16, buf: &mut Buffer) {
+        let block = Block::default()
+            .borders(Borders::NONE)
+            .fg(self.theme.text)
+            .bg(self.theme.normal_row)
+            .title(self.locale.tr("Summary"))
+            .title_alignment(Alignment::Center);
+        Paragraph::new(self.summary.to_string())
+            .block(block)
+            .scroll((scroll, 0))
+            .render(area, buf);
+    }
+
+    /// Shows the pending [Confirm] action as a popup in the center of the screen
+    /// Draws the toasts and the confirmation popup on top of everything else
+    fn render_overlays(&self, area: Rect, buf: &mut Buffer) {
+        self.render_help(area, buf);
+        self.render_toasts(area, buf);
+        self.render_note_input(area, buf);
+        self.render_confirm(area, buf);
+    }
+
+    /// Lists the keys of every action as a popup in the center of the screen
+    ///
+    /// Taken from the [KeyMap] such that keys remapped in the config are shown as well.
+    fn render_help(&self, area: Rect, buf: &mut Buffer) {
+        let Some(scroll) = self.help_scroll else {
+            return;
+        };
+        let bindings: Vec<(String, String)> = self
+            .keymap
+            .bound_actions()
+            .into_iter()
+            .map(|(action, keys)| {
+                let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+                (keys.join(", "), action.to_string().replace('_', " "))
+            })
+            .collect();
+        let keys_width = bindings
+            .iter()
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = bindings
+            .into_iter()
+            .map(|(keys, action)| {
+                Line::from(vec![
+                    Span::raw(format!(" {keys:>keys_width$}  ")).bold(),
+                    Span::raw(action),
+                ])
+            })
+            .collect();
+        let width = 50.min(area.width);
+        let height = area.height.saturating_sub(4).min(lines.len() as u16 + 2);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .fg(self.theme.text)
+            .bg(self.theme.header_bg)
+            .title(self.locale.tr("Help"))
+            .title_alignment(Alignment::Center);
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .scroll((scroll, 0))
+            .block(block)
+            .render(popup, buf);
+    }
+
+    /// Shows the note being edited as a popup in the center of the screen
+    fn render_note_input(&self, area: Rect, buf: &mut Buffer) {
+        let Some(note) = &self.note_input else {
+            return;
+        };
+        let width = 60.min(area.width);
+        let height = 5.min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        let title = match self
+            .currently_selected_device_index()
+            .and_then(|index| self.get_device(&index))
+        {
+            Some(device) => format!("{} · {}", self.locale.tr("Note"), device.info.name_ref()),
+            None => self.locale.tr("Note").to_string(),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .fg(self.theme.text)
+            .bg(self.theme.header_bg)
+            .title(title)
+            .title_alignment(Alignment::Center);
+        Clear.render(popup, buf);
+        Paragraph::new(format!("{note}▏"))
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(popup, buf);
+    }
+
+    /// Stacks the toasts in the lower right corner, above the footer, newest at the bottom
+    fn render_toasts(&self, area: Rect, buf: &mut Buffer) {
+        let mut bottom = area.bottom().saturating_sub(2);
+        for toast in self.toasts.iter().rev() {
+            if bottom < area.y + 3 {
+                break;
+            }
+            let width = (toast.text.chars().count() as u16 + 4).min(area.width);
+            let popup = Rect::new(area.right() - width, bottom - 3, width, 3);
+            let border = if toast.error {
+                self.theme.error
+            } else {
+                self.theme.text
+            };
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border))
+                .bg(self.theme.header_bg);
+            Clear.render(popup, buf);
+            Paragraph::new(toast.text.as_str())
+                .fg(self.theme.text)
+                .block(block)
+                .render(popup, buf);
+            bottom -= 3;
+        }
+    }
+
+    fn render_confirm(&self, area: Rect, buf: &mut Buffer) {
+        let Some(action) = self.confirm else {
+            return;
+        };
+        let question = self.locale.tr(action.question());
+        let width = (question.len() as u16 + 4).min(area.width);
+        let height = 4.min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .fg(self.theme.text)
+            .bg(self.theme.header_bg)
+            .title(self.locale.tr("Confirm"))
+            .title_alignment(Alignment::Center);
+        Clear.render(popup, buf);
+        Paragraph::new(vec![Line::from(question), Line::from("y / n").bold()])
+            .centered()
+            .block(block)
+            .render(popup, buf);
+    }
+
+    fn render_footer(&self, area: Rect, buf: &mut Buffer) {
+        let [status_area, hints_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(area);
+
+        // Current pane, filter and number of devices with a priority
+        let pane = match self.pane {
+            _ if self.wizard.is_some() => "Welcome",
+            _ if self.help_scroll.is_some() => "Help",
+            _ if self.summary_scroll.is_some() => "Summary",
+            _ if self.show_compare => "Compare",
+            _ if self.history_state.is_some() => "History",
+            _ if self.hidden_state.is_some() => "Hidden",
+            _ if self.view == View::Benchmarks => "Benchmarks",
+            _ if self.view == View::Diagnostics => "Diagnostics",
+            Pane::Hierarchy(depth) => self.level(depth).map_or("", |level| level.title()),
+            Pane::Priorities => "Priorities",
+        };
+        let pane = if self.confirm.is_some() {
+            "Confirm"
+        } else {
+            pane
+        };
+        let tr = |message| self.locale.tr(message);
+        let mut status = vec![Span::raw(format!(" {} ", tr(pane))).bold().reversed()];
+        if self.filter_mode || !self.filter.is_empty() {
+            let cursor = if self.filter_mode { "▏" } else { "" };
+            status.push(Span::raw(format!(
+                " {}: {}{cursor}",
+                tr("Filter"),
+                self.filter
+            )));
+        }
+        if let Some(group) = &self.tag_input {
+            status.push(Span::raw(format!(" {}: {group}▏", tr("Tag"))));
+        }
+        if let Some(nickname) = &self.nickname_input {
+            status.push(Span::raw(format!(" {}: {nickname}▏", tr("Nickname"))));
+        }
+        if self.jump_pending {
+            status.push(Span::raw(format!(" {}: ▏", tr("Jump to"))));
+        }
+        status.push(Span::raw(format!(" {}: {}", tr("Sort"), self.sort)));
+        status.push(Span::raw(format!(
+            " {}",
+            self.locale.format(
+                "{count} selected",
+                &[("count", &self.selected_count().to_string())]
+            )
+        )));
+        Paragraph::new(Line::from(status))
+            .fg(self.theme.text)
+            .bg(self.theme.header_bg)
+            .render(status_area, buf);
+
+        let hints = self
+            .key_hints()
+            .into_iter()
+            .map(|(key, action)| format!("{key} {}", self.locale.tr(action)))
+            .collect::<Vec<_>>()
+            .join(" · ");
+        Paragraph::new(hints).centered().render(hints_area, buf);
     }
 }
 
 impl PlatformList {
-    fn from_platforms(platforms: &Vec<clinfo::PlatformInfo>) -> PlatformList {
+    fn from_platforms(platforms: &[PlatformInfo]) -> PlatformList {
         let mut state = ListState::default();
         let items: Vec<_> = platforms
-            .clone()
-            .into_iter()
-            .map(|platform_info| {
-                let items: Vec<_> = platform_info
-                    .devices()
-                    .into_iter()
-                    .map(|info| DeviceItem { info })
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(platform, platform_info)| {
+                let devices = platform_info.devices_ref();
+                #[cfg(feature = "sysinfo")]
+                let usage = opencl3_select::device_usage(devices);
+                #[cfg(not(feature = "sysinfo"))]
+                let usage = vec![None; devices.len()];
+                let items: Vec<_> = devices
+                    .iter()
+                    .cloned()
+                    .zip(usage)
+                    .map(|(info, usage)| DeviceItem {
+                        info,
+                        usage,
+                        missing: false,
+                    })
                     .collect();
                 let mut state = ListState::default();
-                if items.len() > 0 {
+                if !items.is_empty() {
                     state.select(Some(0));
                 }
+                let visible = (0..items.len()).collect();
 
                 PlatformItem {
                     info: platform_info,
-                    devices: DeviceList { state, items },
+                    devices: DeviceList {
+                        platform,
+                        state,
+                        items,
+                        visible,
+                    },
                 }
             })
             .collect();
-        if items.len() > 0 {
+        if !items.is_empty() {
             state.select(Some(0));
         }
-        PlatformList { state, items }
+        PlatformList {
+            state,
+            items,
+            priorities: UniquePriorityList::new(),
+        }
     }
 
-    fn next(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
+    /// Lists a device which is no longer present below its platform, see [DeviceItem::missing]
+    ///
+    /// The platform is listed again as well if it disappeared together with the device.
+    fn push_missing(&mut self, info: DeviceInfo) {
+        let platform = match self
+            .items
+            .iter()
+            .position(|platform| platform.info.name_ref() == info.platform_ref())
+        {
+            Some(platform) => platform,
+            None => {
+                let platform = self.items.len();
+                self.items.push(PlatformItem {
+                    info: PlatformInfo::builder().name(info.platform_ref()).build(),
+                    devices: DeviceList {
+                        platform,
+                        state: ListState::default(),
+                        items: Vec::new(),
+                        visible: Vec::new(),
+                    },
+                });
+                platform
             }
-            None => 0,
         };
-        self.state.select(Some(i));
+        self.items[platform].devices.items.push(DeviceItem {
+            info,
+            usage: None,
+            missing: true,
+        });
+    }
+}
+
+impl Level for PlatformList {
+    fn title(&self) -> &'static str {
+        "Platforms"
     }
 
-    fn previous(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.items.len() - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
-        };
-        self.state.select(Some(i));
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn item_height(&self) -> u16 {
+        4
+    }
+
+    fn state(&self) -> &ListState {
+        &self.state
+    }
+
+    fn state_mut(&mut self) -> &mut ListState {
+        &mut self.state
+    }
+
+    fn name(&self, i: usize) -> Option<&str> {
+        self.items.get(i).map(|platform| platform.info.name_ref())
+    }
+
+    fn empty_message(&self, locale: Locale) -> Vec<Line<'static>> {
+        std::iter::once(Line::from(format!(" {}", locale.tr("No OpenCL platforms found"))).bold())
+            .chain(SETUP_HINTS.map(|hint| Line::from(format!(" • {hint}"))))
+            .collect()
+    }
+
+    fn list_items(&self, theme: &Theme, _marks: &Marks) -> Vec<ListItem<'_>> {
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(i, platform)| {
+                let priority = match self.priorities.position_of(&i) {
+                    Some(Priority::Selected(n)) => Some(n),
+                    _ => None,
+                };
+                platform.to_list_item(i, priority, theme)
+            })
+            .collect()
+    }
+
+    fn child(&self, i: usize) -> Option<&dyn Level> {
+        self.items
+            .get(i)
+            .map(|platform| &platform.devices as &dyn Level)
+    }
+
+    fn child_mut(&mut self, i: usize) -> Option<&mut dyn Level> {
+        self.items
+            .get_mut(i)
+            .map(|platform| &mut platform.devices as &mut dyn Level)
     }
 }
 
 impl DeviceList {
-    fn next(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
-        };
-        self.state.select(Some(i));
+    /// Index into `items` of the currently highlighted device
+    fn selected_index(&self) -> Option<usize> {
+        self.state
+            .selected()
+            .and_then(|i| self.visible.get(i))
+            .copied()
     }
 
-    fn previous(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.items.len() - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
-        };
-        self.state.select(Some(i));
+    fn update_visible(&mut self, filter: &str, sort: SortOrder) {
+        self.visible = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, device)| device.info.matches(filter))
+            .map(|(i, _)| i)
+            .collect();
+        let items = &self.items;
+        match sort {
+            SortOrder::Enumeration => {}
+            SortOrder::Name => self
+                .visible
+                .sort_by(|&i, &j| items[i].info.name_ref().cmp(items[j].info.name_ref())),
+            SortOrder::Vendor => self
+                .visible
+                .sort_by(|&i, &j| items[i].info.vendor_ref().cmp(items[j].info.vendor_ref())),
+            SortOrder::Type => self
+                .visible
+                .sort_by_key(|&i| (items[i].info.type_text_ref(), items[i].info.name_ref())),
+        }
+        self.state.select(if self.visible.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
     }
 }
 
-fn style_platform_name<'a>(name: String, style_string: String) -> Span<'a> {
-    if name.to_lowercase().contains("nvidia") {
-        return Span::raw(style_string).green();
+impl Level for DeviceList {
+    fn title(&self) -> &'static str {
+        "Devices"
+    }
+
+    fn len(&self) -> usize {
+        self.visible.len()
+    }
+
+    fn item_height(&self) -> u16 {
+        2
+    }
+
+    fn state(&self) -> &ListState {
+        &self.state
+    }
+
+    fn state_mut(&mut self) -> &mut ListState {
+        &mut self.state
+    }
+
+    fn name(&self, i: usize) -> Option<&str> {
+        self.visible.get(i).map(|&j| self.items[j].info.name_ref())
+    }
+
+    fn empty_message(&self, locale: Locale) -> Vec<Line<'static>> {
+        let line = |message| Line::from(format!(" {}", locale.tr(message)));
+        let hint = |message| Line::from(format!(" • {}", locale.tr(message)));
+        match self.items.is_empty() {
+            true => vec![
+                line("This platform reports no devices").bold(),
+                hint("Devices may be hidden by the config or the smoke test"),
+                Line::from(format!(" • {}", SETUP_HINTS[2])),
+            ],
+            false => vec![
+                line("No device matches the filter").bold(),
+                hint("Press / and Esc to clear it"),
+            ],
+        }
     }
-    if name.to_lowercase().contains("intel") {
-        return Span::raw(style_string).blue();
+
+    fn list_items(&self, theme: &Theme, marks: &Marks) -> Vec<ListItem<'_>> {
+        self.visible
+            .iter()
+            .enumerate()
+            .map(|(i, &j)| {
+                let marked = marks.compare.contains(&(self.platform, j));
+                let rank = marks.rank(&(self.platform, j));
+                let nickname = marks.nicknames.nickname_of(&self.items[j].info);
+                self.items[j].to_list_item(i, marked, rank, nickname, theme)
+            })
+            .collect()
     }
-    if name.to_lowercase().contains("amd") {
-        return Span::raw(style_string).red();
+
+    /// Counts the devices of the platform which have been given a priority, eg.
+    /// `Devices (2/5 selected)`
+    fn heading(&self, marks: &Marks, locale: Locale) -> String {
+        let selected = marks
+            .priorities
+            .view_priority_list()
+            .into_iter()
+            .filter(|(platform, _)| *platform == self.platform)
+            .count();
+        locale.format(
+            "{title} ({selected}/{count} selected)",
+            &[
+                ("title", locale.tr(self.title())),
+                ("selected", &selected.to_string()),
+                ("count", &self.items.len().to_string()),
+            ],
+        )
     }
-    Span::raw(style_string)
+}
+
+fn style_platform_name<'a>(name: &str, style_string: &'a str, theme: &Theme) -> Span<'a> {
+    let name = name.to_lowercase();
+    let color = if name.contains("nvidia") {
+        theme.nvidia
+    } else if name.contains("intel") {
+        theme.intel
+    } else if name.contains("amd") {
+        theme.amd
+    } else {
+        return Span::raw(style_string);
+    };
+    Span::raw(style_string).fg(color)
 }
 
 impl PlatformItem {
-    fn to_list_item(&self, index: usize) -> ListItem {
+    /// Platforms with a priority are prefixed with it
+    fn to_list_item(&self, index: usize, priority: Option<usize>, theme: &Theme) -> ListItem<'_> {
         let bg_color = match index % 2 {
-            0 => NORMAL_ROW_COLOR,
-            _ => ALT_ROW_COLOR,
+            0 => theme.normal_row,
+            _ => theme.alt_row,
         };
 
+        let prefix = priority.map(|n| format!("[{n}] ")).unwrap_or_default();
         let mut text = Text::default();
         text.extend([
-            style_platform_name(self.info.name(), self.info.name()),
-            style_platform_name(self.info.name(), self.info.version()),
-            style_platform_name(self.info.name(), self.info.vendor()),
-            style_platform_name(self.info.name(), self.info.profile()),
+            Line::from(vec![
+                Span::raw(prefix),
+                style_platform_name(self.info.name_ref(), self.info.name_ref(), theme),
+            ]),
+            style_platform_name(self.info.name_ref(), self.info.version_ref(), theme).into(),
+            style_platform_name(self.info.name_ref(), self.info.vendor_ref(), theme).into(),
+            style_platform_name(self.info.name_ref(), self.info.profile_ref(), theme).into(),
         ]);
 
         ListItem::new(text).bg(bg_color)
     }
 }
 
-impl DeviceInfo {
-    fn to_list_item(&self, index: usize) -> ListItem {
+impl DeviceItem {
+    /// Devices which are marked for comparison are prefixed with a diamond, devices with a
+    /// priority with their rank, eg. `[#1]`, and [missing](DeviceItem::missing) devices are
+    /// struck through in the error color
+    fn to_list_item(
+        &self,
+        index: usize,
+        marked: bool,
+        rank: Option<usize>,
+        nickname: Option<&str>,
+        theme: &Theme,
+    ) -> ListItem<'_> {
+        let info = &self.info;
         let bg_color = match index % 2 {
-            0 => NORMAL_ROW_COLOR,
-            _ => ALT_ROW_COLOR,
+            0 => theme.normal_row,
+            _ => theme.alt_row,
         };
 
+        let mut title = vec![
+            Span::raw(if marked { "◆ " } else { "" }),
+            Span::raw(rank.map(|rank| format!("[#{rank}] ")).unwrap_or_default()).bold(),
+            Span::raw(info.name_ref()).bold(),
+            Span::raw(
+                nickname
+                    .map(|nickname| format!(" ({nickname})"))
+                    .unwrap_or_default(),
+            )
+            .italic(),
+        ];
+        // Interop users have to filter on these before creating shared contexts
+        for api in info.interop_apis() {
+            title.extend([Span::raw(" "), Span::raw(format!(" {api} ")).reversed()]);
+        }
+        if self.missing {
+            title[2] = title[2].clone().crossed_out();
+            title.extend([Span::raw(" "), Span::raw(" missing ").reversed()]);
+        }
+
         let mut text = Text::default();
         text.extend([
-            Span::raw(self.vendor()),
-            Span::raw(format!("Vendor Id: {}", self.vendor_id())),
-            Span::raw(self.vendor_id_text()),
-            Span::raw(self.name()),
-            Span::raw(self.version()),
-            Span::raw(format!("Type: {}", self.r#type())),
-            Span::raw(self.type_text()),
-            Span::raw(self.profile()),
-            Span::raw(self.extensions()),
-            Span::raw(self.opencl_c_version()),
-            Span::raw(format!("SVM Mem Capability: {}", self.svm_mem_capability())),
+            Line::from(title),
+            Line::from(vec![
+                Span::raw(info.type_text_ref()),
+                Span::raw(" · "),
+                Span::raw(info.version_ref()),
+                Span::raw(match self.usage.map(|usage| usage.gauge(5)) {
+                    Some(gauge) if !gauge.is_empty() => format!(" · {gauge}"),
+                    _ => String::new(),
+                }),
+            ]),
         ]);
 
-        ListItem::new(text).bg(bg_color)
+        let item = ListItem::new(text).bg(bg_color);
+        match self.missing {
+            true => item.fg(theme.error),
+            false => item,
+        }
     }
+
+    /// Plain-text rendering of all [sections](DeviceItem::sections)
+    #[cfg(feature = "clipboard")]
+    fn to_text(&self) -> String {
+        let sections = self.sections();
+        let width = sections
+            .iter()
+            .flat_map(|(_, rows)| rows.iter().map(|(key, _)| key.len()))
+            .max()
+            .unwrap_or(0);
+        let mut text = String::new();
+        for (title, rows) in sections {
+            text.push_str(title);
+            text.push('\n');
+            for (key, value) in rows {
+                text.push_str(&format!("  {key:width$} {value}\n"));
+            }
+        }
+        text
+    }
+
+    /// All properties of the device grouped into titled sections
+    fn sections(&self) -> Vec<(&'static str, Vec<(&'static str, String)>)> {
+        let info = &self.info;
+        // Only attached to CPU devices, left empty for all others
+        let host_cpu = info.host_cpu_ref();
+        let optional =
+            |value: Option<u32>| value.map(|value| value.to_string()).unwrap_or_default();
+        vec![
+            (
+                "Device",
+                vec![
+                    ("Name", info.name()),
+                    ("Platform", info.platform()),
+                    ("Vendor", info.vendor()),
+                    (
+                        "Vendor Id",
+                        format!("{} ({})", info.vendor_id(), info.vendor_id_text()),
+                    ),
+                    ("Version", info.version()),
+                    ("Type", format!("{} ({})", info.r#type(), info.type_text())),
+                    ("Profile", queried(info.profile(), "")),
+                    ("OpenCL C Version", queried(info.opencl_c_version(), "")),
+                    ("Driver Version", queried(info.driver_version(), "")),
+                    ("SVM Capabilities", queried(info.svm_capabilities(), "")),
+                    (
+                        "Usage",
+                        self.usage
+                            .map(|usage| usage.to_string())
+                            .unwrap_or_default(),
+                    ),
+                    (
+                        "UUID",
+                        info.uuid().map(|uuid| hex(&uuid)).unwrap_or_default(),
+                    ),
+                    (
+                        "LUID",
+                        info.luid().map(|luid| hex(&luid)).unwrap_or_default(),
+                    ),
+                ],
+            ),
+            (
+                "Capabilities",
+                info.capabilities()
+                    .into_iter()
+                    .map(|(capability, supported)| {
+                        let supported = match (info.extensions(), supported) {
+                            (None, _) => NOT_QUERIED,
+                            (Some(_), true) => "✓",
+                            (Some(_), false) => "✗",
+                        };
+                        (capability.label, supported.into())
+                    })
+                    .chain([(
+                        "Other Extensions",
+                        info.uncategorized_extensions().join(" "),
+                    )])
+                    .collect(),
+            ),
+            (
+                "Memory",
+                vec![
+                    (
+                        "Global Size",
+                        queried(info.global_mem_size().map(Bytes), ""),
+                    ),
+                    (
+                        "Global Cache Size",
+                        queried(info.global_mem_cache_size().map(Bytes), ""),
+                    ),
+                    (
+                        "Global Cache Line Size",
+                        queried(
+                            info.global_mem_cacheline_size()
+                                .map(|size| Bytes(size.into())),
+                            "",
+                        ),
+                    ),
+                    (
+                        "Global Cache Type",
+                        queried(info.global_mem_cache_type_text(), ""),
+                    ),
+                    (
+                        "Max Allocation Size",
+                        queried(info.max_mem_alloc_size().map(Bytes), ""),
+                    ),
+                    ("Local Size", queried(info.local_mem_size().map(Bytes), "")),
+                    (
+                        "Max Constant Buffer Size",
+                        queried(info.max_constant_buffer_size().map(Bytes), ""),
+                    ),
+                    (
+                        "Base Address Alignment",
+                        queried(info.mem_base_addr_align(), " bit"),
+                    ),
+                    (
+                        "Host Unified Memory",
+                        queried(info.host_unified_memory(), ""),
+                    ),
+                    (
+                        "Error Correction",
+                        queried(info.error_correction_support(), ""),
+                    ),
+                ],
+            ),
+            (
+                "Execution",
+                vec![
+                    ("Compute Units", queried(info.max_compute_units(), "")),
+                    (
+                        "Max Work Group Size",
+                        queried(info.max_work_group_size(), ""),
+                    ),
+                    ("Image Support", queried(info.image_support(), "")),
+                    (
+                        "Printf Buffer Size",
+                        queried(info.printf_buffer_size().map(Bytes), ""),
+                    ),
+                    (
+                        "Timer Resolution",
+                        queried(info.profiling_timer_resolution(), " ns"),
+                    ),
+                    ("Little Endian", queried(info.endian_little(), "")),
+                    (
+                        "Max Clock Frequency",
+                        queried(info.max_clock_frequency().map(format_frequency), ""),
+                    ),
+                ],
+            ),
+            (
+                "Host CPU",
+                vec![
+                    (
+                        "Model",
+                        host_cpu
+                            .and_then(|cpu| cpu.model.clone())
+                            .unwrap_or_default(),
+                    ),
+                    ("Cores", optional(host_cpu.and_then(|cpu| cpu.cores))),
+                    ("Threads", optional(host_cpu.map(|cpu| cpu.threads))),
+                    (
+                        "NUMA Nodes",
+                        optional(host_cpu.and_then(|cpu| cpu.numa_nodes)),
+                    ),
+                ],
+            ),
+            (
+                "Partitioning",
+                vec![
+                    (
+                        "Max Sub-Devices",
+                        queried(info.partition_max_sub_devices(), ""),
+                    ),
+                    (
+                        "Schemes",
+                        queried(
+                            info.partition_properties_ref()
+                                .map(|schemes| match schemes {
+                                    [] => "none".into(),
+                                    schemes => schemes
+                                        .iter()
+                                        .map(|scheme| scheme.to_string())
+                                        .collect::<Vec<_>>()
+                                        .join(", "),
+                                }),
+                            "",
+                        ),
+                    ),
+                    (
+                        "Affinity Domains",
+                        queried(info.partition_affinity_domains(), ""),
+                    ),
+                ],
+            ),
+            (
+                "OpenCL 3.0",
+                vec![
+                    ("Numeric Version", queried(info.numeric_version(), "")),
+                    ("ILs", queried(info.ils_ref().map(join), "")),
+                    (
+                        "Atomic Memory",
+                        queried(info.atomic_memory_capabilities(), ""),
+                    ),
+                    (
+                        "Atomic Fence",
+                        queried(info.atomic_fence_capabilities(), ""),
+                    ),
+                    (
+                        "OpenCL C Versions",
+                        queried(info.opencl_c_all_versions_ref().map(join), ""),
+                    ),
+                    (
+                        "OpenCL C Features",
+                        queried(info.opencl_c_features_ref().map(join), ""),
+                    ),
+                    ("Build Options", info.cl_std_options().join(" ")),
+                ],
+            ),
+            (
+                "Images",
+                vec![(
+                    "2D Read-Only Formats",
+                    queried(
+                        info.image_formats_ref().map(|formats| match formats {
+                            [] => "none".into(),
+                            formats => formats
+                                .iter()
+                                .map(|format| format.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        }),
+                        "",
+                    ),
+                )],
+            ),
+        ]
+    }
+}
+
+/// Shown in place of properties which were not queried during enumeration
+const NOT_QUERIED: &str = "not queried";
+
+/// Joins the names and versions of intermediate languages or features
+fn join(values: &[NameVersion]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats a property followed by its unit or [NOT_QUERIED]
+fn queried(value: Option<impl std::fmt::Display>, unit: &str) -> String {
+    match value {
+        Some(value) => format!("{value}{unit}"),
+        None => NOT_QUERIED.into(),
+    }
+}
+
+/// Formats bytes as lowercase hexadecimal digits
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
 }
@@ -0,0 +1,115 @@
+//! One-call selection of a device for projects which do not need any configuration
+
+use std::path::PathBuf;
+
+use crate::clinfo::CL_DEVICE_TYPE_GPU;
+use crate::{
+    AutoSelect, ClState, DeviceInfo, DeviceSpec, EnumerationCache, Result, SelectionError,
+    SelectionHistory, UniquePriorityList,
+};
+
+/// Name of the project-local selection which is searched for in the working directory and its
+/// ancestors
+pub const PROJECT_SELECTION_FILE: &str = ".opencl3_select.toml";
+
+/// Name of the file in the data directory which stores previous selections
+pub const SELECTION_HISTORY_FILE: &str = "history.json";
+
+/// Name of the file in the cache directory which stores the enumerated setup
+const SETUP_CACHE_FILE: &str = "setup.json";
+
+/// Environment variable naming the device which [pick_device] returns
+const ENV_OVERRIDE: &str = "OPENCL_DEVICE";
+
+/// The saved selection ordered from highest to lowest priority, empty if there is none
+///
+/// The project-local selection in the working directory or the closest of its ancestors takes
+/// precedence over the most recent selection of the global history, which is written by the
+/// `opencl3-select` binary.
+pub fn saved_selection() -> Result<Vec<DeviceInfo>> {
+    let project = std::env::current_dir().ok().and_then(|dir| {
+        dir.ancestors()
+            .map(|dir| dir.join(PROJECT_SELECTION_FILE))
+            .find(|path| path.is_file())
+    });
+    if let Some(path) = project {
+        let selection: UniquePriorityList<DeviceInfo> = crate::load(path)?;
+        return Ok(selection
+            .view_priority_list()
+            .into_iter()
+            .cloned()
+            .collect());
+    }
+    let history = match dirs::data_dir() {
+        Some(dir) => SelectionHistory::load_or_default(
+            dir.join("opencl3_select").join(SELECTION_HISTORY_FILE),
+        )?,
+        None => SelectionHistory::default(),
+    };
+    Ok(history
+        .entries
+        .first()
+        .map(|entry| {
+            entry
+                .selection
+                .view_priority_list()
+                .into_iter()
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Picks a device with the default pipeline of this crate
+///
+/// 1. The setup is taken from an [EnumerationCache] in the users cache directory, such that
+///    only the first call and calls after driver changes enumerate the devices.
+/// 2. The device named by the environment variable `OPENCL_DEVICE` is used if it is set. A
+///    name which matches no device is an error rather than silently ignored.
+/// 3. The first device of the [saved selection](saved_selection) which is still present.
+/// 4. The best device according to the default [AutoSelect].
+/// 5. The first GPU and finally the first device of any type.
+/// ```no_run
+/// let device = opencl3_select::pick_device()?;
+/// let context = opencl3::context::Context::from_device(&device)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn pick_device() -> Result<opencl3::device::Device> {
+    let cl_state = match setup_cache_path() {
+        Some(path) => EnumerationCache::new(path).get_setup()?,
+        None => crate::get_setup()?,
+    };
+    pick(&cl_state)?.resolve()
+}
+
+/// Location of the cached setup inside the users cache directory
+fn setup_cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("opencl3_select").join(SETUP_CACHE_FILE))
+}
+
+/// The device chosen by the steps of [pick_device] after the enumeration
+fn pick(cl_state: &ClState) -> Result<DeviceInfo> {
+    if let Some(name) = std::env::var(ENV_OVERRIDE)
+        .ok()
+        .filter(|name| !name.is_empty())
+    {
+        return DeviceSpec::Name(name).find(cl_state);
+    }
+    let devices = cl_state.get_all_devices();
+    let saved = saved_selection()?;
+    let device = saved
+        .iter()
+        .find_map(|saved| devices.iter().find(|device| *device == saved))
+        .or_else(|| AutoSelect::default().best(&devices, &saved))
+        .or_else(|| {
+            devices
+                .iter()
+                .find(|device| device.r#type() & CL_DEVICE_TYPE_GPU != 0)
+        })
+        .or_else(|| devices.first());
+    #[cfg(feature = "tracing")]
+    tracing::info!(device = ?device.map(|device| device.name_ref()), "picked device");
+    device
+        .cloned()
+        .ok_or_else(|| SelectionError::NoMatchingDevice.into())
+}
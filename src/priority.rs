@@ -1,10 +1,11 @@
+//! Ordered selection of elements by priority
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// A dynamic list which labels elements by priority
 /// ```
-/// use opencl3_select::PriorityList;
-/// let mut prio_list = PriorityList::new();
+/// use opencl3_select::UniquePriorityList;
+/// let mut prio_list = UniquePriorityList::new();
 ///
 /// prio_list.push(1);
 /// prio_list.push(3);
@@ -19,23 +20,30 @@ use serde::{Deserialize, Serialize};
 /// assert_eq!(remaining[1], &3);
 /// assert_eq!(remaining[2], &77);
 /// ```
-#[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct UniquePriorityList<T> {
+    /// Elements which have been given a priority, ordered from highest to lowest
     selected: Vec<T>,
+    /// Elements which have not (yet) been given a priority
     remaining: Vec<T>,
+    /// Index of the selected element handed out next by
+    /// [round_robin](UniquePriorityList::round_robin)
+    #[cfg_attr(feature = "serde", serde(default))]
+    cursor: usize,
 }
 
 impl<T> UniquePriorityList<T> {
-    /// Construct a new empty [PriorityList]
+    /// Construct a new empty [UniquePriorityList]
     /// ```
-    /// use opencl3_select::PriorityList;
-    /// let priorty_list: PriorityList<u8> = PriorityList::new();
+    /// use opencl3_select::UniquePriorityList;
+    /// let priorty_list: UniquePriorityList<u8> = UniquePriorityList::new();
     /// ```
     pub fn new() -> Self {
         Self {
             selected: Vec::new(),
             remaining: Vec::new(),
+            cursor: 0,
         }
     }
 
@@ -46,7 +54,7 @@ impl<T> UniquePriorityList<T> {
 
     /// Gets the nth-highest priority member
     pub fn priority_nth(&self, n: usize) -> Option<&T> {
-        self.selected.iter().nth(n)
+        self.selected.get(n)
     }
 
     /// Adds another member to the priority list
@@ -60,13 +68,109 @@ impl<T> UniquePriorityList<T> {
     }
 
     /// Adds another element and sets it as the first priority
-    pub fn push_set_first(&mut self, element: T) {
-        self.selected.insert(0, element)
+    ///
+    /// An element which is already part of the list is moved instead.
+    pub fn push_set_first(&mut self, element: T)
+    where
+        T: PartialEq,
+    {
+        self.push_set_nth(element, 0);
     }
 
     /// Adds another elements and sets it as the nth priority
-    pub fn push_set_nth(&mut self, element: T, n: usize) {
-        self.selected.insert(n, element)
+    ///
+    /// An element which is already part of the list is moved instead. Positions past the end
+    /// of the selected elements append the element.
+    /// ```
+    /// use opencl3_select::UniquePriorityList;
+    /// let mut prio_list = UniquePriorityList::from(["gpu0", "gpu1"]);
+    /// prio_list.push("cpu");
+    /// prio_list.push_set_nth("cpu", 1);
+    /// prio_list.push_set_nth("gpu0", 5);
+    /// let selected: Vec<_> = prio_list.view_priority_list().into_iter().collect();
+    /// assert_eq!(selected, [&"cpu", &"gpu1", &"gpu0"]);
+    /// assert_eq!(prio_list.view_remaining().into_iter().count(), 0);
+    /// ```
+    pub fn push_set_nth(&mut self, element: T, n: usize)
+    where
+        T: PartialEq,
+    {
+        self.selected.retain(|e| e != &element);
+        self.remaining.retain(|e| e != &element);
+        let n = n.min(self.selected.len());
+        self.selected.insert(n, element);
+    }
+
+    /// Removes repeated elements, keeping the occurrence with the highest priority
+    ///
+    /// Selected elements take precedence over remaining ones.
+    fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        let mut kept: Vec<T> = Vec::new();
+        for element in std::mem::take(&mut self.selected) {
+            if !kept.contains(&element) {
+                kept.push(element);
+            }
+        }
+        self.selected = kept;
+        let mut kept: Vec<T> = Vec::new();
+        for element in std::mem::take(&mut self.remaining) {
+            if !self.selected.contains(&element) && !kept.contains(&element) {
+                kept.push(element);
+            }
+        }
+        self.remaining = kept;
+    }
+
+    /// Checks the invariant that every element is part of either the selected or the remaining
+    /// elements exactly once
+    ///
+    /// Lists built with [From] or deserialized drop repeated elements and all methods keep the
+    /// invariant. Below, random sequences of the methods moving elements are checked:
+    /// ```
+    /// use opencl3_select::UniquePriorityList;
+    /// let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    /// let mut random = |bound: u64| {
+    ///     seed ^= seed << 13;
+    ///     seed ^= seed >> 7;
+    ///     seed ^= seed << 17;
+    ///     seed % bound
+    /// };
+    /// for _ in 0..50 {
+    ///     let mut prio_list = UniquePriorityList::new();
+    ///     for _ in 0..100 {
+    ///         let element = random(10);
+    ///         match random(6) {
+    ///             0 => prio_list.push(element),
+    ///             1 => prio_list.push_set_first(element),
+    ///             2 => prio_list.push_set_nth(element, random(12) as usize),
+    ///             3 => prio_list.select(random(12) as usize),
+    ///             4 => prio_list.select_set_nth(random(12) as usize, random(12) as usize),
+    ///             _ => prio_list.promote_matching(|e| *e == element),
+    ///         }
+    ///         assert!(prio_list.is_unique());
+    ///         for element in 0..10 {
+    ///             let count = prio_list
+    ///                 .view_priority_list()
+    ///                 .into_iter()
+    ///                 .chain(prio_list.view_remaining())
+    ///                 .filter(|e| **e == element)
+    ///                 .count();
+    ///             assert_eq!(count, prio_list.position_of(&element).is_some() as usize);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn is_unique(&self) -> bool
+    where
+        T: PartialEq,
+    {
+        let all: Vec<&T> = self.selected.iter().chain(&self.remaining).collect();
+        all.iter()
+            .enumerate()
+            .all(|(n, element)| !all[..n].contains(element))
     }
 
     /// View the current priority list
@@ -81,25 +185,198 @@ impl<T> UniquePriorityList<T> {
 
     /// Selects a currently not selected item in the priority
     /// list with currently lowest priority
+    ///
+    /// `n` is the index among the remaining elements, out of range indices are ignored.
+    /// ```
+    /// use opencl3_select::{Priority, UniquePriorityList};
+    /// let mut prio_list = UniquePriorityList::new();
+    /// for n in 0..3 {
+    ///     prio_list.push(n);
+    /// }
+    /// prio_list.select(1);
+    /// prio_list.select(5);
+    /// assert_eq!(prio_list.position_of(&1), Some(Priority::Selected(0)));
+    /// assert_eq!(prio_list.position_of(&2), Some(Priority::Remaining(1)));
+    /// ```
     pub fn select(&mut self, n: usize) {
-        if self.remaining.len() < n {
+        if n < self.remaining.len() {
             let selected = self.remaining.remove(n);
             self.selected.push(selected);
         }
     }
 
     /// Selects an element and puts it at the nth position of the list
+    ///
+    /// Positions past the end of the selected elements append the element.
+    /// ```
+    /// use opencl3_select::{Priority, UniquePriorityList};
+    /// let mut prio_list = UniquePriorityList::from(["gpu0"]);
+    /// prio_list.push("gpu1");
+    /// prio_list.push("cpu");
+    /// prio_list.select_set_first(1);
+    /// prio_list.select_set_nth(0, 7);
+    /// prio_list.select_set_nth(3, 0);
+    /// assert_eq!(prio_list.position_of(&"cpu"), Some(Priority::Selected(0)));
+    /// assert_eq!(prio_list.position_of(&"gpu1"), Some(Priority::Selected(2)));
+    /// ```
     pub fn select_set_nth(&mut self, n: usize, priority_level: usize) {
-        if self.remaining.len() < n {
+        if n < self.remaining.len() {
             let selected = self.remaining.remove(n);
+            let priority_level = priority_level.min(self.selected.len());
             self.selected.insert(priority_level, selected);
         }
     }
 
-    /// See [select_set_nth](PriorityList::select_set_nth)
+    /// See [select_set_nth](UniquePriorityList::select_set_nth)
     pub fn select_set_first(&mut self, n: usize) {
         self.select_set_nth(n, 0);
     }
+
+    /// Sorts the remaining elements by the given key
+    ///
+    /// The sort is stable such that elements with equal keys keep their order.
+    /// ```
+    /// use opencl3_select::UniquePriorityList;
+    /// let mut prio_list = UniquePriorityList::new();
+    /// for element in ["pocl", "nvidia", "intel", "amd"] {
+    ///     prio_list.push(element);
+    /// }
+    /// prio_list.sort_remaining_by_key(|name| name.len());
+    /// let remaining: Vec<_> = prio_list.view_remaining().into_iter().collect();
+    /// assert_eq!(remaining, [&"amd", &"pocl", &"intel", &"nvidia"]);
+    /// ```
+    pub fn sort_remaining_by_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.remaining.sort_by_key(f);
+    }
+
+    /// Selects all remaining elements for which the predicate returns `true`
+    ///
+    /// The promoted elements keep their relative order and are appended with the
+    /// currently lowest priority.
+    /// ```
+    /// use opencl3_select::{Priority, UniquePriorityList};
+    /// let mut prio_list = UniquePriorityList::new();
+    /// for n in 0..6 {
+    ///     prio_list.push(n);
+    /// }
+    /// prio_list.promote_matching(|n| n % 2 == 1);
+    /// assert_eq!(prio_list.position_of(&3), Some(Priority::Selected(1)));
+    /// assert_eq!(prio_list.position_of(&4), Some(Priority::Remaining(2)));
+    /// assert_eq!(prio_list.position_of(&7), None);
+    /// ```
+    pub fn promote_matching<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let (promoted, remaining) = std::mem::take(&mut self.remaining)
+            .into_iter()
+            .partition::<Vec<_>, _>(|element| predicate(element));
+        self.selected.extend(promoted);
+        self.remaining = remaining;
+    }
+
+    /// Hands out the selected elements one after another, starting over after the last one
+    ///
+    /// The cursor is part of the list such that saving and loading the list continues the
+    /// rotation where it left off. Useful to spread many small jobs over equivalent devices.
+    /// ```
+    /// use opencl3_select::UniquePriorityList;
+    /// let mut prio_list = UniquePriorityList::from(["gpu0", "gpu1"]);
+    /// assert_eq!(prio_list.round_robin(), Some(&"gpu0"));
+    /// assert_eq!(prio_list.round_robin(), Some(&"gpu1"));
+    /// assert_eq!(prio_list.round_robin(), Some(&"gpu0"));
+    /// assert_eq!(UniquePriorityList::<u8>::new().round_robin(), None);
+    /// ```
+    pub fn round_robin(&mut self) -> Option<&T> {
+        if self.selected.is_empty() {
+            return None;
+        }
+        let n = self.cursor % self.selected.len();
+        self.cursor = n + 1;
+        self.selected.get(n)
+    }
+
+    /// Picks a selected element at random with a probability proportional to its weight
+    ///
+    /// The nth weight belongs to the element with the nth-highest priority. Elements without
+    /// a weight and negative or non-finite weights are never picked. Returns `None` if no
+    /// element has a positive weight.
+    /// ```
+    /// use opencl3_select::UniquePriorityList;
+    /// let prio_list = UniquePriorityList::from(["gpu0", "gpu1", "cpu"]);
+    /// for _ in 0..20 {
+    ///     assert_ne!(prio_list.pick_weighted(&[3.0, 1.0]), Some(&"cpu"));
+    /// }
+    /// assert_eq!(prio_list.pick_weighted(&[0.0, 2.0, 0.0]), Some(&"gpu1"));
+    /// assert_eq!(prio_list.pick_weighted(&[]), None);
+    /// ```
+    pub fn pick_weighted(&self, weights: &[f64]) -> Option<&T> {
+        let weight = |w: &f64| if w.is_finite() && *w > 0.0 { *w } else { 0.0 };
+        let total: f64 = weights.iter().take(self.selected.len()).map(weight).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut threshold = random_unit() * total;
+        let mut last = None;
+        for (element, w) in self.selected.iter().zip(weights.iter().map(weight)) {
+            if w <= 0.0 {
+                continue;
+            }
+            if threshold < w {
+                return Some(element);
+            }
+            threshold -= w;
+            last = Some(element);
+        }
+        // Rounding can leave a tiny remainder behind the last positive weight
+        last
+    }
+
+    /// Finds the position of an element in either the selected or the remaining elements
+    pub fn position_of(&self, element: &T) -> Option<Priority>
+    where
+        T: PartialEq,
+    {
+        if let Some(n) = self.selected.iter().position(|e| e == element) {
+            return Some(Priority::Selected(n));
+        }
+        self.remaining
+            .iter()
+            .position(|e| e == element)
+            .map(Priority::Remaining)
+    }
+}
+
+/// Uniformly distributed number in `[0, 1)` seeded by the randomly keyed std hasher
+fn random_unit() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Position of an element within a [UniquePriorityList]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Priority {
+    /// The element has been selected with the given rank where `0` is the highest priority
+    Selected(usize),
+    /// The element has not been selected and is at the given index of the remaining elements
+    Remaining(usize),
+}
+
+impl<T> Default for UniquePriorityList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T, I> From<I> for UniquePriorityList<T>
@@ -107,10 +384,61 @@ where
     I: IntoIterator<Item = T>,
     T: Eq,
 {
+    /// Selects the elements in the given order, repeated elements are only kept once
+    /// ```
+    /// use opencl3_select::UniquePriorityList;
+    /// let prio_list = UniquePriorityList::from([2, 1, 2]);
+    /// assert!(prio_list.is_unique());
+    /// let selected: Vec<_> = prio_list.view_priority_list().into_iter().collect();
+    /// assert_eq!(selected, [&2, &1]);
+    /// ```
     fn from(value: I) -> Self {
-        Self {
+        let mut prio_list = Self {
             selected: value.into_iter().collect(),
             remaining: Vec::new(),
+            cursor: 0,
+        };
+        prio_list.dedup();
+        prio_list
+    }
+}
+
+/// Repeated elements of the deserialized lists are only kept once, selected elements take
+/// precedence over remaining ones
+/// ```
+/// use opencl3_select::UniquePriorityList;
+/// let prio_list: UniquePriorityList<u8> =
+///     serde_json::from_str(r#"{"selected": [1, 1], "remaining": [2, 1]}"#)?;
+/// assert!(prio_list.is_unique());
+/// let selected: Vec<_> = prio_list.view_priority_list().into_iter().collect();
+/// let remaining: Vec<_> = prio_list.view_remaining().into_iter().collect();
+/// assert_eq!((selected, remaining), (vec![&1], vec![&2]));
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[cfg(feature = "serde")]
+impl<'de, T> Deserialize<'de> for UniquePriorityList<T>
+where
+    T: Deserialize<'de> + PartialEq,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Fields<T> {
+            selected: Vec<T>,
+            remaining: Vec<T>,
+            #[serde(default)]
+            cursor: usize,
         }
+        let Fields {
+            selected,
+            remaining,
+            cursor,
+        } = Fields::deserialize(deserializer)?;
+        let mut prio_list = Self {
+            selected,
+            remaining,
+            cursor,
+        };
+        prio_list.dedup();
+        Ok(prio_list)
     }
 }
@@ -0,0 +1,187 @@
+//! Built-in requirements and scoring weights for common use cases
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{AutoSelect, DeviceInfo, DeviceScorer};
+
+/// Weights by which properties of a device add up to its score
+///
+/// Used as [DeviceScorer], the weights of all properties which a device has are summed up.
+/// ```
+/// use opencl3_select::{DeviceInfo, DeviceScorer, ScoringWeights};
+/// let weights = ScoringWeights {
+///     fp64: 2.0,
+///     memory_per_gib: 0.25,
+///     ..Default::default()
+/// };
+/// let device = DeviceInfo::builder()
+///     .extensions("cl_khr_fp64")
+///     .global_mem_size(8u64 << 30)
+///     .build();
+/// assert_eq!(weights.score(&device), Some(4.0));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(default))]
+pub struct ScoringWeights {
+    /// Added if the device supports double precision
+    pub fp64: f64,
+    /// Added if the device supports half precision
+    pub fp16: f64,
+    /// Added for every GiB of global memory
+    pub memory_per_gib: f64,
+    /// Added if the device shares its memory with the host, eg. integrated GPUs
+    pub host_unified_memory: f64,
+    /// Added if the device implements the embedded profile
+    pub embedded_profile: f64,
+}
+
+impl DeviceScorer for ScoringWeights {
+    fn name(&self) -> &str {
+        "weights"
+    }
+
+    fn score(&self, device: &DeviceInfo) -> Option<f64> {
+        let weight = |weight: f64, present: bool| if present { weight } else { 0.0 };
+        let memory = device.global_mem_size().unwrap_or(0) as f64 / f64::from(1 << 30);
+        Some(
+            weight(self.fp64, device.supports("fp64"))
+                + weight(self.fp16, device.supports("fp16"))
+                + self.memory_per_gib * memory
+                + weight(
+                    self.host_unified_memory,
+                    device.host_unified_memory() == Some(true),
+                )
+                + weight(
+                    self.embedded_profile,
+                    device.profile_ref() == Some("EMBEDDED_PROFILE"),
+                ),
+        )
+    }
+}
+
+/// Presets of requirements and [ScoringWeights] which can be selected by name
+/// ```
+/// use opencl3_select::{DeviceInfo, Profile};
+/// let devices = [
+///     DeviceInfo::builder().name("small").global_mem_size(4u64 << 30).build(),
+///     DeviceInfo::builder()
+///         .name("large")
+///         .extensions("cl_khr_fp64")
+///         .global_mem_size(16u64 << 30)
+///         .build(),
+/// ];
+/// let profile: Profile = "compute".parse()?;
+/// let best = profile
+///     .auto_select()
+///     .best_with(&devices, &[], &[&profile.weights()]);
+/// assert_eq!(best.unwrap().name(), "large");
+///
+/// // Neither device can share buffers with OpenGL
+/// let profile = Profile::GraphicsInterop;
+/// assert!(profile.auto_select().best(&devices, &[]).is_none());
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum Profile {
+    /// Number crunching, prefers GPUs and accelerators with double precision and much memory
+    Compute,
+    /// Sharing buffers with OpenGL, requires `cl_khr_gl_sharing` and prefers GPUs
+    GraphicsInterop,
+    /// Small or low-power devices, prefers the embedded profile, half precision and memory
+    /// shared with the host
+    Embedded,
+}
+
+impl Profile {
+    /// All profiles in the order in which they are listed
+    pub const ALL: [Self; 3] = [Self::Compute, Self::GraphicsInterop, Self::Embedded];
+
+    /// Name by which the profile is selected, eg. `graphics-interop`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Compute => "compute",
+            Self::GraphicsInterop => "graphics-interop",
+            Self::Embedded => "embedded",
+        }
+    }
+
+    /// Requirements and preferred device types of the profile
+    pub fn auto_select(&self) -> AutoSelect {
+        let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+        match self {
+            Self::Compute => AutoSelect {
+                require: vec![],
+                prefer: strings(&["gpu", "accelerator"]),
+                ..Default::default()
+            },
+            Self::GraphicsInterop => AutoSelect {
+                require: strings(&["cl_khr_gl_sharing"]),
+                prefer: strings(&["gpu"]),
+                ..Default::default()
+            },
+            Self::Embedded => AutoSelect::default(),
+        }
+    }
+
+    /// Weights by which the devices fulfilling the requirements are ranked
+    pub fn weights(&self) -> ScoringWeights {
+        match self {
+            Self::Compute => ScoringWeights {
+                fp64: 2.0,
+                memory_per_gib: 0.25,
+                ..Default::default()
+            },
+            Self::GraphicsInterop => ScoringWeights {
+                memory_per_gib: 0.25,
+                ..Default::default()
+            },
+            Self::Embedded => ScoringWeights {
+                fp16: 1.0,
+                host_unified_memory: 1.0,
+                embedded_profile: 2.0,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl AutoSelect {
+    /// Adds the requirements of the profile and falls back to its preferred types
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        let preset = profile.auto_select();
+        self.require.extend(preset.require);
+        if self.prefer.is_empty() {
+            self.prefer = preset.prefer;
+        }
+        self
+    }
+}
+
+impl std::fmt::Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(Profile::name).collect();
+                format!(
+                    "unknown profile \"{s}\", expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
@@ -0,0 +1,354 @@
+//! Self-contained reports of the OpenCL setup for experiment records and bug reports
+
+use std::path::Path;
+
+#[cfg(feature = "bench")]
+use crate::BenchmarkDelta;
+use crate::{ClState, DeviceInfo, HostInfo, StorageError};
+
+/// File formats of [ClState::write_report]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReportFormat {
+    /// [Markdown](https://commonmark.org)
+    Markdown,
+    /// Single HTML page without external resources
+    Html,
+}
+
+impl ReportFormat {
+    /// Determine the format from the extension of the given path
+    /// ```
+    /// use opencl3_select::ReportFormat;
+    /// assert_eq!(ReportFormat::from_path("report.md"), Some(ReportFormat::Markdown));
+    /// assert_eq!(ReportFormat::from_path("report.html"), Some(ReportFormat::Html));
+    /// assert_eq!(ReportFormat::from_path("report.json"), None);
+    /// ```
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = String;
+
+    /// Parses the name of a format as it is used as file extension
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "html" | "htm" => Ok(Self::Html),
+            _ => Err(format!(
+                "unknown report format \"{s}\", expected one of markdown, html"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Markdown => write!(f, "markdown"),
+            Self::Html => write!(f, "html"),
+        }
+    }
+}
+
+/// Escapes characters which have a special meaning in the given format
+fn escape(format: ReportFormat, text: &str) -> String {
+    match format {
+        ReportFormat::Markdown => text.replace('|', "\\|"),
+        ReportFormat::Html => text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;"),
+    }
+}
+
+/// Renders a heading of the given level
+fn heading(format: ReportFormat, level: usize, text: &str) -> String {
+    match format {
+        ReportFormat::Markdown => format!("{} {}\n\n", "#".repeat(level), escape(format, text)),
+        ReportFormat::Html => format!("<h{level}>{}</h{level}>\n", escape(format, text)),
+    }
+}
+
+/// Renders a table with a header row
+fn table(format: ReportFormat, header: &[&str], rows: &[Vec<String>]) -> String {
+    match format {
+        ReportFormat::Markdown => {
+            let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+            let mut out = line(header.iter().map(|h| escape(format, h)).collect());
+            out += &line(header.iter().map(|_| "---".to_string()).collect());
+            for row in rows {
+                out += &line(row.iter().map(|cell| escape(format, cell)).collect());
+            }
+            out + "\n"
+        }
+        ReportFormat::Html => {
+            let line = |tag: &str, cells: Vec<String>| {
+                let cells: String = cells
+                    .iter()
+                    .map(|cell| format!("<{tag}>{}</{tag}>", escape(format, cell)))
+                    .collect();
+                format!("<tr>{cells}</tr>\n")
+            };
+            let mut out = String::from("<table>\n");
+            out += &line("th", header.iter().map(|h| h.to_string()).collect());
+            for row in rows {
+                out += &line("td", row.clone());
+            }
+            out + "</table>\n"
+        }
+    }
+}
+
+/// Renders preformatted text
+fn preformatted(format: ReportFormat, text: &str) -> String {
+    match format {
+        ReportFormat::Markdown => format!("```text\n{text}```\n\n"),
+        ReportFormat::Html => format!("<pre>{}</pre>\n", escape(format, text)),
+    }
+}
+
+/// One row per device with the most relevant properties
+fn device_rows(devices: &[DeviceInfo]) -> Vec<Vec<String>> {
+    devices
+        .iter()
+        .map(|device| {
+            vec![
+                device.name(),
+                device.vendor(),
+                device.type_text(),
+                device.version(),
+                device.opencl_c_version().unwrap_or_default(),
+                device
+                    .global_mem_size()
+                    .map(crate::format_bytes)
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect()
+}
+
+/// Columns of [device_rows]
+const DEVICE_HEADER: &[&str] = &[
+    "Name",
+    "Vendor",
+    "Type",
+    "Version",
+    "OpenCL C Version",
+    "Global Memory",
+];
+
+/// Columns of [ClState::capability_matrix]
+const MATRIX_HEADER: &[&str] = &[
+    "Device",
+    "Platform",
+    "Type",
+    "OpenCL",
+    "fp64",
+    "fp16",
+    "Images",
+    "SVM",
+    "Global Memory",
+    "Max Allocation",
+];
+
+/// Renders a property which is either supported or not, `?` if it was not queried
+fn yes_no(supported: Option<bool>) -> String {
+    match supported {
+        Some(true) => "yes".into(),
+        Some(false) => "no".into(),
+        None => "?".into(),
+    }
+}
+
+/// Renders the changes of benchmarks versus their stored baseline as a report section
+///
+/// Changes worse than the relative `tolerance` are flagged as regressions.
+/// See [BenchmarkHistory](crate::BenchmarkHistory).
+#[cfg(feature = "bench")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "bench")))]
+pub fn benchmark_report(format: ReportFormat, deltas: &[BenchmarkDelta], tolerance: f64) -> String {
+    let rows: Vec<_> = deltas
+        .iter()
+        .map(|delta| {
+            let driver = match delta.driver_changed() {
+                true => format!("{} -> {}", delta.baseline_driver, delta.current_driver),
+                false => delta.current_driver.clone(),
+            };
+            vec![
+                delta.name.clone(),
+                format!("{} {}", delta.baseline, delta.unit),
+                format!("{} {}", delta.current, delta.unit),
+                format!("{:+.1}%", 100.0 * delta.relative()),
+                driver,
+                match delta.is_regression(tolerance) {
+                    true => "regression".into(),
+                    false => String::new(),
+                },
+            ]
+        })
+        .collect();
+    heading(format, 2, "Benchmarks")
+        + &table(
+            format,
+            &["Benchmark", "Baseline", "Current", "Change", "Driver", ""],
+            &rows,
+        )
+}
+
+impl ClState {
+    /// Renders a report of all platforms and devices together with the selected devices
+    ///
+    /// The selection is listed from highest to lowest priority.
+    pub fn report(&self, format: ReportFormat, selection: &[DeviceInfo]) -> String {
+        let mut body = heading(format, 1, "OpenCL setup report");
+        // Snapshots saved before the host was recorded are attributed to the current machine
+        let host = self.host().cloned().unwrap_or_else(HostInfo::current);
+        body += &table(
+            format,
+            &[
+                "Generated by",
+                "Host",
+                "Operating system",
+                "Architecture",
+                "ICD loader",
+            ],
+            &[vec![
+                format!("opencl3-select {}", env!("CARGO_PKG_VERSION")),
+                host.hostname.unwrap_or_default(),
+                host.os,
+                host.arch,
+                host.icd_loader_version.unwrap_or_default(),
+            ]],
+        );
+
+        body += &heading(format, 2, "Selection");
+        let selection_rows: Vec<_> = device_rows(selection)
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| [vec![i.to_string()], row].concat())
+            .collect();
+        body += &table(
+            format,
+            &[&["Priority"], DEVICE_HEADER].concat(),
+            &selection_rows,
+        );
+
+        body += &heading(format, 2, "Platforms");
+        for platform in self.platforms_ref() {
+            body += &heading(format, 3, platform.name_ref());
+            body += &table(
+                format,
+                &["Version", "Vendor", "Profile"],
+                &[vec![
+                    platform.version(),
+                    platform.vendor(),
+                    platform.profile(),
+                ]],
+            );
+            body += &table(format, DEVICE_HEADER, &device_rows(platform.devices_ref()));
+        }
+
+        body += &heading(format, 2, "Summary");
+        body += &preformatted(format, &self.summary().to_string());
+
+        match format {
+            ReportFormat::Markdown => body,
+            ReportFormat::Html => format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+                <title>OpenCL setup report</title>\n<style>\n\
+                body {{ font-family: sans-serif; }}\n\
+                table {{ border-collapse: collapse; margin-bottom: 1em; }}\n\
+                th, td {{ border: 1px solid #999; padding: 0.2em 0.5em; text-align: left; }}\n\
+                </style>\n</head>\n<body>\n{body}</body>\n</html>\n"
+            ),
+        }
+    }
+
+    /// Renders a table of all devices and their most relevant capabilities
+    ///
+    /// Meant to be pasted into the README of a project to document the hardware it supports.
+    /// Properties which were not queried are shown as `?`.
+    /// ```
+    /// use opencl3_select::{ClState, DeviceInfo, PlatformInfo, ReportFormat};
+    /// let state = ClState::from_platforms(vec![PlatformInfo::builder()
+    ///     .name("PoCL")
+    ///     .device(
+    ///         DeviceInfo::builder()
+    ///             .name("cpu-haswell")
+    ///             .extensions("cl_khr_fp64")
+    ///             .svm_mem_capability(1u64)
+    ///             .global_mem_size(8u64 << 30)
+    ///             .build(),
+    ///     )
+    ///     .build()]);
+    /// let matrix = state.capability_matrix(ReportFormat::Markdown);
+    /// assert!(matrix.starts_with("| Device | Platform | Type | OpenCL | fp64 |"));
+    /// assert!(matrix.contains(
+    ///     "| cpu-haswell | PoCL | GPU | 3.0 | yes | no | ? | coarse_grain_buffer | 8.0 GiB | ? |"
+    /// ));
+    /// ```
+    pub fn capability_matrix(&self, format: ReportFormat) -> String {
+        let rows: Vec<_> = self
+            .platforms_ref()
+            .iter()
+            .flat_map(|platform| {
+                platform.devices_ref().iter().map(|device| {
+                    let supports =
+                        |extension| device.extensions_ref().map(|_| device.supports(extension));
+                    vec![
+                        device.name(),
+                        platform.name(),
+                        device
+                            .type_text_ref()
+                            .trim_start_matches("CL_DEVICE_TYPE_")
+                            .to_string(),
+                        crate::OpenClVersion::parse(device.version_ref())
+                            .map(|version| version.to_string())
+                            .unwrap_or_default(),
+                        yes_no(supports("fp64")),
+                        yes_no(supports("fp16")),
+                        yes_no(device.image_support()),
+                        device
+                            .svm_capabilities()
+                            .map_or("?".into(), |svm| svm.to_string()),
+                        device
+                            .global_mem_size()
+                            .map_or("?".into(), crate::format_bytes),
+                        device
+                            .max_mem_alloc_size()
+                            .map_or("?".into(), crate::format_bytes),
+                    ]
+                })
+            })
+            .collect();
+        table(format, MATRIX_HEADER, &rows)
+    }
+
+    /// Writes the [report](ClState::report) to the given path
+    /// ```no_run
+    /// # #[cfg(feature = "opencl")] {
+    /// use opencl3_select::ReportFormat;
+    /// let cl_state = opencl3_select::get_setup()?;
+    /// let selection = cl_state.get_all_devices();
+    /// cl_state.write_report("opencl.md", ReportFormat::Markdown, &selection)?;
+    /// # }
+    /// # Ok::<(), opencl3_select::ClSelectError>(())
+    /// ```
+    pub fn write_report(
+        &self,
+        path: impl AsRef<Path>,
+        format: ReportFormat,
+        selection: &[DeviceInfo],
+    ) -> crate::Result<()> {
+        std::fs::write(path, self.report(format, selection))
+            .map_err(|error| StorageError::Report(error).into())
+    }
+}
@@ -0,0 +1,728 @@
+//! Configure which devices are taken into account when selecting
+
+use regex::{Regex, RegexSet};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ChannelOrder, ChannelType, ClState, DeviceInfo, ImageFormat, Info, QueryLevel, SelectionError,
+};
+
+/// Settings which control how devices are enumerated and selected
+///
+/// All regular expressions use the syntax of the [regex] crate and match
+/// anywhere inside of the vendor or name of a device.
+/// ```
+/// use opencl3_select::SelectConfig;
+/// let config = SelectConfig {
+///     deny_names: vec!["Oclgrind".into(), "^pthread".into()],
+///     ..Default::default()
+/// };
+/// let filter = config.device_filter()?;
+/// assert!(filter.allows("NVIDIA Corporation", "NVIDIA GeForce RTX 3060"));
+/// assert!(!filter.allows("Oclgrind", "Oclgrind Simulator"));
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(default))]
+pub struct SelectConfig {
+    /// If not empty, only devices whose vendor matches one of these are considered
+    pub allow_vendors: Vec<String>,
+    /// If not empty, only devices whose name matches one of these are considered
+    pub allow_names: Vec<String>,
+    /// Devices whose vendor matches one of these are never considered
+    pub deny_vendors: Vec<String>,
+    /// Devices whose name matches one of these are never considered
+    pub deny_names: Vec<String>,
+    /// Vendors whose platforms are listed first and win ties of [AutoSelect], eg. `NVIDIA`
+    ///
+    /// See [ClState::sort_platforms_by_vendor_preference].
+    pub prefer_vendors: Vec<String>,
+    /// Device properties which are queried during enumeration
+    pub query: QueryLevel,
+    /// Milliseconds after which platforms still being enumerated are skipped
+    ///
+    /// Guards against ICDs which deadlock, skipped platforms are listed in
+    /// [ClState::enumeration_errors]. Without a timeout enumeration waits for every platform.
+    pub enumeration_timeout: Option<u64>,
+}
+
+impl SelectConfig {
+    /// Compiles the allow and deny lists into a [DeviceFilter]
+    pub fn device_filter(&self) -> crate::Result<DeviceFilter> {
+        Ok(DeviceFilter {
+            allow_vendors: RegexSet::new(&self.allow_vendors)?,
+            allow_names: RegexSet::new(&self.allow_names)?,
+            deny_vendors: RegexSet::new(&self.deny_vendors)?,
+            deny_names: RegexSet::new(&self.deny_names)?,
+        })
+    }
+
+    /// Removes all devices from the state which are not allowed by this config and sorts the
+    /// platforms by the [preferred vendors](SelectConfig::prefer_vendors)
+    pub fn apply(&self, cl_state: &mut ClState) -> crate::Result<()> {
+        cl_state.sort_platforms_by_vendor_preference(&self.prefer_vendors);
+        let filter = self.device_filter()?;
+        cl_state.retain_devices(|device| {
+            let allowed = filter.allows_device(device);
+            #[cfg(feature = "tracing")]
+            if !allowed {
+                tracing::debug!(
+                    device = device.name_ref(),
+                    "device excluded by allow and deny lists"
+                );
+            }
+            allowed
+        });
+        Ok(())
+    }
+}
+
+/// Compiled allow and deny lists of a [SelectConfig]
+#[derive(Clone, Debug)]
+pub struct DeviceFilter {
+    /// See [SelectConfig::allow_vendors]
+    allow_vendors: RegexSet,
+    /// See [SelectConfig::allow_names]
+    allow_names: RegexSet,
+    /// See [SelectConfig::deny_vendors]
+    deny_vendors: RegexSet,
+    /// See [SelectConfig::deny_names]
+    deny_names: RegexSet,
+}
+
+impl DeviceFilter {
+    /// Checks if a device with the given vendor and name may be considered
+    pub fn allows(&self, vendor: &str, name: &str) -> bool {
+        let allowed = (self.allow_vendors.is_empty() || self.allow_vendors.is_match(vendor))
+            && (self.allow_names.is_empty() || self.allow_names.is_match(name));
+        let denied = self.deny_vendors.is_match(vendor) || self.deny_names.is_match(name);
+        allowed && !denied
+    }
+
+    /// See [allows](DeviceFilter::allows)
+    pub fn allows_device(&self, device: &DeviceInfo) -> bool {
+        self.allows(device.vendor_ref(), device.name_ref())
+    }
+}
+
+/// Requirements and preferences by which a single device is chosen without user interaction
+///
+/// Devices which do not [support](DeviceInfo::supports) every requirement are discarded.
+/// The remaining devices are ranked by
+/// 1. the first matching entry of [prefer](AutoSelect::prefer),
+/// 2. the total score of custom [DeviceScorer]s, see [best_with](AutoSelect::best_with),
+/// 3. their position in a previously stored selection,
+/// 4. their type (GPU, accelerator, CPU) and amount of global memory.
+///
+/// Remaining ties keep the order of the given devices, eg. the platforms sorted by
+/// [SelectConfig::prefer_vendors].
+#[cfg_attr(feature = "serde", doc = "```")]
+#[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+/// use opencl3_select::{AutoSelect, DeviceInfo};
+/// let device = |name: &str, type_text: &str, extensions: &str| -> DeviceInfo {
+///     serde_json::from_value(serde_json::json!({
+///         "vendor": "Vendor", "vendor_id": 0, "vendor_id_text": "", "name": name,
+///         "version": "OpenCL 3.0", "type": 0, "type_text": type_text, "profile": "",
+///         "extensions": extensions, "opencl_c_version": "", "svm_mem_capability": 0,
+///     }))
+///     .unwrap()
+/// };
+/// let devices = [
+///     device("cpu", "CL_DEVICE_TYPE_CPU", "cl_khr_fp64"),
+///     device("gpu", "CL_DEVICE_TYPE_GPU", "cl_khr_fp16"),
+/// ];
+/// let auto = AutoSelect::default();
+/// assert_eq!(auto.best(&devices, &[]).unwrap().name(), "gpu");
+/// assert_eq!(auto.best(&devices, &devices[..1]).unwrap().name(), "cpu");
+///
+/// let auto = AutoSelect {
+///     require: vec!["fp64".into()],
+///     ..Default::default()
+/// };
+/// assert_eq!(auto.best(&devices, &[]).unwrap().name(), "cpu");
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(default))]
+pub struct AutoSelect {
+    /// Extensions, capabilities or OpenCL C features which the device has to support, eg. `fp64`
+    /// or `__opencl_c_generic_address_space`
+    pub require: Vec<String>,
+    /// Preferred device types from most to least preferred, eg. `gpu`
+    pub prefer: Vec<String>,
+    /// Limits the device has to reach in addition to the required capabilities
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub requirements: Requirements,
+}
+
+impl AutoSelect {
+    /// Checks if the device supports all requirements
+    pub fn accepts(&self, device: &DeviceInfo) -> bool {
+        let missing = self
+            .require
+            .iter()
+            .find(|requirement| !device.supports(requirement));
+        #[cfg(feature = "tracing")]
+        if let Some(requirement) = missing {
+            tracing::debug!(
+                device = device.name_ref(),
+                requirement,
+                "device does not fulfill requirement"
+            );
+        }
+        missing.is_none() && self.requirements.accepts(device)
+    }
+
+    /// The first requirement the device does not fulfill, eg. `requirement fp64`
+    pub fn rejection(&self, device: &DeviceInfo) -> Option<String> {
+        self.require
+            .iter()
+            .find(|requirement| !device.supports(requirement))
+            .map(|requirement| format!("requirement {requirement}"))
+            .or_else(|| self.requirements.rejection(device))
+    }
+
+    /// Traces how [rank](AutoSelect::rank) arrives at the best device
+    ///
+    /// Lists the stored priorities which are missing, the devices eliminated by a requirement or
+    /// scorer and the order of the remaining devices.
+    /// ```
+    /// use opencl3_select::{AutoSelect, DeviceInfo};
+    /// let devices = [
+    ///     DeviceInfo::builder().name("igpu").r#type(4).build(),
+    ///     DeviceInfo::builder().name("cpu").r#type(2).extensions("cl_khr_fp64").build(),
+    /// ];
+    /// let missing = DeviceInfo::builder().name("dgpu").build();
+    /// let auto = AutoSelect {
+    ///     require: vec!["fp64".into()],
+    ///     ..Default::default()
+    /// };
+    /// let explanation = auto.explain(&devices, &[missing], &[]);
+    /// assert_eq!(explanation.steps[0], "saved priority #1 dgpu is missing");
+    /// assert_eq!(explanation.steps[1], "requirement fp64 eliminated igpu");
+    /// assert_eq!(explanation.chosen.unwrap().name(), "cpu");
+    /// ```
+    pub fn explain(
+        &self,
+        devices: &[DeviceInfo],
+        priorities: &[DeviceInfo],
+        scorers: &[&dyn DeviceScorer],
+    ) -> Explanation {
+        let mut steps = vec![];
+        for (i, priority) in priorities.iter().enumerate() {
+            if !devices.contains(priority) {
+                steps.push(format!(
+                    "saved priority #{} {} is missing",
+                    i + 1,
+                    priority.name_ref()
+                ));
+            }
+        }
+        for device in devices {
+            if let Some(rejection) = self.rejection(device) {
+                steps.push(format!("{rejection} eliminated {}", device.name_ref()));
+            } else if let Some(scorer) =
+                scorers.iter().find(|scorer| scorer.score(device).is_none())
+            {
+                steps.push(format!(
+                    "scorer {} rejected {}",
+                    scorer.name(),
+                    device.name_ref()
+                ));
+            }
+        }
+        let ranked = self.rank(devices, priorities, scorers);
+        if !ranked.is_empty() {
+            steps.push(format!("ranked the remaining {} devices", ranked.len()));
+        }
+        for (i, ranked) in ranked.iter().enumerate() {
+            steps.push(format!("#{} {ranked}", i + 1));
+        }
+        Explanation {
+            steps,
+            chosen: ranked.first().map(|ranked| ranked.device.clone()),
+        }
+    }
+
+    /// Chooses the best device which fulfills all requirements
+    ///
+    /// `priorities` is a stored selection ordered from highest to lowest priority.
+    pub fn best<'a>(
+        &self,
+        devices: &'a [DeviceInfo],
+        priorities: &[DeviceInfo],
+    ) -> Option<&'a DeviceInfo> {
+        self.best_with(devices, priorities, &[])
+    }
+
+    /// Variant of [best](AutoSelect::best) which also takes custom [DeviceScorer]s into account
+    pub fn best_with<'a>(
+        &self,
+        devices: &'a [DeviceInfo],
+        priorities: &[DeviceInfo],
+        scorers: &[&dyn DeviceScorer],
+    ) -> Option<&'a DeviceInfo> {
+        let best = self
+            .rank(devices, priorities, scorers)
+            .into_iter()
+            .next()
+            .map(|ranked| ranked.device);
+        #[cfg(feature = "tracing")]
+        match best {
+            Some(device) => tracing::info!(device = device.name_ref(), "chose device"),
+            None => tracing::info!("no device fulfills the requirements"),
+        }
+        best
+    }
+
+    /// All devices which fulfill the requirements from best to worst
+    ///
+    /// Devices rejected by one of the scorers are left out. The position of every criterion
+    /// is kept in the [RankedDevice] such that the order can be explained.
+    pub fn rank<'a>(
+        &self,
+        devices: &'a [DeviceInfo],
+        priorities: &[DeviceInfo],
+        scorers: &[&dyn DeviceScorer],
+    ) -> Vec<RankedDevice<'a>> {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("auto_select", require = ?self.require, prefer = ?self.prefer)
+                .entered();
+        let mut ranked: Vec<_> = devices
+            .iter()
+            .filter(|device| self.accepts(device))
+            .filter_map(|device| {
+                let score = scorers.iter().try_fold(0.0, |total, scorer| {
+                    let score = scorer.score(device);
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(
+                        device = device.name_ref(),
+                        scorer = scorer.name(),
+                        ?score,
+                        "scored device"
+                    );
+                    score.map(|score| total + score)
+                })?;
+                let ranked = RankedDevice {
+                    device,
+                    preference: self
+                        .prefer
+                        .iter()
+                        .position(|device_type| is_of_type(device, device_type)),
+                    score,
+                    priority: priorities.iter().position(|priority| priority == device),
+                    device_type: ["gpu", "accelerator", "cpu"]
+                        .iter()
+                        .position(|device_type| is_of_type(device, device_type))
+                        .unwrap_or(3),
+                };
+                #[cfg(feature = "tracing")]
+                tracing::trace!(device = device.name_ref(), %ranked, "ranked device");
+                Some(ranked)
+            })
+            .collect();
+        let preference = |ranked: &RankedDevice| ranked.preference.unwrap_or(self.prefer.len());
+        let tie_break = |ranked: &RankedDevice| {
+            (
+                ranked.priority.unwrap_or(priorities.len()),
+                ranked.device_type,
+                std::cmp::Reverse(ranked.device.global_mem_size().unwrap_or(0)),
+            )
+        };
+        ranked.sort_by(|a, b| {
+            preference(a)
+                .cmp(&preference(b))
+                .then(b.score.total_cmp(&a.score))
+                .then_with(|| tie_break(a).cmp(&tie_break(b)))
+        });
+        ranked
+    }
+}
+
+/// Memory limits and image formats which a device has to support to be selected by [AutoSelect]
+///
+/// Devices often report plenty of global memory but can only allocate a fraction of it in a
+/// single buffer. Every limit is raised by the safety [margin](Requirements::margin) before it is
+/// compared. Devices whose limits or image formats were not queried are rejected, the latter
+/// are only queried with [QueryLevel::Deep](crate::QueryLevel::Deep).
+/// ```
+/// use opencl3_select::{ChannelOrder, ChannelType, DeviceInfo, ImageFormat, Requirements};
+/// let device = DeviceInfo::builder()
+///     .global_mem_size(16u64 << 30)
+///     .max_mem_alloc_size(4u64 << 30)
+///     .build();
+/// assert!(Requirements::min_alloc(4 << 30).accepts(&device));
+/// assert!(!Requirements::min_alloc(4 << 30).with_margin(10).accepts(&device));
+/// assert!(!Requirements::min_alloc(8 << 30).accepts(&device));
+/// assert!(!Requirements::min_alloc(1).accepts(&DeviceInfo::builder().build()));
+/// assert!(Requirements::default().accepts(&DeviceInfo::builder().build()));
+///
+/// let images = Requirements::default().needs_image_format(ChannelOrder::Rgba, ChannelType::Float);
+/// let rgba = ImageFormat::new(ChannelOrder::Rgba, ChannelType::UnormInt8);
+/// assert_eq!(
+///     images.rejection(&DeviceInfo::builder().image_formats(vec![rgba]).build()).unwrap(),
+///     "image format RGBA/FLOAT"
+/// );
+/// let float = ImageFormat::new(ChannelOrder::Rgba, ChannelType::Float);
+/// assert!(images.accepts(&DeviceInfo::builder().image_formats(vec![rgba, float]).build()));
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(default))]
+pub struct Requirements {
+    /// Size in bytes of the largest buffer the device has to be able to allocate at once
+    pub min_alloc: Option<u64>,
+    /// Percentage by which every limit is raised before it is compared, eg. `10` for 10 %
+    pub margin: u32,
+    /// Formats of read-only 2D images the device has to support, eg. `RGBA/FLOAT`
+    pub image_formats: Vec<ImageFormat>,
+}
+
+impl Requirements {
+    /// Requires the device to allocate a single buffer of this many bytes
+    pub fn min_alloc(bytes: u64) -> Self {
+        Self {
+            min_alloc: Some(bytes),
+            ..Default::default()
+        }
+    }
+
+    /// Raises every limit by this percentage
+    pub fn with_margin(mut self, percent: u32) -> Self {
+        self.margin = percent;
+        self
+    }
+
+    /// Additionally requires the device to support images of this format
+    pub fn needs_image_format(mut self, order: ChannelOrder, data_type: ChannelType) -> Self {
+        self.image_formats.push(ImageFormat::new(order, data_type));
+        self
+    }
+
+    /// The limit raised by the [margin](Requirements::margin)
+    fn with_margin_applied(&self, limit: u64) -> u64 {
+        let raised = u128::from(limit) * (100 + u128::from(self.margin)) / 100;
+        raised.try_into().unwrap_or(u64::MAX)
+    }
+
+    /// The first limit the device does not reach, eg. `allocation of 8.0 GiB` or
+    /// `image format RGBA/FLOAT`
+    pub fn rejection(&self, device: &DeviceInfo) -> Option<String> {
+        if let Some(min_alloc) = self.min_alloc {
+            let required = self.with_margin_applied(min_alloc);
+            if device
+                .max_mem_alloc_size()
+                .is_none_or(|size| size < required)
+            {
+                return Some(format!("allocation of {}", crate::Bytes(required)));
+            }
+        }
+        let supported = device.image_formats_ref().unwrap_or_default();
+        self.image_formats
+            .iter()
+            .find(|format| !supported.contains(format))
+            .map(|format| format!("image format {format}"))
+    }
+
+    /// Checks if the device reaches all limits
+    pub fn accepts(&self, device: &DeviceInfo) -> bool {
+        let rejection = self.rejection(device);
+        #[cfg(feature = "tracing")]
+        if let Some(rejection) = &rejection {
+            tracing::debug!(
+                device = device.name_ref(),
+                rejection,
+                max_mem_alloc_size = device.max_mem_alloc_size(),
+                "device does not reach a limit"
+            );
+        }
+        rejection.is_none()
+    }
+}
+
+/// Custom heuristic which takes part in the ranking of [AutoSelect]
+///
+/// The scores of all scorers are summed up, devices with a higher total are preferred after the
+/// [preferred types](AutoSelect::prefer) and before the stored priorities.
+#[cfg_attr(feature = "serde", doc = "```")]
+#[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+/// use opencl3_select::{AutoSelect, DeviceInfo, DeviceScorer};
+/// /// Avoids devices which are known to be busy
+/// struct Busy(Vec<String>);
+///
+/// impl DeviceScorer for Busy {
+///     fn name(&self) -> &str {
+///         "busy"
+///     }
+///
+///     fn score(&self, device: &DeviceInfo) -> Option<f64> {
+///         Some(if self.0.contains(&device.name()) { -1.0 } else { 0.0 })
+///     }
+/// }
+///
+/// let device = |name: &str| -> DeviceInfo {
+///     serde_json::from_value(serde_json::json!({
+///         "vendor": "Vendor", "vendor_id": 0, "vendor_id_text": "", "name": name,
+///         "version": "OpenCL 3.0", "type": 4, "type_text": "CL_DEVICE_TYPE_GPU",
+///     }))
+///     .unwrap()
+/// };
+/// let devices = [device("first"), device("second")];
+/// let busy = Busy(vec!["first".into()]);
+/// let auto = AutoSelect::default();
+/// assert_eq!(auto.best(&devices, &[]).unwrap().name(), "first");
+/// assert_eq!(auto.best_with(&devices, &[], &[&busy]).unwrap().name(), "second");
+/// ```
+pub trait DeviceScorer {
+    /// Short name of the heuristic used in diagnostics, eg. `wgpu adapter`
+    fn name(&self) -> &str;
+
+    /// Score of the device where higher is better, `None` rejects the device
+    fn score(&self, device: &DeviceInfo) -> Option<f64>;
+}
+
+/// A device together with the criteria by which [AutoSelect::rank] ordered it
+#[derive(Clone, Debug, PartialEq)]
+pub struct RankedDevice<'a> {
+    /// The ranked device
+    pub device: &'a DeviceInfo,
+    /// Position of the first matching [preferred type](AutoSelect::prefer)
+    pub preference: Option<usize>,
+    /// Sum of the scores of all [DeviceScorer]s
+    pub score: f64,
+    /// Position in the stored priorities
+    pub priority: Option<usize>,
+    /// Position of the device type in GPU, accelerator, CPU or `3` for other types
+    pub device_type: usize,
+}
+
+impl std::fmt::Display for RankedDevice<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let position = |position: Option<usize>| match position {
+            Some(position) => format!("#{}", position + 1),
+            None => "-".into(),
+        };
+        write!(
+            f,
+            "{}: preference {}, score {}, priority {}, type {}",
+            self.device.name_ref(),
+            position(self.preference),
+            self.score,
+            position(self.priority),
+            self.device.type_text_ref(),
+        )?;
+        if let Some(memory) = self.device.global_mem_size() {
+            write!(f, ", memory {}", crate::Bytes(memory))?;
+        }
+        Ok(())
+    }
+}
+
+/// Step-by-step trace of how a device was chosen, eg. by [AutoSelect::explain]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Explanation {
+    /// Steps in the order in which they were taken
+    pub steps: Vec<String>,
+    /// The chosen device, `None` if no device fulfills the requirements
+    pub chosen: Option<DeviceInfo>,
+}
+
+impl std::fmt::Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(f, "{}. {step}", i + 1)?;
+        }
+        match &self.chosen {
+            Some(device) => writeln!(f, "=> chose {}", device.name_ref()),
+            None => writeln!(f, "=> no device fulfills the requirements"),
+        }
+    }
+}
+
+/// Identifies a single device without user interaction
+#[cfg_attr(feature = "serde", doc = "```")]
+#[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+/// use opencl3_select::{ClSelectError, ClState, DeviceSpec, SelectionError};
+/// let state: ClState = serde_json::from_str(r#"{"platforms": [{
+///     "name": "PoCL", "vendor": "The pocl project", "version": "OpenCL 3.0 PoCL",
+///     "profile": "FULL_PROFILE", "extensions": "", "devices": [
+///         {"platform": "PoCL", "vendor": "AuthenticAMD", "vendor_id": 4098,
+///          "vendor_id_text": "AMD", "name": "cpu-haswell", "version": "OpenCL 3.0 PoCL",
+///          "type": 2, "type_text": "CL_DEVICE_TYPE_CPU"},
+///         {"platform": "PoCL", "vendor": "NVIDIA Corporation", "vendor_id": 4318,
+///          "vendor_id_text": "NVIDIA", "name": "NVIDIA GeForce RTX 3060",
+///          "version": "OpenCL 3.0 PoCL", "type": 4, "type_text": "CL_DEVICE_TYPE_GPU"}
+///     ]
+/// }]}"#)?;
+/// let spec = DeviceSpec::Regex {
+///     device: Some("(?i)nvidia".into()),
+///     platform: None,
+/// };
+/// assert_eq!(spec.find(&state)?.name(), "NVIDIA GeForce RTX 3060");
+///
+/// let spec = DeviceSpec::Regex {
+///     device: None,
+///     platform: Some("^PoCL$".into()),
+/// };
+/// assert!(matches!(
+///     spec.find(&state),
+///     Err(ClSelectError::Selection(SelectionError::AmbiguousDevice { candidates, .. }))
+///         if candidates.len() == 2
+/// ));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DeviceSpec {
+    /// The device whose name equals the given one
+    Name(String),
+    /// The device with the given [id](DeviceInfo::id)
+    Id(crate::DeviceId),
+    /// The device the user gave this nickname, see [DeviceNicknames](crate::DeviceNicknames)
+    Nickname {
+        /// Nickname as given by the user
+        nickname: String,
+        /// Id of the device the nickname belongs to
+        device: crate::DeviceId,
+    },
+    /// The device whose name, vendor or version matches `device` on a platform whose name,
+    /// vendor or version matches `platform`
+    ///
+    /// Missing expressions match everything.
+    Regex {
+        /// Expression matched against the device
+        device: Option<String>,
+        /// Expression matched against the platform of the device
+        platform: Option<String>,
+    },
+}
+
+impl DeviceSpec {
+    /// Spec for the device named by the user
+    ///
+    /// `name:<nickname>` refers to the device with this nickname if there is one. Any other text
+    /// and unknown nicknames refer to the device with this name.
+    pub fn parse(text: &str, nicknames: &crate::DeviceNicknames) -> Self {
+        let Some(name) = text.strip_prefix("name:") else {
+            return Self::Name(text.to_string());
+        };
+        match nicknames.get(name) {
+            Some(device) => Self::Nickname {
+                nickname: name.to_string(),
+                device: device.id(),
+            },
+            None => Self::Name(name.to_string()),
+        }
+    }
+
+    /// All devices of the state which fit the spec
+    pub fn candidates(&self, cl_state: &ClState) -> crate::Result<Vec<DeviceInfo>> {
+        let compile = |expression: &Option<String>| expression.as_deref().map(Regex::new);
+        let (device_regex, platform_regex) = match self {
+            Self::Name(name) => {
+                return Ok(cl_state
+                    .get_all_devices()
+                    .into_iter()
+                    .filter(|device| Info::name(device) == name)
+                    .collect())
+            }
+            Self::Id(id) => return Ok(cl_state.device(*id).into_iter().cloned().collect()),
+            Self::Nickname { device, .. } => {
+                return Ok(cl_state
+                    .get_all_devices()
+                    .into_iter()
+                    .filter(|candidate| candidate.id() == *device)
+                    .collect())
+            }
+            Self::Regex { device, platform } => {
+                (compile(device).transpose()?, compile(platform).transpose()?)
+            }
+        };
+        Ok(cl_state
+            .platforms_ref()
+            .iter()
+            .filter(|platform| {
+                platform_regex
+                    .as_ref()
+                    .is_none_or(|re| info_matches(re, *platform))
+            })
+            .flat_map(|platform| platform.devices_ref())
+            .filter(|device| {
+                device_regex
+                    .as_ref()
+                    .is_none_or(|re| info_matches(re, *device))
+            })
+            .cloned()
+            .collect())
+    }
+
+    /// The only device which fits the spec
+    ///
+    /// Fails with [SelectionError::DeviceNotFound] if no device fits and with
+    /// [SelectionError::AmbiguousDevice] listing the candidates if several do.
+    pub fn find(&self, cl_state: &ClState) -> crate::Result<DeviceInfo> {
+        let mut candidates = self.candidates(cl_state)?;
+        match candidates.len() {
+            0 => Err(SelectionError::DeviceNotFound(self.clone()).into()),
+            1 => Ok(candidates.remove(0)),
+            _ => Err(SelectionError::AmbiguousDevice {
+                spec: self.clone(),
+                candidates: candidates
+                    .iter()
+                    .map(|device| format!("{} ({})", device.name_ref(), device.platform_ref()))
+                    .collect(),
+            }
+            .into()),
+        }
+    }
+}
+
+impl std::fmt::Display for DeviceSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{name}"),
+            Self::Id(id) => write!(f, "{id}"),
+            Self::Nickname { nickname, .. } => write!(f, "name:{nickname}"),
+            Self::Regex { device, platform } => {
+                let parts: Vec<_> = [("device", device), ("platform", platform)]
+                    .into_iter()
+                    .filter_map(|(label, expression)| {
+                        expression.as_ref().map(|re| format!("{label} /{re}/"))
+                    })
+                    .collect();
+                write!(f, "{}", parts.join(" on "))
+            }
+        }
+    }
+}
+
+/// Checks if the regex matches the name, vendor or version
+fn info_matches(regex: &Regex, info: &impl Info) -> bool {
+    [info.name(), info.vendor(), info.version()]
+        .into_iter()
+        .any(|text| regex.is_match(text))
+}
+
+/// Checks if the device is of the given type such as `gpu` or `cpu`
+fn is_of_type(device: &DeviceInfo, device_type: &str) -> bool {
+    device
+        .type_text_ref()
+        .to_lowercase()
+        .ends_with(&device_type.to_lowercase())
+}
+
+/// Variant of [get_setup](crate::get_setup) which only keeps devices allowed by the config
+///
+/// Only the device properties of [SelectConfig::query] are queried, see
+/// [SelectConfig::enumeration_timeout] for giving up on platforms which hang.
+#[cfg(feature = "opencl")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "opencl")))]
+pub fn get_setup_with_config(config: &SelectConfig) -> crate::Result<ClState> {
+    let mut cl_state = match config.enumeration_timeout {
+        Some(timeout) => {
+            crate::get_setup_with_timeout(config.query, std::time::Duration::from_millis(timeout))?
+        }
+        None => crate::get_setup_with_level(config.query)?,
+    };
+    config.apply(&mut cl_state)?;
+    Ok(cl_state)
+}
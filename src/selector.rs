@@ -0,0 +1,275 @@
+//! Keeps the selected device up to date for long-running applications
+
+#[cfg(feature = "serde")]
+use std::path::PathBuf;
+
+use crate::{
+    ClSelectError, ClState, DeviceInfo, DeviceSpec, Explanation, SelectionError, SelectionSpec,
+};
+
+/// Why the selected device changed, see [SelectionChange]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChangeCause {
+    /// The device was resolved for the first time
+    Initial,
+    /// The environment variable overriding the selection was set, changed or removed
+    EnvOverride,
+    /// The spec file was edited
+    SpecFile,
+    /// Devices were added, removed or updated
+    Hardware,
+}
+
+/// Previously and newly selected device passed to the callbacks of [Selector::on_change]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectionChange {
+    /// Device selected before, `None` if no device fulfilled the spec
+    pub old: Option<DeviceInfo>,
+    /// Device selected now, `None` if no device fulfills the spec anymore
+    pub new: Option<DeviceInfo>,
+    /// Input whose change caused the new selection
+    pub cause: ChangeCause,
+}
+
+/// Observer registered with [Selector::on_change]
+type ChangeCallback = Box<dyn FnMut(&SelectionChange) + Send + Sync>;
+
+/// Inputs of the last resolution, compared to find the [ChangeCause]
+#[derive(Clone, Debug)]
+struct Inputs {
+    /// Spec as read from the spec file or given on construction
+    spec: SelectionSpec,
+    /// Value of the override variable if it was set
+    env: Option<String>,
+}
+
+/// Resolves the best device of a [SelectionSpec] again whenever its inputs change
+///
+/// The device is resolved by [update](Selector::update) from
+/// 1. the environment variable given to [env_override](Selector::env_override), which names
+///    the device to use,
+/// 2. the spec file given to [spec_file](Selector::spec_file), if it exists,
+/// 3. the spec given on construction otherwise.
+///
+/// Callbacks registered with [on_change](Selector::on_change) are called whenever the resolved
+/// device differs from the previous one, such that work can be migrated to the new device.
+/// ```
+/// use opencl3_select::{ChangeCause, ClState, DeviceInfo, PlatformInfo, Selector, SelectionSpec};
+/// use std::sync::{Arc, Mutex};
+/// let state = ClState::from_platforms(vec![PlatformInfo::builder()
+///     .name("PoCL")
+///     .device(DeviceInfo::builder().name("cpu-haswell").build())
+///     .device(DeviceInfo::builder().name("cpu-skylake").build())
+///     .build()]);
+/// let changes = Arc::new(Mutex::new(vec![]));
+/// let mut selector = Selector::new(SelectionSpec::default()).env_override("DOCTEST_DEVICE");
+/// let recorded = Arc::clone(&changes);
+/// selector.on_change(move |change| recorded.lock().unwrap().push(change.cause));
+///
+/// assert_eq!(selector.update(&state)?.unwrap().name(), "cpu-haswell");
+/// std::env::set_var("DOCTEST_DEVICE", "cpu-skylake");
+/// assert_eq!(selector.update(&state)?.unwrap().name(), "cpu-skylake");
+/// // Nothing changed, the callbacks are not called again
+/// selector.update(&state)?;
+/// assert_eq!(
+///     *changes.lock().unwrap(),
+///     [ChangeCause::Initial, ChangeCause::EnvOverride]
+/// );
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+pub struct Selector {
+    /// Spec used if there is no spec file
+    spec: SelectionSpec,
+    /// File from which the spec is read on every update
+    #[cfg(feature = "serde")]
+    spec_file: Option<PathBuf>,
+    /// Name of the environment variable overriding the spec
+    env_override: Option<String>,
+    /// Inputs of the last update, `None` before the first one
+    inputs: Option<Inputs>,
+    /// Currently selected device
+    current: Option<DeviceInfo>,
+    /// Observers called when the selected device changes
+    callbacks: Vec<ChangeCallback>,
+}
+
+impl Selector {
+    /// Selector resolving the given spec
+    pub fn new(spec: SelectionSpec) -> Self {
+        Self {
+            spec,
+            #[cfg(feature = "serde")]
+            spec_file: None,
+            env_override: None,
+            inputs: None,
+            current: None,
+            callbacks: vec![],
+        }
+    }
+
+    /// Reads the spec from this file on every update, its format is taken from its extension
+    ///
+    /// The spec given on construction is used while the file does not exist.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
+    pub fn spec_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.spec_file = Some(path.into());
+        self
+    }
+
+    /// Uses the device named by this environment variable instead of the spec if it is set
+    pub fn env_override(mut self, variable: impl Into<String>) -> Self {
+        self.env_override = Some(variable.into());
+        self
+    }
+
+    /// Registers a callback which is called whenever the selected device changes
+    pub fn on_change(
+        &mut self,
+        callback: impl FnMut(&SelectionChange) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// The device selected by the last [update](Selector::update)
+    pub fn current(&self) -> Option<&DeviceInfo> {
+        self.current.as_ref()
+    }
+
+    /// Resolves the device again from the current inputs and notifies the callbacks if it changed
+    ///
+    /// Returns `None` if no device fulfills the spec or the overriding device is missing. Fails
+    /// if the spec file cannot be read, the selection is kept in that case.
+    pub fn update(&mut self, cl_state: &ClState) -> crate::Result<Option<&DeviceInfo>> {
+        let inputs = Inputs {
+            spec: self.load_spec()?,
+            env: self
+                .env_override
+                .as_ref()
+                .and_then(|variable| std::env::var(variable).ok())
+                .filter(|value| !value.is_empty()),
+        };
+        let device = resolve(&inputs, cl_state)?;
+        let cause = match &self.inputs {
+            None => ChangeCause::Initial,
+            Some(old) if old.env != inputs.env => ChangeCause::EnvOverride,
+            Some(old) if old.spec != inputs.spec => ChangeCause::SpecFile,
+            Some(_) => ChangeCause::Hardware,
+        };
+        self.inputs = Some(inputs);
+        if device != self.current {
+            let change = SelectionChange {
+                old: std::mem::replace(&mut self.current, device.clone()),
+                new: device,
+                cause,
+            };
+            for callback in &mut self.callbacks {
+                callback(&change);
+            }
+        }
+        Ok(self.current.as_ref())
+    }
+
+    /// Traces how [update](Selector::update) would arrive at the device without changing the
+    /// selection
+    /// ```
+    /// use opencl3_select::{ClState, DeviceInfo, PlatformInfo, Selector, SelectionSpec};
+    /// let state = ClState::from_platforms(vec![PlatformInfo::builder()
+    ///     .name("PoCL")
+    ///     .device(DeviceInfo::builder().name("cpu-haswell").build())
+    ///     .build()]);
+    /// let selector = Selector::new(SelectionSpec::default()).env_override("DOCTEST_UNSET");
+    /// let explanation = selector.explain(&state)?;
+    /// assert_eq!(
+    ///     explanation.steps[0],
+    ///     "environment variable DOCTEST_UNSET ignored because it is unset"
+    /// );
+    /// assert_eq!(explanation.chosen.unwrap().name(), "cpu-haswell");
+    /// # Ok::<(), opencl3_select::ClSelectError>(())
+    /// ```
+    pub fn explain(&self, cl_state: &ClState) -> crate::Result<Explanation> {
+        let mut steps = vec![];
+        if let Some(variable) = &self.env_override {
+            match std::env::var(variable)
+                .ok()
+                .filter(|value| !value.is_empty())
+            {
+                Some(name) => {
+                    steps.push(format!("environment variable {variable} names \"{name}\""));
+                    let inputs = Inputs {
+                        spec: SelectionSpec::default(),
+                        env: Some(name),
+                    };
+                    return Ok(Explanation {
+                        steps,
+                        chosen: resolve(&inputs, cl_state)?,
+                    });
+                }
+                None => steps.push(format!(
+                    "environment variable {variable} ignored because it is unset"
+                )),
+            }
+        }
+        #[cfg(feature = "serde")]
+        if let Some(path) = &self.spec_file {
+            steps.push(match path.exists() {
+                true => format!("read the spec from {}", path.display()),
+                false => format!("spec file {} does not exist", path.display()),
+            });
+        }
+        let mut explanation = self.load_spec()?.explain(cl_state)?;
+        explanation.steps.splice(0..0, steps);
+        Ok(explanation)
+    }
+
+    /// Enumerates the devices of this machine and [updates](Selector::update) the selection
+    /// every `interval` until an error occurs
+    /// ```no_run
+    /// use opencl3_select::{Selector, SelectionSpec};
+    /// let mut selector = Selector::new(SelectionSpec::default()).env_override("OPENCL_DEVICE");
+    /// selector.on_change(|change| {
+    ///     if let Some(device) = &change.new {
+    ///         println!("migrating to {}", device.name());
+    ///     }
+    /// });
+    /// selector.watch(std::time::Duration::from_secs(10))?;
+    /// # Ok::<(), opencl3_select::ClSelectError>(())
+    /// ```
+    #[cfg(feature = "opencl")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "opencl")))]
+    pub fn watch(mut self, interval: std::time::Duration) -> crate::Result<()> {
+        loop {
+            let cl_state = crate::get_setup()?;
+            self.update(&cl_state)?;
+            std::thread::sleep(interval);
+        }
+    }
+
+    /// The spec from the spec file if it exists and the one given on construction otherwise
+    fn load_spec(&self) -> crate::Result<SelectionSpec> {
+        #[cfg(feature = "storage")]
+        if let Some(path) = self.spec_file.as_ref().filter(|path| path.exists()) {
+            return crate::load(path);
+        }
+        Ok(self.spec.clone())
+    }
+}
+
+/// The best device for the inputs, `None` if there is none
+fn resolve(inputs: &Inputs, cl_state: &ClState) -> crate::Result<Option<DeviceInfo>> {
+    let device = match &inputs.env {
+        Some(name) => DeviceSpec::Name(name.clone()).find(cl_state),
+        None => inputs
+            .spec
+            .resolve(cl_state)
+            .map(|devices| devices[0].clone()),
+    };
+    match device {
+        Ok(device) => Ok(Some(device)),
+        Err(ClSelectError::Selection(
+            SelectionError::NoMatchingDevice | SelectionError::DeviceNotFound(_),
+        )) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
@@ -0,0 +1,123 @@
+//! Answers device queries over a unix domain socket
+//!
+//! Every line received on a connection is one JSON query, every query is answered by one line
+//! of JSON. Successful queries are answered with `{"ok": <result>}`, failed ones with
+//! `{"error": {"kind": <kind>, "message": <message>}}`.
+//! ```text
+//! {"query": "list"}
+//! {"query": "auto", "require": ["fp64"], "prefer": ["gpu"]}
+//! {"query": "resolve", "name": "NVIDIA GeForce RTX 3060"}
+//! {"query": "get", "id": "5f3e2b1c0a9d8e7f"}
+//! ```
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::Arc;
+
+use opencl3_select::{
+    AutoSelect, ClState, DeviceId, DeviceSpec, Info, Result, SelectionError, StorageError,
+};
+use serde::Deserialize;
+
+use crate::{error_message, Exit};
+
+/// Query sent by a client
+#[derive(Debug, Deserialize)]
+#[serde(tag = "query", rename_all = "snake_case")]
+enum Query {
+    /// All platforms and their devices
+    List,
+    /// The best device as determined by [AutoSelect] and the most recent selection
+    Auto(AutoSelect),
+    /// The device with the given name, see [resolve]
+    Resolve {
+        /// Name of the device or a part of it
+        name: String,
+    },
+    /// The device with the given id
+    Get {
+        /// Id of the device as listed by `opencl3-select list -vv`
+        id: DeviceId,
+    },
+}
+
+/// Finds the device whose name equals the given one or, failing that, contains it ignoring case
+fn resolve(state: &ClState, name: &str) -> Result<serde_json::Value> {
+    let devices = state.get_all_devices();
+    let device = devices
+        .iter()
+        .find(|device| Info::name(*device) == name)
+        .or_else(|| devices.iter().find(|device| device.matches(name)))
+        .ok_or_else(|| SelectionError::DeviceNotFound(DeviceSpec::Name(name.to_string())))?;
+    Ok(serde_json::to_value(device)?)
+}
+
+/// Answers a single line of a client
+fn answer(state: &ClState, line: &str) -> Result<serde_json::Value> {
+    match serde_json::from_str(line)? {
+        Query::List => Ok(serde_json::to_value(state)?),
+        Query::Auto(auto) => {
+            let devices = state.get_all_devices();
+            let priorities = opencl3_select::saved_selection()?;
+            let device = auto
+                .best(&devices, &priorities)
+                .ok_or(SelectionError::NoMatchingDevice)?;
+            Ok(serde_json::to_value(device)?)
+        }
+        Query::Resolve { name } => resolve(state, &name),
+        Query::Get { id } => Ok(serde_json::to_value(DeviceSpec::Id(id).find(state)?)?),
+    }
+}
+
+/// Answers all queries of one connection until the client closes it
+fn handle(state: &ClState, stream: UnixStream) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match answer(state, &line) {
+            Ok(value) => serde_json::json!({ "ok": value }),
+            Err(error) => {
+                let (_, kind) = Exit::from_error(&error);
+                serde_json::json!({ "error": { "kind": kind, "message": error_message(&error) } })
+            }
+        };
+        writeln!(writer, "{response}")?;
+    }
+    Ok(())
+}
+
+/// Listens on the socket and answers queries until the process is terminated
+///
+/// A stale socket left behind by a previous run is replaced. Every connection is handled on
+/// its own thread such that slow clients do not block others.
+pub fn serve(state: ClState, path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(StorageError::Io)?;
+    }
+    if path.exists() {
+        std::fs::remove_file(path).map_err(StorageError::Io)?;
+    }
+    let listener = UnixListener::bind(path).map_err(StorageError::Io)?;
+    eprintln!("serving device queries on {}", path.display());
+    let state = Arc::new(state);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("could not accept connection: {error}");
+                continue;
+            }
+        };
+        let state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            if let Err(error) = handle(&state, stream) {
+                eprintln!("connection closed: {error}");
+            }
+        });
+    }
+    Ok(())
+}
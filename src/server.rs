@@ -0,0 +1,232 @@
+//! Minimal HTTP endpoint serving the OpenCL inventory of a node, eg. for cluster schedulers
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+
+use crate::{ClState, DeviceId, SelectionSpec, StorageError};
+
+/// Status and JSON body answering a single request, see [respond]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HttpResponse {
+    /// HTTP status code, eg. `200`
+    pub status: u16,
+    /// Serialized result or `{"error": <message>}`
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// Response with status `200` and the serialized value as body
+    fn ok(value: &impl serde::Serialize) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status: 200, body },
+            Err(error) => Self::error(500, &error),
+        }
+    }
+
+    /// Response with the given status and the error message as body
+    fn error(status: u16, error: &dyn std::fmt::Display) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": error.to_string() }).to_string(),
+        }
+    }
+
+    /// Reason phrase of the status code
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Answers a `GET` request for the given target, ie. its path and query
+///
+/// - `/devices` serves the whole [ClState].
+/// - `/devices/<id>` serves the device with the given [DeviceId].
+/// - `/auto-select` serves the devices fulfilling a [SelectionSpec] from best to worst. The
+///   requirements are taken from the query parameters `require`, `prefer`, `profile`,
+///   `min_alloc`, `margin`, `image_formats` and `count`, where lists are separated by commas, eg.
+///   `/auto-select?require=fp64&prefer=gpu,cpu&count=1`. Fails with status `404` if no device
+///   fulfills them.
+/// ```
+/// use opencl3_select::{ClState, DeviceInfo, PlatformInfo};
+/// let state = ClState::from_platforms(vec![PlatformInfo::builder()
+///     .name("PoCL")
+///     .device(DeviceInfo::builder().name("cpu-haswell").extensions("cl_khr_fp64").build())
+///     .build()]);
+/// let response = opencl3_select::respond(&state, "/auto-select?require=fp64&count=1");
+/// assert_eq!(response.status, 200);
+/// assert!(response.body.contains(r#""name":"cpu-haswell""#));
+///
+/// let response = opencl3_select::respond(&state, "/auto-select?require=fp16");
+/// assert_eq!(response.status, 404);
+/// assert_eq!(response.body, r#"{"error":"no device fulfills the requirements"}"#);
+///
+/// let id = state.get_all_devices()[0].id();
+/// let response = opencl3_select::respond(&state, &format!("/devices/{id}"));
+/// assert_eq!(response.status, 200);
+/// ```
+pub fn respond(state: &ClState, target: &str) -> HttpResponse {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let path = path.trim_end_matches('/');
+    if let Some(id) = path.strip_prefix("/devices/") {
+        return match id.parse::<DeviceId>() {
+            Ok(id) => match state.device(id) {
+                Some(device) => HttpResponse::ok(device),
+                None => HttpResponse::error(404, &format!("no device with id {id}")),
+            },
+            Err(message) => HttpResponse::error(400, &message),
+        };
+    }
+    match path {
+        "/devices" => HttpResponse::ok(state),
+        "/auto-select" => {
+            let spec = match selection_spec(query) {
+                Ok(spec) => spec,
+                Err(message) => return HttpResponse::error(400, &message),
+            };
+            match spec.resolve(state) {
+                Ok(devices) => HttpResponse::ok(&devices),
+                Err(error) => HttpResponse::error(404, &error),
+            }
+        }
+        _ => HttpResponse::error(404, &format!("unknown path \"{path}\"")),
+    }
+}
+
+/// Builds the spec described by the query parameters of `/auto-select`
+fn selection_spec(query: &str) -> Result<SelectionSpec, String> {
+    let mut spec = SelectionSpec::default();
+    for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+        let value = percent_decode(value);
+        let list = || value.split(',').map(str::to_string).collect::<Vec<_>>();
+        match key {
+            "require" => spec.auto.require.extend(list()),
+            "prefer" => spec.auto.prefer.extend(list()),
+            "profile" => spec.profile = Some(value.parse()?),
+            "min_alloc" => {
+                let bytes = value
+                    .parse()
+                    .map_err(|_| format!("invalid allocation size \"{value}\""))?;
+                spec.auto.requirements.min_alloc = Some(bytes);
+            }
+            "margin" => {
+                let percent = value
+                    .parse()
+                    .map_err(|_| format!("invalid margin \"{value}\""))?;
+                spec.auto.requirements.margin = percent;
+            }
+            "image_formats" => {
+                for format in list() {
+                    spec.auto.requirements.image_formats.push(format.parse()?);
+                }
+            }
+            "count" => {
+                let count = value
+                    .parse()
+                    .map_err(|_| format!("invalid count \"{value}\""))?;
+                spec.count = Some(count);
+            }
+            _ => return Err(format!("unknown query parameter \"{key}\"")),
+        }
+    }
+    Ok(spec)
+}
+
+/// Decodes `%XX` escapes and `+` of a query value, invalid escapes are kept as they are
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', None) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, None) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Reads one request of the connection and writes the response
+fn handle(state: &ClState, stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip the headers, requests are expected to have no body
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => respond(state, target),
+        (Some(method), Some(_)) => {
+            HttpResponse::error(405, &format!("method {method} is not allowed"))
+        }
+        _ => HttpResponse::error(400, &"malformed request"),
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+/// Serves the state over HTTP until the process is terminated, see [respond] for the endpoints
+///
+/// Every connection is answered on its own thread and closed after one request. The state is
+/// not refreshed, restart the server after changing drivers.
+/// ```no_run
+/// # #[cfg(feature = "opencl")] {
+/// let state = opencl3_select::get_setup()?;
+/// opencl3_select::serve_http(state, "0.0.0.0:7878")?;
+/// # }
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+pub fn serve_http(state: ClState, address: impl ToSocketAddrs) -> crate::Result<()> {
+    let listener = TcpListener::bind(address).map_err(StorageError::Io)?;
+    #[cfg(feature = "tracing")]
+    tracing::info!(address = ?listener.local_addr().ok(), "serving device inventory");
+    let state = Arc::new(state);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            let result = handle(&state, stream);
+            #[cfg(feature = "tracing")]
+            if let Err(error) = result {
+                tracing::warn!(%error, "could not answer request");
+            }
+            #[cfg(not(feature = "tracing"))]
+            let _ = result;
+        });
+    }
+    Ok(())
+}
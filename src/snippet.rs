@@ -0,0 +1,106 @@
+//! Source snippets which bake the selected device into a build
+
+use std::path::Path;
+
+use crate::{DeviceInfo, Result, StorageError};
+
+/// Format of a snippet naming the selected device, see [SnippetFormat::render]
+///
+/// Embedded and firmware-style projects decide on a device once per build instead of asking
+/// at runtime. The Rust snippet is included with `include!`, the env file is read by a build
+/// script which forwards the variables to the compiler:
+/// ```no_run
+/// // build.rs
+/// let env = std::fs::read_to_string("selected_device.env").unwrap();
+/// for line in env.lines().filter(|line| !line.starts_with('#')) {
+///     println!("cargo:rustc-env={line}");
+/// }
+/// println!("cargo:rerun-if-changed=selected_device.env");
+/// // The crate then reads env!("SELECTED_DEVICE")
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SnippetFormat {
+    /// Rust source with one `const` per property, written to `.rs` files
+    Rust,
+    /// `KEY=value` lines, written to `.env` files
+    Env,
+}
+
+impl SnippetFormat {
+    /// Format given by the extension of the path, `None` for other files
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        [Self::Rust, Self::Env]
+            .into_iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+
+    /// Extension of files in this format without the leading dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Rust => "rs",
+            Self::Env => "env",
+        }
+    }
+
+    /// Snippet defining `SELECTED_DEVICE`, `SELECTED_DEVICE_ID` and `SELECTED_PLATFORM`
+    /// ```
+    /// use opencl3_select::{DeviceInfo, PlatformInfo, SnippetFormat};
+    /// let platform = PlatformInfo::builder()
+    ///     .name("NVIDIA CUDA")
+    ///     .device(DeviceInfo::builder().name("NVIDIA \"RTX\" 3060").build())
+    ///     .build();
+    /// let device = &platform.devices_ref()[0];
+    ///
+    /// let rust = SnippetFormat::Rust.render(device);
+    /// assert!(rust.contains("pub const SELECTED_DEVICE: &str = \"NVIDIA \\\"RTX\\\" 3060\";\n"));
+    /// assert!(rust.contains("pub const SELECTED_PLATFORM: &str = \"NVIDIA CUDA\";\n"));
+    ///
+    /// let env = SnippetFormat::Env.render(device);
+    /// assert!(env.contains("SELECTED_DEVICE=NVIDIA \"RTX\" 3060\n"));
+    /// assert!(env.contains(&format!("SELECTED_DEVICE_ID={}\n", device.id())));
+    /// ```
+    pub fn render(&self, device: &DeviceInfo) -> String {
+        let id = device.id().to_string();
+        let properties = [
+            (
+                "SELECTED_DEVICE",
+                "Name of the selected device",
+                device.name_ref(),
+            ),
+            (
+                "SELECTED_DEVICE_ID",
+                "Id of the selected device, see `opencl3-select list -vv`",
+                id.as_str(),
+            ),
+            (
+                "SELECTED_PLATFORM",
+                "Platform of the selected device",
+                device.platform_ref(),
+            ),
+        ];
+        let mut out = String::new();
+        match self {
+            Self::Rust => {
+                out += "// Generated by opencl3-select, changes are overwritten\n";
+                for (name, doc, value) in properties {
+                    out += &format!("\n/// {doc}\npub const {name}: &str = {value:?};\n");
+                }
+            }
+            Self::Env => {
+                out += "# Generated by opencl3-select, changes are overwritten\n";
+                for (name, _, value) in properties {
+                    // Lines are the only separator, so values must not span several
+                    let value = value.replace(['\n', '\r'], " ");
+                    out += &format!("{name}={value}\n");
+                }
+            }
+        }
+        out
+    }
+
+    /// Writes the snippet for the device to the path
+    pub fn save(&self, device: &DeviceInfo, path: impl AsRef<Path>) -> Result<()> {
+        Ok(std::fs::write(path, self.render(device)).map_err(StorageError::Io)?)
+    }
+}
@@ -0,0 +1,175 @@
+//! Declarative selections which are resolved against a machine without user interaction
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AutoSelect, ClState, DeviceInfo, Explanation, Profile, ScoringWeights, SelectConfig,
+    SelectionError,
+};
+
+/// Describes which devices to select on any machine, eg. for configuration management tools
+///
+/// Devices are narrowed down by the allow and deny lists of [select](SelectionSpec::select),
+/// must fulfill the requirements of [auto](SelectionSpec::auto) and are ordered as described
+/// there. All fields are flattened when (de)serialized:
+/// ```toml
+/// deny_names = ["Oclgrind"]
+/// require = ["fp64"]
+/// prefer = ["gpu", "cpu"]
+/// profile = "compute"
+/// count = 1
+/// output = "selection.toml"
+///
+/// [env]
+/// OPENCL_DEVICE = "{name}"
+/// PYOPENCL_CTX = "{platform_index}:{device_index}"
+/// ```
+#[cfg_attr(feature = "storage-toml", doc = "```")]
+#[cfg_attr(not(feature = "storage-toml"), doc = "```ignore")]
+/// use opencl3_select::{ClState, SelectionSpec};
+/// let state: ClState = serde_json::from_str(r#"{"platforms": [{
+///     "name": "PoCL", "vendor": "The pocl project", "version": "OpenCL 3.0 PoCL",
+///     "profile": "FULL_PROFILE", "extensions": "", "devices": [{
+///         "platform": "PoCL", "vendor": "AuthenticAMD", "vendor_id": 4098,
+///         "vendor_id_text": "AMD", "name": "cpu-haswell", "version": "OpenCL 3.0 PoCL",
+///         "type": 2, "type_text": "CL_DEVICE_TYPE_CPU", "extensions": "cl_khr_fp64"
+///     }]
+/// }]}"#)?;
+/// let spec: SelectionSpec = toml::from_str(r#"
+///     require = ["fp64"]
+///     env = { OPENCL_DEVICE = "{name}", PYOPENCL_CTX = "{platform_index}:{device_index}" }
+/// "#)?;
+/// let devices = spec.resolve(&state)?;
+/// assert_eq!(devices[0].name(), "cpu-haswell");
+/// assert_eq!(
+///     spec.env_exports(&state, &devices[0]),
+///     [("OPENCL_DEVICE".into(), "cpu-haswell".into()), ("PYOPENCL_CTX".into(), "0:0".into())]
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(default))]
+pub struct SelectionSpec {
+    /// Devices which are taken into account and how they are enumerated
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub select: SelectConfig,
+    /// Requirements and preferred device types
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub auto: AutoSelect,
+    /// Built-in requirements and weights which are added to [auto](SelectionSpec::auto)
+    pub profile: Option<Profile>,
+    /// Maximum number of selected devices, all matching devices if missing
+    pub count: Option<usize>,
+    /// File the selection is written to, the format is taken from its extension
+    pub output: Option<PathBuf>,
+    /// Environment variables to export for the best device
+    ///
+    /// Values may contain the placeholders `{name}`, `{vendor}`, `{platform}`, `{version}`,
+    /// `{platform_index}` and `{device_index}`, the indices refer to the
+    /// [canonical order](ClState::canonicalize).
+    pub env: BTreeMap<String, String>,
+}
+
+impl SelectionSpec {
+    /// Devices fulfilling the spec from best to worst
+    ///
+    /// Fails with [SelectionError::NoMatchingDevice] if there are none.
+    pub fn resolve(&self, cl_state: &ClState) -> crate::Result<Vec<DeviceInfo>> {
+        let filter = self.select.device_filter()?;
+        let devices: Vec<_> = self
+            .sorted_by_vendor(cl_state)
+            .get_all_devices()
+            .into_iter()
+            .filter(|device| filter.allows_device(device))
+            .collect();
+        let (auto, weights) = self.auto_with_weights();
+        let selection: Vec<_> = auto
+            .rank(&devices, &[], &[&weights])
+            .into_iter()
+            .take(self.count.unwrap_or(usize::MAX))
+            .map(|ranked| ranked.device.clone())
+            .collect();
+        match selection.is_empty() {
+            true => Err(SelectionError::NoMatchingDevice.into()),
+            false => Ok(selection),
+        }
+    }
+
+    /// Traces how [resolve](SelectionSpec::resolve) arrives at the best device
+    ///
+    /// See [AutoSelect::explain], the devices excluded by the allow and deny lists are listed
+    /// first.
+    pub fn explain(&self, cl_state: &ClState) -> crate::Result<Explanation> {
+        let filter = self.select.device_filter()?;
+        let (allowed, excluded): (Vec<_>, Vec<_>) = self
+            .sorted_by_vendor(cl_state)
+            .get_all_devices()
+            .into_iter()
+            .partition(|device| filter.allows_device(device));
+        let mut steps: Vec<_> = excluded
+            .iter()
+            .map(|device| format!("allow and deny lists excluded {}", device.name_ref()))
+            .collect();
+        if let Some(profile) = self.profile {
+            steps.push(format!(
+                "profile {profile} added its requirements and weights"
+            ));
+        }
+        let (auto, weights) = self.auto_with_weights();
+        let mut explanation = auto.explain(&allowed, &[], &[&weights]);
+        explanation.steps.splice(0..0, steps);
+        Ok(explanation)
+    }
+
+    /// Requirements and weights including those of the [profile](SelectionSpec::profile)
+    fn auto_with_weights(&self) -> (AutoSelect, ScoringWeights) {
+        match self.profile {
+            Some(profile) => (self.auto.clone().with_profile(profile), profile.weights()),
+            None => (self.auto.clone(), ScoringWeights::default()),
+        }
+    }
+
+    /// Copy of the state with the platforms of the [preferred vendors](SelectConfig::prefer_vendors)
+    /// first
+    fn sorted_by_vendor(&self, cl_state: &ClState) -> ClState {
+        let mut sorted = cl_state.clone();
+        sorted.sort_platforms_by_vendor_preference(&self.select.prefer_vendors);
+        sorted
+    }
+
+    /// The [env](SelectionSpec::env) variables with their placeholders filled in for the device
+    pub fn env_exports(&self, cl_state: &ClState, device: &DeviceInfo) -> Vec<(String, String)> {
+        let (platform_index, device_index) = cl_state
+            .platforms_ref()
+            .iter()
+            .enumerate()
+            .find_map(|(i, platform)| {
+                let j = platform.devices_ref().iter().position(|d| d == device)?;
+                Some((i.to_string(), j.to_string()))
+            })
+            .unwrap_or_default();
+        let placeholders = [
+            ("{name}", device.name_ref()),
+            ("{vendor}", device.vendor_ref()),
+            ("{platform}", device.platform_ref()),
+            ("{version}", device.version_ref()),
+            ("{platform_index}", &platform_index),
+            ("{device_index}", &device_index),
+        ];
+        self.env
+            .iter()
+            .map(|(key, template)| {
+                let value = placeholders
+                    .iter()
+                    .fold(template.clone(), |value, (placeholder, replacement)| {
+                        value.replace(placeholder, replacement)
+                    });
+                (key.clone(), value)
+            })
+            .collect()
+    }
+}
@@ -1 +1,498 @@
+//! Save and load serializable values such as [ClState](crate::ClState) to and from disk
 
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{ClState, DeviceInfo, PlatformInfo, Result, StorageError, UniquePriorityList};
+
+/// File formats supported by the storage module
+///
+/// Every format is behind its own feature, `storage-json`, `storage-toml` and `storage-yaml`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    /// [JSON](https://www.json.org)
+    #[cfg(feature = "storage-json")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage-json")))]
+    Json,
+    /// [TOML](https://toml.io)
+    #[cfg(feature = "storage-toml")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage-toml")))]
+    Toml,
+    /// [YAML](https://yaml.org)
+    #[cfg(feature = "storage-yaml")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "storage-yaml")))]
+    Yaml,
+}
+
+impl StorageFormat {
+    /// All formats enabled by the features of this build
+    pub const ALL: &'static [Self] = &[
+        #[cfg(feature = "storage-json")]
+        Self::Json,
+        #[cfg(feature = "storage-toml")]
+        Self::Toml,
+        #[cfg(feature = "storage-yaml")]
+        Self::Yaml,
+    ];
+
+    /// Name of the format as it is used as file extension
+    pub fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "storage-json")]
+            Self::Json => "json",
+            #[cfg(feature = "storage-toml")]
+            Self::Toml => "toml",
+            #[cfg(feature = "storage-yaml")]
+            Self::Yaml => "yaml",
+        }
+    }
+
+    /// Determine the format from the extension of the given path
+    #[cfg_attr(all(feature = "storage-json", feature = "storage-yaml"), doc = "```")]
+    #[cfg_attr(
+        not(all(feature = "storage-json", feature = "storage-yaml")),
+        doc = "```ignore"
+    )]
+    /// use opencl3_select::StorageFormat;
+    /// assert_eq!(StorageFormat::from_path("selection.json"), Some(StorageFormat::Json));
+    /// assert_eq!(StorageFormat::from_path("selection.yml"), Some(StorageFormat::Yaml));
+    /// assert_eq!(StorageFormat::from_path("selection"), None);
+    /// ```
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            #[cfg(feature = "storage-json")]
+            "json" => Some(Self::Json),
+            #[cfg(feature = "storage-toml")]
+            "toml" => Some(Self::Toml),
+            #[cfg(feature = "storage-yaml")]
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Serialize a value into a human-readable string of this format
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<String> {
+        Ok(match self {
+            #[cfg(feature = "storage-json")]
+            Self::Json => serde_json::to_string_pretty(value)?,
+            #[cfg(feature = "storage-toml")]
+            Self::Toml => toml::to_string_pretty(value)?,
+            #[cfg(feature = "storage-yaml")]
+            Self::Yaml => serde_yaml::to_string(value)?,
+        })
+    }
+
+    /// Deserialize a value from a string of this format
+    pub fn decode<T: DeserializeOwned>(&self, contents: &str) -> Result<T> {
+        Ok(match self {
+            #[cfg(feature = "storage-json")]
+            Self::Json => serde_json::from_str(contents)?,
+            #[cfg(feature = "storage-toml")]
+            Self::Toml => toml::from_str(contents)?,
+            #[cfg(feature = "storage-yaml")]
+            Self::Yaml => serde_yaml::from_str(contents)?,
+        })
+    }
+}
+
+impl std::str::FromStr for StorageFormat {
+    type Err = String;
+
+    /// Parses the name of a format as it is used as file extension
+    #[cfg_attr(feature = "storage-yaml", doc = "```")]
+    #[cfg_attr(not(feature = "storage-yaml"), doc = "```ignore")]
+    /// use opencl3_select::StorageFormat;
+    /// assert_eq!("yaml".parse(), Ok(StorageFormat::Yaml));
+    /// assert!("xml".parse::<StorageFormat>().is_err());
+    /// ```
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            #[cfg(feature = "storage-json")]
+            "json" => Ok(Self::Json),
+            #[cfg(feature = "storage-toml")]
+            "toml" => Ok(Self::Toml),
+            #[cfg(feature = "storage-yaml")]
+            "yaml" | "yml" => Ok(Self::Yaml),
+            _ => Err(format!(
+                "unknown format \"{s}\", expected one of {}",
+                Self::ALL
+                    .iter()
+                    .map(|format| format.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for StorageFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Determines the [StorageFormat] of a path or returns an error
+fn format_of(path: &Path) -> Result<StorageFormat> {
+    StorageFormat::from_path(path)
+        .ok_or_else(|| StorageError::UnknownFormat(path.to_path_buf()).into())
+}
+
+/// Options of [save_with]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SaveOptions {
+    /// Keep a copy of the previous file with an additional `.bak` extension
+    pub backup: bool,
+    /// Store a checksum of the contents with an additional `.checksum` extension
+    ///
+    /// If present, the checksum is verified by [load].
+    pub checksum: bool,
+}
+
+/// Appends a suffix to the full file name of the path, eg. `selection.json.bak`
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// 64-bit [FNV-1a](https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function) hash
+pub(crate) fn checksum(contents: &[u8]) -> u64 {
+    contents.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Writes the contents to a temporary file next to the path and renames it afterwards
+///
+/// The file at the path thus either contains the old or the new contents but is never
+/// partially written.
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp = with_suffix(path, ".tmp");
+    let mut file = std::fs::File::create(&tmp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    std::fs::rename(tmp, path)
+}
+
+/// Save a value to the given path
+///
+/// The [StorageFormat] is determined by the extension of the path.
+/// The file is replaced atomically such that a crash never leaves it partially written.
+pub fn save<T: Serialize>(value: &T, path: impl AsRef<Path>) -> Result<()> {
+    save_with(value, path, &SaveOptions::default())
+}
+
+/// Variant of [save] which can keep a backup and store a checksum
+#[cfg_attr(feature = "storage-json", doc = "```")]
+#[cfg_attr(not(feature = "storage-json"), doc = "```ignore")]
+/// use opencl3_select::{load, save_with, SaveOptions, StorageFormat, UniquePriorityList};
+/// let path = std::env::temp_dir().join("opencl3_select_doctest_save_with.json");
+/// let options = SaveOptions { backup: true, checksum: true };
+/// save_with(&UniquePriorityList::from([1, 2]), &path, &options)?;
+/// save_with(&UniquePriorityList::from([3]), &path, &options)?;
+///
+/// let list: UniquePriorityList<u8> = load(&path)?;
+/// assert_eq!(list.priority_first(), Some(&3));
+/// let backup = std::fs::read_to_string(path.with_extension("json.bak")).unwrap();
+/// let backup: UniquePriorityList<u8> = StorageFormat::Json.decode(&backup)?;
+/// assert_eq!(backup.priority_first(), Some(&1));
+///
+/// // Changes which do not match the checksum are detected and the backup is loaded instead
+/// std::fs::write(&path, r#"{"selected":[4],"remaining":[]}"#).unwrap();
+/// let list: UniquePriorityList<u8> = load(&path)?;
+/// assert_eq!(list.priority_first(), Some(&1));
+///
+/// std::fs::remove_file(path.with_extension("json.bak")).unwrap();
+/// assert!(matches!(
+///     load::<UniquePriorityList<u8>>(&path),
+///     Err(opencl3_select::ClSelectError::Storage(
+///         opencl3_select::StorageError::Corrupt(_)
+///     ))
+/// ));
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+pub fn save_with<T: Serialize>(
+    value: &T,
+    path: impl AsRef<Path>,
+    options: &SaveOptions,
+) -> Result<()> {
+    let path = path.as_ref();
+    #[cfg(feature = "tracing")]
+    tracing::debug!(?path, ?options, "saving");
+    let contents = format_of(path)?.encode(value)?;
+    if options.backup && path.exists() {
+        let previous = std::fs::read(path).map_err(StorageError::Io)?;
+        write_atomic(&with_suffix(path, ".bak"), &previous).map_err(StorageError::Io)?;
+    }
+
+    // The old checksum goes first and the new one is written last, such that a crash in
+    // between leaves the contents unverified rather than marked as corrupt
+    let checksum_path = with_suffix(path, ".checksum");
+    if checksum_path.exists() {
+        std::fs::remove_file(&checksum_path).map_err(StorageError::Io)?;
+    }
+    write_atomic(path, contents.as_bytes()).map_err(StorageError::Io)?;
+    if options.checksum {
+        let checksum = format!("{:016x}\n", checksum(contents.as_bytes()));
+        write_atomic(&checksum_path, checksum.as_bytes()).map_err(StorageError::Io)?;
+    }
+    Ok(())
+}
+
+/// Load a value from the given path
+///
+/// The [StorageFormat] is determined by the extension of the path.
+/// If a checksum was stored by [save_with], it is verified before decoding and a
+/// mismatch is reported as [StorageError::Corrupt]. Contents which are corrupt or fail to
+/// decode are replaced by the backup of [save_with] if there is one.
+pub fn load<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T> {
+    let path = path.as_ref();
+    #[cfg(feature = "tracing")]
+    tracing::debug!(?path, "loading");
+    let format = format_of(path)?;
+    match load_verified(path, format) {
+        // A missing or unreadable file is no reason to fall back
+        Err(error @ crate::ClSelectError::Storage(StorageError::Io(_))) => Err(error),
+        Err(error) => {
+            let backup = with_suffix(path, ".bak");
+            if !backup.exists() {
+                return Err(error);
+            }
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?path, %error, "loading the backup instead");
+            let contents = std::fs::read_to_string(backup).map_err(StorageError::Io)?;
+            format.decode(&contents).map_err(|_| error)
+        }
+        value => value,
+    }
+}
+
+/// Loads a [ClState] which stands in for the OpenCL setup, eg. a snapshot saved by
+/// `opencl3-select list` on another machine
+///
+/// Contents which can not be decoded are reported as [StorageError::FixtureParse] naming the
+/// file, such that a broken fixture is told apart from a broken config or selection.
+#[cfg_attr(feature = "storage-json", doc = "```")]
+#[cfg_attr(not(feature = "storage-json"), doc = "```ignore")]
+/// use opencl3_select::{load_fixture, save, ClSelectError, ClState, StorageError};
+/// let path = std::env::temp_dir().join("opencl3_select_doctest_fixture.json");
+/// save(&ClState::from_platforms(vec![]), &path)?;
+/// assert!(load_fixture(&path)?.is_empty());
+///
+/// std::fs::write(&path, r#"{"platforms": 3}"#).unwrap();
+/// let error = load_fixture(&path).unwrap_err();
+/// assert!(matches!(
+///     &error,
+///     ClSelectError::Storage(StorageError::FixtureParse { path: error_path, .. })
+///         if error_path == &path
+/// ));
+/// # Ok::<(), ClSelectError>(())
+/// ```
+pub fn load_fixture(path: impl AsRef<Path>) -> Result<ClState> {
+    let path = path.as_ref();
+    let format = format_of(path)?;
+    let contents = std::fs::read_to_string(path).map_err(StorageError::Io)?;
+    format.decode(&contents).map_err(|error| {
+        let message = match std::error::Error::source(&error) {
+            Some(source) => source.to_string(),
+            None => error.to_string(),
+        };
+        StorageError::FixtureParse {
+            path: path.to_path_buf(),
+            message,
+        }
+        .into()
+    })
+}
+
+/// Loads the value from the path after checking the contents against a stored checksum
+fn load_verified<T: DeserializeOwned>(path: &Path, format: StorageFormat) -> Result<T> {
+    let contents = std::fs::read_to_string(path).map_err(StorageError::Io)?;
+    let checksum_path = with_suffix(path, ".checksum");
+    if checksum_path.exists() {
+        let expected = std::fs::read_to_string(checksum_path).map_err(StorageError::Io)?;
+        if u64::from_str_radix(expected.trim(), 16).ok() != Some(checksum(contents.as_bytes())) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?path, "contents do not match the stored checksum");
+            return Err(StorageError::Corrupt(path.to_path_buf()).into());
+        }
+    }
+    format.decode(&contents)
+}
+
+/// Columns of [to_csv], named like the serialized fields of [DeviceInfo]
+pub const DEFAULT_CSV_COLUMNS: [&str; 6] = [
+    "platform",
+    "name",
+    "vendor",
+    "type_text",
+    "version",
+    "global_mem_size",
+];
+
+/// Quotes a field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    match value.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+/// Writes one row per device with the [DEFAULT_CSV_COLUMNS]
+///
+/// See [to_csv_with] to choose the columns.
+pub fn to_csv(state: &ClState) -> Result<String> {
+    to_csv_with(state, &DEFAULT_CSV_COLUMNS)
+}
+
+/// Writes one row per device with the given columns preceded by a header row
+///
+/// Columns are named like the serialized fields of [DeviceInfo], the column `id` holds the
+/// [DeviceId](crate::DeviceId). Properties which were not queried are left empty, lists such as
+/// `uuid` are written as JSON.
+/// ```
+/// use opencl3_select::{to_csv_with, ClState};
+/// let state: ClState = serde_json::from_str(r#"{"platforms": [{
+///     "name": "Oclgrind", "version": "OpenCL 1.2", "vendor": "University of Bristol",
+///     "profile": "FULL_PROFILE", "extensions": "", "devices": [{
+///         "platform": "Oclgrind", "vendor": "University of Bristol", "vendor_id": 0,
+///         "vendor_id_text": "", "name": "Oclgrind Simulator", "version": "OpenCL 1.2",
+///         "type": 8, "type_text": "CL_DEVICE_TYPE_ACCELERATOR", "global_mem_size": 134217728
+///     }]
+/// }]}"#)?;
+/// assert_eq!(
+///     to_csv_with(&state, &["name", "vendor", "global_mem_size", "profile"])?,
+///     "name,vendor,global_mem_size,profile\n\
+///      Oclgrind Simulator,University of Bristol,134217728,\n"
+/// );
+/// assert!(to_csv_with(&state, &["colour"]).is_err());
+/// let id = state.get_all_devices()[0].id();
+/// assert_eq!(to_csv_with(&state, &["id"])?, format!("id\n{id}\n"));
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+pub fn to_csv_with(state: &ClState, columns: &[impl AsRef<str>]) -> Result<String> {
+    let header: Vec<_> = columns
+        .iter()
+        .map(|column| csv_field(column.as_ref()))
+        .collect();
+    let mut csv = header.join(",") + "\n";
+    for device in state.get_all_devices() {
+        let value = serde_json::to_value(&device)?;
+        let row = columns
+            .iter()
+            .map(|column| {
+                let column = column.as_ref();
+                let cell = match value.get(column) {
+                    None if column == "id" => device.id().to_string(),
+                    None => return Err(StorageError::UnknownColumn(column.to_string()).into()),
+                    Some(serde_json::Value::Null) => String::new(),
+                    Some(serde_json::Value::String(text)) => text.clone(),
+                    Some(other) => other.to_string(),
+                };
+                Ok(csv_field(&cell))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        csv += &(row.join(",") + "\n");
+    }
+    Ok(csv)
+}
+
+/// A selection which was made at some point in time
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HistoryEntry {
+    /// Time of the selection in seconds since the unix epoch
+    pub timestamp: u64,
+    /// The selected devices
+    pub selection: UniquePriorityList<DeviceInfo>,
+    /// Preferred order of platforms which provide the same device
+    #[serde(default)]
+    pub platforms: UniquePriorityList<PlatformInfo>,
+}
+
+/// The most recent selections, newest first
+/// ```
+/// use opencl3_select::{SelectionHistory, UniquePriorityList};
+/// let mut history = SelectionHistory::default();
+/// history.push(UniquePriorityList::from([]), UniquePriorityList::new(), 2);
+/// history.push(UniquePriorityList::from([]), UniquePriorityList::new(), 2);
+/// assert_eq!(history.entries.len(), 1);
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct SelectionHistory {
+    /// Stored selections ordered from newest to oldest
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl SelectionHistory {
+    /// Loads the history from the given path or returns an empty history if it does not exist
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match path.exists() {
+            true => load(path),
+            false => Ok(Self::default()),
+        }
+    }
+
+    /// Adds a selection as the newest entry and keeps at most `max_len` entries
+    ///
+    /// If the selection and platform priorities equal the newest entry, only its timestamp
+    /// is updated.
+    pub fn push(
+        &mut self,
+        selection: UniquePriorityList<DeviceInfo>,
+        platforms: UniquePriorityList<PlatformInfo>,
+        max_len: usize,
+    ) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        if self
+            .entries
+            .first()
+            .is_some_and(|entry| entry.selection == selection && entry.platforms == platforms)
+        {
+            self.entries.remove(0);
+        }
+        self.entries.insert(
+            0,
+            HistoryEntry {
+                timestamp,
+                selection,
+                platforms,
+            },
+        );
+        self.entries.truncate(max_len);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            entries = self.entries.len(),
+            "recorded selection in history"
+        );
+    }
+}
+
+/// Asynchronous variant of [save] which writes on a blocking thread of the [tokio] runtime
+#[cfg(feature = "tokio")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "tokio")))]
+pub async fn save_async<T>(value: T, path: impl AsRef<Path>) -> Result<()>
+where
+    T: Serialize + Send + 'static,
+{
+    let path = path.as_ref().to_path_buf();
+    tokio::task::spawn_blocking(move || save(&value, path)).await?
+}
+
+/// Asynchronous variant of [load] which reads on a blocking thread of the [tokio] runtime
+#[cfg(feature = "tokio")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "tokio")))]
+pub async fn load_async<T>(path: impl AsRef<Path>) -> Result<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let path = path.as_ref().to_path_buf();
+    tokio::task::spawn_blocking(move || load(path)).await?
+}
@@ -0,0 +1,132 @@
+//! Aggregated overview of all platforms and devices of a [ClState]
+
+use std::collections::{BTreeMap, BTreeSet};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::clinfo::CL_DEVICE_TYPE_GPU;
+use crate::{ClState, OpenClVersion};
+
+/// Aggregated overview of a [ClState]
+///
+/// The [Display](std::fmt::Display) implementation renders a plain-text report.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Summary {
+    /// Number of platforms
+    pub n_platforms: usize,
+    /// Names of all devices in the order of the columns of [extensions](Summary::extensions)
+    pub devices: Vec<String>,
+    /// Number of devices per (human-readable) device type
+    pub devices_by_type: BTreeMap<String, usize>,
+    /// Number of devices per vendor
+    pub devices_by_vendor: BTreeMap<String, usize>,
+    /// Sum of the global memory of all GPUs in bytes
+    pub total_gpu_memory: u64,
+    /// Lowest OpenCL version supported by any device
+    pub min_version: Option<OpenClVersion>,
+    /// Highest OpenCL version supported by any device
+    pub max_version: Option<OpenClVersion>,
+    /// For every extension whether it is supported by the n-th device
+    pub extensions: BTreeMap<String, Vec<bool>>,
+}
+
+impl ClState {
+    /// Aggregates all platforms and devices into a [Summary]
+    pub fn summary(&self) -> Summary {
+        let devices = self.get_all_devices();
+        let mut summary = Summary {
+            n_platforms: self.platforms_ref().len(),
+            devices: devices.iter().map(|device| device.name()).collect(),
+            ..Default::default()
+        };
+
+        for device in devices.iter() {
+            *summary
+                .devices_by_type
+                .entry(device.type_text())
+                .or_default() += 1;
+            *summary
+                .devices_by_vendor
+                .entry(device.vendor())
+                .or_default() += 1;
+            if device.r#type() & CL_DEVICE_TYPE_GPU != 0 {
+                summary.total_gpu_memory += device.global_mem_size().unwrap_or(0);
+            }
+        }
+
+        let versions = devices.iter().filter_map(|device| device.opencl_version());
+        summary.min_version = versions.clone().min();
+        summary.max_version = versions.max();
+
+        let supported: Vec<BTreeSet<String>> = devices
+            .iter()
+            .map(|device| {
+                device
+                    .extensions_ref()
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .map(String::from)
+                    .collect()
+            })
+            .collect();
+        for extension in supported.iter().flatten() {
+            summary
+                .extensions
+                .entry(extension.clone())
+                .or_insert_with(|| supported.iter().map(|s| s.contains(extension)).collect());
+        }
+        summary
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} devices on {} platforms",
+            self.devices.len(),
+            self.n_platforms
+        )?;
+
+        writeln!(f, "\nDevices by type")?;
+        for (device_type, count) in self.devices_by_type.iter() {
+            writeln!(f, "  {device_type:<32} {count}")?;
+        }
+        writeln!(f, "\nDevices by vendor")?;
+        for (vendor, count) in self.devices_by_vendor.iter() {
+            writeln!(f, "  {vendor:<32} {count}")?;
+        }
+
+        writeln!(
+            f,
+            "\nTotal GPU memory: {}",
+            crate::Bytes(self.total_gpu_memory)
+        )?;
+        if let (Some(min), Some(max)) = (self.min_version, self.max_version) {
+            writeln!(f, "OpenCL versions: {min} - {max}")?;
+        }
+
+        writeln!(f, "\nDevices")?;
+        for (i, name) in self.devices.iter().enumerate() {
+            writeln!(f, "  D{i:<3} {name}")?;
+        }
+
+        // Matrix with one row per extension and one column per device
+        let width = self.extensions.keys().map(String::len).max().unwrap_or(0);
+        write!(f, "\n{:width$}", "Extensions", width = width + 2)?;
+        for i in 0..self.devices.len() {
+            write!(f, " D{i:<3}")?;
+        }
+        writeln!(f)?;
+        for (extension, supported) in self.extensions.iter() {
+            write!(f, "  {extension:width$}")?;
+            for &s in supported.iter() {
+                write!(f, " {:<4}", if s { "✓" } else { "·" })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
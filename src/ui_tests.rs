@@ -0,0 +1,306 @@
+//! Snapshots of the TUI rendered into a [TestBackend] for scripted key presses
+//!
+//! The App is driven with synthetic [ClState]s, such that the tests need neither a terminal
+//! nor OpenCL devices. Rendered buffers are compared to the files in `tests/snapshots`, run
+//! the tests with `UPDATE_SNAPSHOTS=1` to write them after an intended change of the UI.
+//!
+//! The [App] is part of the binary and not of the library, so the suite is compiled into the
+//! binary instead of living under `tests/` where only the library could be reached.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use opencl3_select::{ClState, DeviceInfo, PlatformInfo, SelectionHistory};
+use ratatui::{backend::TestBackend, style::Color, Terminal};
+
+use crate::config::{ColorDepth, Config, Theme};
+use crate::keymap::Action;
+use crate::App;
+
+/// Size of the terminal the snapshots are rendered into
+const SIZE: (u16, u16) = (100, 30);
+
+/// Two platforms with three devices of different types
+fn fixture() -> ClState {
+    fixture_with_driver("1.0")
+}
+
+/// The [fixture] with the given driver version on all devices
+fn fixture_with_driver(driver_version: &str) -> ClState {
+    let device = |vendor: &str, name: &str, r#type| {
+        DeviceInfo::builder()
+            .name(name)
+            .vendor(vendor)
+            .version("OpenCL 3.0")
+            .r#type(r#type)
+            .driver_version(driver_version)
+            .global_mem_size(8u64 << 30)
+            .build()
+    };
+    ClState::from_platforms(vec![
+        PlatformInfo::builder()
+            .name("NVIDIA CUDA")
+            .vendor("NVIDIA Corporation")
+            .version("OpenCL 3.0 CUDA 12.4")
+            .device(device(
+                "NVIDIA Corporation",
+                "NVIDIA GeForce RTX 3060",
+                1 << 2,
+            ))
+            .build(),
+        PlatformInfo::builder()
+            .name("Portable Computing Language")
+            .vendor("The pocl project")
+            .version("OpenCL 3.0 PoCL 5.0")
+            .device(device(
+                "AuthenticAMD",
+                "cpu-znver3-AMD Ryzen 7 5800X",
+                1 << 1,
+            ))
+            .device(device("GenuineIntel", "cpu-skylake-Intel Xeon E5", 1 << 1))
+            .build(),
+    ])
+}
+
+/// App showing the given state with the default config
+fn app(cl_state: &ClState) -> App {
+    App::new(cl_state, &Config::default(), SelectionHistory::default())
+}
+
+/// Sends the keys to the App as if they were typed
+fn press(app: &mut App, keys: &[KeyCode]) {
+    for &code in keys {
+        assert_eq!(
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE)),
+            None
+        );
+    }
+}
+
+/// Renders the App and returns the symbols of the buffer line by line
+fn render(app: &mut App) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(SIZE.0, SIZE.1)).unwrap();
+    app.draw(&mut terminal).unwrap();
+    let buffer = terminal.backend().buffer();
+    let mut lines = String::new();
+    for y in 0..buffer.area.height {
+        let line: String = (0..buffer.area.width)
+            .map(|x| buffer.get(x, y).symbol())
+            .collect();
+        lines += line.trim_end();
+        lines += "\n";
+    }
+    lines
+}
+
+/// Compares the rendered App to the snapshot of the given name
+fn assert_snapshot(name: &str, app: &mut App) {
+    let rendered = render(app);
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.txt"));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &rendered).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|error| {
+        panic!("missing snapshot {path:?}, run with UPDATE_SNAPSHOTS=1 to write it: {error}")
+    });
+    assert!(
+        rendered == expected,
+        "{name} differs from its snapshot {path:?}\n{rendered}"
+    );
+}
+
+#[test]
+fn platform_list() {
+    let mut app = app(&fixture());
+    assert_snapshot("platform_list", &mut app);
+}
+
+#[test]
+fn device_list() {
+    let mut app = app(&fixture());
+    press(&mut app, &[KeyCode::Down, KeyCode::Right]);
+    assert_snapshot("device_list", &mut app);
+}
+
+#[test]
+fn detail_pane() {
+    let mut app = app(&fixture());
+    press(&mut app, &[KeyCode::Right, KeyCode::Right]);
+    assert_snapshot("detail_pane", &mut app);
+}
+
+#[test]
+fn summary_overlay() {
+    let mut app = app(&fixture());
+    press(&mut app, &[KeyCode::Char('S')]);
+    assert_snapshot("summary_overlay", &mut app);
+}
+
+#[test]
+fn help_overlay() {
+    let mut app = app(&fixture());
+    press(&mut app, &[KeyCode::Char('?')]);
+    assert_snapshot("help_overlay", &mut app);
+    press(&mut app, &[KeyCode::Esc]);
+    assert_eq!(app.help_scroll, None);
+}
+
+#[test]
+fn priorities() {
+    let mut app = app(&fixture());
+    press(
+        &mut app,
+        &[KeyCode::Right, KeyCode::Char('1'), KeyCode::Tab],
+    );
+    assert_snapshot("priorities", &mut app);
+}
+
+#[test]
+fn ascii_fallback() {
+    let mut app = app(&fixture());
+    app.unicode = false;
+    assert_snapshot("ascii_fallback", &mut app);
+}
+
+#[test]
+fn empty_state() {
+    let mut app = app(&ClState::from_platforms(vec![]));
+    assert_snapshot("empty_state", &mut app);
+}
+
+#[test]
+fn navigation_without_devices() {
+    use KeyCode::*;
+    let keys = [
+        Char('j'),
+        Char('k'),
+        Char('g'),
+        Char('G'),
+        PageUp,
+        PageDown,
+        Tab,
+        Right,
+        Char('j'),
+        Char('G'),
+        PageDown,
+        BackTab,
+        Left,
+        Char('1'),
+    ];
+    for cl_state in [
+        ClState::from_platforms(vec![]),
+        ClState::from_platforms(vec![PlatformInfo::builder().name("Empty").build()]),
+    ] {
+        let mut app = app(&cl_state);
+        press(&mut app, &keys);
+        render(&mut app);
+        // Enter asks before overwriting an output file and otherwise accepts the empty selection
+        let enter = app.handle_key(KeyEvent::new(Enter, KeyModifiers::NONE));
+        assert!(matches!(enter, None | Some(true)));
+    }
+}
+
+#[test]
+fn abort_and_accept() {
+    let mut app = app(&fixture());
+    let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+    assert_eq!(app.handle_key(key(KeyCode::Char('q'))), Some(false));
+    assert_eq!(app.handle_key(key(KeyCode::Enter)), Some(true));
+}
+
+#[test]
+fn divider_stays_in_range() {
+    let mut app = app(&fixture());
+    press(&mut app, &[KeyCode::Char('H'); 20]);
+    assert_eq!(app.divider_percentage, 10);
+    press(&mut app, &[KeyCode::Char('L'); 20]);
+    assert_eq!(app.divider_percentage, 90);
+    press(&mut app, &[KeyCode::Char('=')]);
+    assert_eq!(app.divider_percentage, Config::default().divider_percentage);
+}
+
+#[test]
+fn basic_colors_stay_readable() {
+    let theme = Theme::default().with_color_depth(ColorDepth::Basic);
+    let colors = [theme.text, theme.selected_fg, theme.selected_fg_light];
+    for color in colors {
+        assert!(!matches!(
+            color,
+            Color::Rgb(..) | Color::DarkGray | Color::Black
+        ));
+    }
+    assert_eq!(theme.normal_row, Color::Black);
+
+    let theme = Theme::default().with_color_depth(ColorDepth::Ansi256);
+    assert!(matches!(theme.alt_row, Color::Indexed(232..=255)));
+}
+
+#[test]
+fn missing_device() {
+    let mut app = app(&fixture());
+    press(&mut app, &[KeyCode::Right, KeyCode::Char('1')]);
+    let without_nvidia = ClState::from_platforms(fixture().platforms_ref()[1..].to_vec());
+    assert_eq!(app.set_state(without_nvidia, true), 1);
+    assert!(app.selected_devices().is_empty());
+    assert_eq!(app.selected_count(), 1);
+    assert_snapshot("missing_device", &mut app);
+
+    press(&mut app, &[KeyCode::Char('P')]);
+    assert_eq!(app.items.items.len(), 1);
+    assert_eq!(app.selected_count(), 0);
+    assert_eq!(app.items.state.selected(), Some(0));
+}
+
+#[test]
+fn note_popup() {
+    let mut app = app(&fixture());
+    press(&mut app, &[KeyCode::Right, KeyCode::Char('n')]);
+    press(
+        &mut app,
+        &"shared with display"
+            .chars()
+            .map(KeyCode::Char)
+            .collect::<Vec<_>>(),
+    );
+    assert_snapshot("note_popup", &mut app);
+
+    press(&mut app, &[KeyCode::Enter]);
+    let device = &fixture().get_all_devices()[0];
+    assert_eq!(app.notes.note_of(device), Some("shared with display"));
+    app.toasts.clear();
+    assert_snapshot("note_detail", &mut app);
+}
+
+#[test]
+fn changed_device_is_not_missing() {
+    let mut app = app(&fixture());
+    press(&mut app, &[KeyCode::Right, KeyCode::Char('1')]);
+    assert_eq!(app.set_state(fixture_with_driver("2.0"), true), 0);
+    assert_eq!(app.selected_devices()[0].driver_version_ref(), Some("2.0"));
+    let devices = app
+        .items
+        .items
+        .iter()
+        .map(|platform| platform.devices.items.len());
+    assert_eq!(devices.sum::<usize>(), 3);
+}
+
+#[test]
+fn keys_and_bindings_are_exclusive() {
+    let path = std::env::temp_dir().join("opencl3_select_ui_tests_keys.toml");
+    std::fs::write(&path, "[keys]\nn = \"j\"\n").unwrap();
+    let config = Config::load(Some(&path)).unwrap();
+    let app = App::new(&fixture(), &config, SelectionHistory::default());
+    assert_eq!(
+        app.keymap
+            .action(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)),
+        Some(Action::Down)
+    );
+
+    std::fs::write(&path, "[keys]\nn = \"j\"\n\n[bindings]\nC-n = \"up\"\n").unwrap();
+    assert!(Config::load(Some(&path)).is_err());
+    std::fs::remove_file(path).unwrap();
+}
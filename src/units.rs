@@ -0,0 +1,58 @@
+//! Quantities with units and their human-readable formatting
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Amount of memory, displayed with a binary unit
+///
+/// Serialized as the plain number of bytes.
+/// ```
+/// use opencl3_select::Bytes;
+/// assert_eq!(Bytes(16 << 30).to_string(), "16.0 GiB");
+/// assert_eq!(Bytes(1536).to_string(), "1.5 KiB");
+/// assert_eq!(Bytes(64).to_string(), "64 B");
+/// assert_eq!(format!("{:>9}", Bytes(64)), "     64 B");
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(transparent))]
+pub struct Bytes(pub u64);
+
+impl From<u64> for Bytes {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+impl std::fmt::Display for Bytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+        let mut value = self.0 as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        match unit {
+            0 => f.pad(&format!("{} B", self.0)),
+            _ => f.pad(&format!("{value:.1} {}", UNITS[unit])),
+        }
+    }
+}
+
+/// Formats a byte count with a binary unit, eg. `16.0 GiB`, see [Bytes]
+pub fn format_bytes(bytes: u64) -> String {
+    Bytes(bytes).to_string()
+}
+
+/// Formats a clock frequency given in MHz as reported by OpenCL, eg. `1.80 GHz`
+/// ```
+/// use opencl3_select::format_frequency;
+/// assert_eq!(format_frequency(1800), "1.80 GHz");
+/// assert_eq!(format_frequency(875), "875 MHz");
+/// ```
+pub fn format_frequency(megahertz: u32) -> String {
+    match megahertz {
+        0..=999 => format!("{megahertz} MHz"),
+        _ => format!("{:.2} GHz", f64::from(megahertz) / 1000.0),
+    }
+}
@@ -0,0 +1,310 @@
+//! Current load of GPUs such that idle ones can be picked on shared machines
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Bytes;
+#[cfg(feature = "sysinfo")]
+use crate::DeviceInfo;
+
+/// Utilization and memory usage of a device at the time it was queried
+/// ```
+/// use opencl3_select::DeviceUsage;
+/// let usage = DeviceUsage {
+///     utilization: Some(40),
+///     memory_used: Some(3 << 30),
+///     memory_total: Some(12 << 30),
+/// };
+/// assert_eq!(usage.gauge(5), "▰▰▱▱▱ 40%");
+/// assert_eq!(usage.to_string(), "40% busy, 3.0 GiB of 12.0 GiB used");
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct DeviceUsage {
+    /// Percentage of time during which kernels were running recently
+    pub utilization: Option<u8>,
+    /// Bytes of device memory in use by any process
+    pub memory_used: Option<u64>,
+    /// Bytes of device memory in total
+    pub memory_total: Option<u64>,
+}
+
+impl DeviceUsage {
+    /// Fraction of the device memory in use
+    pub fn memory_fraction(&self) -> Option<f64> {
+        match (self.memory_used, self.memory_total) {
+            (Some(used), Some(total)) if total > 0 => Some(used as f64 / total as f64),
+            _ => None,
+        }
+    }
+
+    /// Load of the device as a fraction, the utilization if known and the memory usage otherwise
+    pub fn load(&self) -> Option<f64> {
+        self.utilization
+            .map(|utilization| f64::from(utilization) / 100.0)
+            .or_else(|| self.memory_fraction())
+    }
+
+    /// Bar of `width` cells filled according to the [load](DeviceUsage::load), eg. `▰▰▱▱▱ 40%`
+    pub fn gauge(&self, width: usize) -> String {
+        let Some(load) = self.load() else {
+            return String::new();
+        };
+        let load = load.clamp(0.0, 1.0);
+        let filled = (load * width as f64).round() as usize;
+        format!(
+            "{}{} {:.0}%",
+            "▰".repeat(filled),
+            "▱".repeat(width - filled),
+            100.0 * load
+        )
+    }
+}
+
+impl std::fmt::Display for DeviceUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = vec![];
+        if let Some(utilization) = self.utilization {
+            parts.push(format!("{utilization}% busy"));
+        }
+        if let (Some(used), Some(total)) = (self.memory_used, self.memory_total) {
+            parts.push(format!("{} of {} used", Bytes(used), Bytes(total)));
+        }
+        match parts.is_empty() {
+            true => write!(f, "unknown"),
+            false => write!(f, "{}", parts.join(", ")),
+        }
+    }
+}
+
+/// Queries the current usage of the devices, `None` for devices whose usage is unknown
+///
+/// NVIDIA GPUs are queried via NVML, which is loaded at runtime such that machines without the
+/// NVIDIA driver are not affected, and matched by their UUID. AMD GPUs are read from the sysfs of
+/// the `amdgpu` driver, which can only be attributed to a device if there is a single AMD card.
+/// Other devices are not supported.
+/// ```no_run
+/// let devices = opencl3_select::get_setup()?.get_all_devices();
+/// let usage = opencl3_select::device_usage(&devices);
+/// for (device, usage) in devices.iter().zip(usage) {
+///     println!("{}: {}", device.name(), usage.unwrap_or_default());
+/// }
+/// # Ok::<(), opencl3_select::ClSelectError>(())
+/// ```
+#[cfg(feature = "sysinfo")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "sysinfo")))]
+pub fn device_usage(devices: &[DeviceInfo]) -> Vec<Option<DeviceUsage>> {
+    #[cfg(unix)]
+    let nvml = nvml::Nvml::load();
+    let amd = amd_card();
+    devices
+        .iter()
+        .map(|device| match device.vendor_id() {
+            #[cfg(unix)]
+            NVIDIA => nvml.as_ref()?.usage(&device.uuid()?),
+            AMD if device.type_text_ref().ends_with("GPU") => amd.as_deref().and_then(amd_usage),
+            _ => None,
+        })
+        .collect()
+}
+
+/// PCI vendor id of NVIDIA
+#[cfg(feature = "sysinfo")]
+const NVIDIA: u32 = 0x10de;
+
+/// PCI vendor id of AMD
+#[cfg(feature = "sysinfo")]
+const AMD: u32 = 0x1002;
+
+/// Sysfs directory of the only AMD card, `None` if there is none or several
+#[cfg(feature = "sysinfo")]
+fn amd_card() -> Option<std::path::PathBuf> {
+    let mut cards = std::fs::read_dir("/sys/class/drm")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path().join("device"))
+        .filter(|device| {
+            let name = device.parent().and_then(|card| card.file_name());
+            // Skip connectors such as card0-DP-1 which link to the same device
+            name.and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("card"))
+                .is_some_and(|index| index.chars().all(|c| c.is_ascii_digit()))
+                && std::fs::read_to_string(device.join("vendor"))
+                    .is_ok_and(|vendor| vendor.trim() == format!("{AMD:#06x}"))
+        });
+    let card = cards.next()?;
+    cards.next().is_none().then_some(card)
+}
+
+/// Reads the usage of an AMD card from the sysfs of the `amdgpu` driver
+#[cfg(feature = "sysinfo")]
+fn amd_usage(card: &std::path::Path) -> Option<DeviceUsage> {
+    let read = |file: &str| -> Option<u64> {
+        std::fs::read_to_string(card.join(file))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    let usage = DeviceUsage {
+        utilization: read("gpu_busy_percent").and_then(|percent| u8::try_from(percent).ok()),
+        memory_used: read("mem_info_vram_used"),
+        memory_total: read("mem_info_vram_total"),
+    };
+    (usage != DeviceUsage::default()).then_some(usage)
+}
+
+/// Minimal bindings to the NVIDIA Management Library, loaded at runtime
+#[cfg(all(feature = "sysinfo", unix))]
+mod nvml {
+    use std::ffi::{c_char, c_int, c_uint, c_void, CStr, CString};
+
+    use super::DeviceUsage;
+
+    /// Opaque handle of a device
+    type Device = *mut c_void;
+
+    /// `nvmlUtilization_t`
+    #[repr(C)]
+    #[derive(Default)]
+    #[allow(dead_code)] // Written by NVML
+    struct Utilization {
+        /// Percentage of time during which kernels were running
+        gpu: c_uint,
+        /// Percentage of time during which memory was read or written
+        memory: c_uint,
+    }
+
+    /// `nvmlMemory_t`
+    #[repr(C)]
+    #[derive(Default)]
+    #[allow(dead_code)] // Written by NVML
+    struct Memory {
+        /// Installed memory in bytes
+        total: u64,
+        /// Unallocated memory in bytes
+        free: u64,
+        /// Allocated memory in bytes
+        used: u64,
+    }
+
+    /// Loaded and initialized NVML, shut down when dropped
+    pub(super) struct Nvml {
+        /// Handle returned by `dlopen`
+        library: *mut c_void,
+        /// `nvmlDeviceGetHandleByUUID`
+        handle_by_uuid: unsafe extern "C" fn(*const c_char, *mut Device) -> c_int,
+        /// `nvmlDeviceGetUtilizationRates`
+        utilization_rates: unsafe extern "C" fn(Device, *mut Utilization) -> c_int,
+        /// `nvmlDeviceGetMemoryInfo`
+        memory_info: unsafe extern "C" fn(Device, *mut Memory) -> c_int,
+        /// `nvmlShutdown`
+        shutdown: unsafe extern "C" fn() -> c_int,
+    }
+
+    /// Return code of successful NVML calls
+    const SUCCESS: c_int = 0;
+
+    impl Nvml {
+        /// Loads and initializes NVML, `None` if the NVIDIA driver is not installed
+        pub(super) fn load() -> Option<Self> {
+            // SAFETY: the symbols are looked up by their documented names and transmuted to
+            // their documented signatures, the library stays loaded until `self` is dropped
+            unsafe {
+                let library = libc::dlopen(c"libnvidia-ml.so.1".as_ptr(), libc::RTLD_NOW);
+                if library.is_null() {
+                    return None;
+                }
+                let symbol = |name: &CStr| {
+                    let symbol = libc::dlsym(library, name.as_ptr());
+                    (!symbol.is_null()).then_some(symbol)
+                };
+                let symbols = (|| {
+                    Some((
+                        symbol(c"nvmlInit_v2")?,
+                        symbol(c"nvmlDeviceGetHandleByUUID")?,
+                        symbol(c"nvmlDeviceGetUtilizationRates")?,
+                        symbol(c"nvmlDeviceGetMemoryInfo")?,
+                        symbol(c"nvmlShutdown")?,
+                    ))
+                })();
+                let Some((init, handle_by_uuid, utilization_rates, memory_info, shutdown)) =
+                    symbols
+                else {
+                    libc::dlclose(library);
+                    return None;
+                };
+                let init =
+                    std::mem::transmute::<*mut c_void, unsafe extern "C" fn() -> c_int>(init);
+                if init() != SUCCESS {
+                    libc::dlclose(library);
+                    return None;
+                }
+                Some(Self {
+                    library,
+                    handle_by_uuid: std::mem::transmute::<
+                        *mut c_void,
+                        unsafe extern "C" fn(*const c_char, *mut Device) -> c_int,
+                    >(handle_by_uuid),
+                    utilization_rates: std::mem::transmute::<
+                        *mut c_void,
+                        unsafe extern "C" fn(Device, *mut Utilization) -> c_int,
+                    >(utilization_rates),
+                    memory_info: std::mem::transmute::<
+                        *mut c_void,
+                        unsafe extern "C" fn(Device, *mut Memory) -> c_int,
+                    >(memory_info),
+                    shutdown: std::mem::transmute::<*mut c_void, unsafe extern "C" fn() -> c_int>(
+                        shutdown,
+                    ),
+                })
+            }
+        }
+
+        /// Usage of the device with the given UUID as reported by `cl_khr_device_uuid`
+        pub(super) fn usage(&self, uuid: &[u8; 16]) -> Option<DeviceUsage> {
+            let uuid = CString::new(format_uuid(uuid)).ok()?;
+            let mut device: Device = std::ptr::null_mut();
+            let mut utilization = Utilization::default();
+            let mut memory = Memory::default();
+            // SAFETY: all pointers are valid for the duration of the calls
+            unsafe {
+                if (self.handle_by_uuid)(uuid.as_ptr(), &mut device) != SUCCESS {
+                    return None;
+                }
+                let utilization = ((self.utilization_rates)(device, &mut utilization) == SUCCESS)
+                    .then(|| u8::try_from(utilization.gpu).unwrap_or(100));
+                let memory = ((self.memory_info)(device, &mut memory) == SUCCESS)
+                    .then_some((memory.used, memory.total));
+                Some(DeviceUsage {
+                    utilization,
+                    memory_used: memory.map(|(used, _)| used),
+                    memory_total: memory.map(|(_, total)| total),
+                })
+            }
+        }
+    }
+
+    impl Drop for Nvml {
+        fn drop(&mut self) {
+            // SAFETY: NVML was initialized in `load` and is not used afterwards
+            unsafe {
+                (self.shutdown)();
+                libc::dlclose(self.library);
+            }
+        }
+    }
+
+    /// Formats the UUID the way NVML expects it, eg. `GPU-6b7f4a11-0e2c-...`
+    fn format_uuid(uuid: &[u8; 16]) -> String {
+        let hex: String = uuid.iter().map(|byte| format!("{byte:02x}")).collect();
+        format!(
+            "GPU-{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    }
+}
@@ -0,0 +1,71 @@
+//! Correlate OpenCL devices with Vulkan physical devices
+//!
+//! Applications which use both OpenCL and Vulkan (eg. via [wgpu](https://wgpu.rs)) can use the
+//! identifiers of `cl_khr_device_uuid` to find the Vulkan physical device which belongs to a
+//! selected OpenCL device. The identifiers on the Vulkan side are obtained from
+//! `VkPhysicalDeviceIDProperties`.
+
+use crate::DeviceInfo;
+
+/// Identifiers of a Vulkan physical device as reported by `VkPhysicalDeviceIDProperties`
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct VulkanDeviceId {
+    /// Value of `deviceUUID`
+    pub uuid: [u8; 16],
+    /// Value of `deviceLUID` if `deviceLUIDValid` is set
+    pub luid: Option<[u8; 8]>,
+}
+
+impl DeviceInfo {
+    /// UUID under which the device is known to Vulkan
+    ///
+    /// Returns [None] if the device does not support `cl_khr_device_uuid`.
+    pub fn vulkan_uuid(&self) -> Option<[u8; 16]> {
+        self.uuid()
+    }
+
+    /// Checks if the Vulkan physical device refers to this device
+    ///
+    /// Devices are matched by their UUID and, if both sides report one, by their LUID.
+    pub fn matches_vulkan(&self, vulkan_device: &VulkanDeviceId) -> bool {
+        let uuid_matches = self.uuid() == Some(vulkan_device.uuid);
+        let luid_matches = match (self.luid(), vulkan_device.luid) {
+            (Some(luid), Some(vulkan_luid)) => luid == vulkan_luid,
+            _ => false,
+        };
+        uuid_matches || luid_matches
+    }
+}
+
+/// Finds the Vulkan physical device for each of the given OpenCL devices
+///
+/// The result contains the index into `vulkan_devices` at the position of the corresponding
+/// OpenCL device or [None] if no Vulkan device matches.
+/// ```
+/// use opencl3_select::{match_vulkan_devices, DeviceInfo, VulkanDeviceId};
+/// let device: DeviceInfo = serde_json::from_str(r#"{
+///     "vendor": "Oclgrind", "vendor_id": 0, "vendor_id_text": "",
+///     "name": "Oclgrind Simulator", "version": "OpenCL 1.2", "type": 4, "type_text": "GPU",
+///     "profile": "FULL_PROFILE", "extensions": "cl_khr_device_uuid", "opencl_c_version": "",
+///     "svm_mem_capability": 0, "uuid": [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]
+/// }"#)?;
+/// let vulkan_devices = [
+///     VulkanDeviceId { uuid: [0; 16], luid: None },
+///     VulkanDeviceId { uuid: device.vulkan_uuid().unwrap(), luid: None },
+/// ];
+/// assert_eq!(match_vulkan_devices(&[device], &vulkan_devices), [Some(1)]);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn match_vulkan_devices(
+    devices: &[DeviceInfo],
+    vulkan_devices: &[VulkanDeviceId],
+) -> Vec<Option<usize>> {
+    devices
+        .iter()
+        .map(|device| {
+            vulkan_devices
+                .iter()
+                .position(|vulkan_device| device.matches_vulkan(vulkan_device))
+        })
+        .collect()
+}